@@ -0,0 +1,668 @@
+use crate::args::{CliAddKind, CliAddSubCommand};
+use crate::config::{ConfigError, StrixConfig, StrixConfigPackType, STRIX_CONFIG};
+use crate::error::{ExitCode, IoError};
+use crate::fmt;
+use crate::fmt::FmtConfig;
+use json_comments::StripComments;
+use log::{info, warn};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AddError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(
+        "Invalid identifier {0:?}, only lowercase letters, digits and underscores are allowed"
+    )]
+    InvalidIdentifier(String),
+    #[error(
+        "Couldn't find a {0:?} project in {STRIX_CONFIG:?}, it is required for this generator"
+    )]
+    MissingProject(StrixConfigPackType),
+    #[error("{0:?} already exists, use --force to overwrite it")]
+    Exists(PathBuf),
+    #[error("An unexpected Error occurred while trying to deserialize {path:?}, Err: {source}")]
+    Deserialize {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("Expected {0:?} to contain a JSON object")]
+    NotAnObject(PathBuf),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl AddError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            AddError::Config(_) | AddError::MissingProject(_) => ExitCode::Config,
+            AddError::InvalidIdentifier(_)
+            | AddError::Deserialize { .. }
+            | AddError::NotAnObject(_) => ExitCode::Validation,
+            AddError::Exists(_) | AddError::Io(_) => ExitCode::Io,
+        }
+    }
+}
+
+struct AddContext {
+    namespace: String,
+    name: String,
+    identifier: String,
+    display_name: String,
+    behaviour: Option<PathBuf>,
+    resource: Option<PathBuf>,
+    force: bool,
+    fmt: FmtConfig,
+    /// New lang keys go into `texts/<primary_language>.lang`
+    primary_language: String,
+}
+
+fn find_project(config: &StrixConfig, pack_type: StrixConfigPackType) -> Option<PathBuf> {
+    let mut projects: Vec<_> = config
+        .projects
+        .iter()
+        .filter(|(_, v)| **v == pack_type)
+        .map(|(k, _)| k)
+        .collect();
+    projects.sort();
+
+    if projects.len() > 1 {
+        warn!(
+            "Found multiple {:?} projects, using {:?}",
+            pack_type, projects[0]
+        );
+    }
+
+    projects.first().map(PathBuf::from)
+}
+
+fn fallback_namespace(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn display_name(name: &str) -> String {
+    name.split('_')
+        .filter(|v| !v.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+pub async fn add(add: CliAddSubCommand, config: Option<StrixConfig>) -> Result<(), AddError> {
+    let config = config.ok_or(ConfigError::Missing("adding content"))?;
+
+    let (namespace, name) = match add.name.split_once(':') {
+        Some((namespace, name)) => (namespace.to_string(), name.to_string()),
+        None => {
+            let namespace = if config.namespace.is_empty() {
+                let namespace = fallback_namespace(&config.name);
+                warn!("No namespace set in {STRIX_CONFIG:?}, falling back to {namespace:?}");
+                namespace
+            } else {
+                config.namespace.clone()
+            };
+
+            (namespace, add.name.clone())
+        }
+    };
+
+    if !is_valid_name(&namespace) || !is_valid_name(&name) {
+        return Err(AddError::InvalidIdentifier(format!("{namespace}:{name}")));
+    }
+
+    let ctx = AddContext {
+        identifier: format!("{namespace}:{name}"),
+        display_name: display_name(&name),
+        namespace,
+        name,
+        behaviour: find_project(&config, StrixConfigPackType::Behaviour),
+        resource: find_project(&config, StrixConfigPackType::Resource),
+        force: add.force,
+        fmt: FmtConfig::new(&config.fmt),
+        primary_language: config.lint.primary_language().to_string(),
+    };
+
+    let mut changes = Changes::default();
+
+    match add.kind {
+        CliAddKind::Entity => add_entity(&ctx, &mut changes),
+        CliAddKind::Item => add_item(&ctx, &mut changes),
+        CliAddKind::Block => add_block(&ctx, &mut changes),
+        CliAddKind::Recipe => add_recipe(&ctx, &mut changes),
+        CliAddKind::LootTable => add_loot_table(&ctx, &mut changes),
+        CliAddKind::Function => add_function(&ctx, &mut changes),
+        CliAddKind::Particle => add_particle(&ctx, &mut changes),
+    }?;
+
+    changes.write(&ctx)?;
+
+    info!("Added {:?} {:?}", add.kind, ctx.identifier);
+
+    Ok(())
+}
+
+fn require(
+    project: &Option<PathBuf>,
+    pack_type: StrixConfigPackType,
+) -> Result<&PathBuf, AddError> {
+    project.as_ref().ok_or(AddError::MissingProject(pack_type))
+}
+
+/// The files a generator creates or updates, nothing is written until every one is known
+#[derive(Default)]
+struct Changes {
+    created: Vec<(PathBuf, String)>,
+    updated: Vec<(PathBuf, String)>,
+}
+
+impl Changes {
+    fn create_text(&mut self, path: PathBuf, text: String) {
+        self.created.push((path, text));
+    }
+
+    fn create_json(&mut self, path: PathBuf, json: Value) {
+        self.create_text(
+            path,
+            serde_json::to_string_pretty(&json).unwrap_or_default(),
+        );
+    }
+
+    /// Loads a shared registry file like `item_texture.json`, or `default` if it doesn't exist
+    /// yet, and queues it with the changes of `f`.
+    fn update_registry(
+        &mut self,
+        path: PathBuf,
+        default: Value,
+        f: impl FnOnce(&mut Map<String, Value>),
+    ) -> Result<(), AddError> {
+        let mut json: Value = if path.exists() {
+            let text = fs::read_to_string(&path).map_err(|err| IoError::new("read", &path, err))?;
+
+            serde_json::from_reader(StripComments::new(text.as_bytes())).map_err(|source| {
+                AddError::Deserialize {
+                    path: path.clone(),
+                    source,
+                }
+            })?
+        } else {
+            default
+        };
+
+        match json.as_object_mut() {
+            Some(v) => f(v),
+            None => return Err(AddError::NotAnObject(path)),
+        }
+
+        self.updated.push((
+            path,
+            serde_json::to_string_pretty(&json).unwrap_or_default(),
+        ));
+        Ok(())
+    }
+
+    /// Checks that none of the created files exist, unless `--force` is set, then formats the
+    /// files with the `fmt` settings of the project and writes them.
+    fn write(self, ctx: &AddContext) -> Result<(), AddError> {
+        for (path, _) in &self.created {
+            if path.exists() && !ctx.force {
+                return Err(AddError::Exists(path.clone()));
+            }
+        }
+
+        let created = self.created.into_iter().map(|v| (v, "Created"));
+        let updated = self.updated.into_iter().map(|v| (v, "Updated"));
+
+        for ((path, text), action) in created.chain(updated) {
+            let text = fmt::format_buffer(&path, &text, &ctx.fmt)
+                .ok()
+                .flatten()
+                .unwrap_or(text);
+
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| IoError::new("create", parent, err))?;
+            }
+
+            fs::write(&path, text).map_err(|err| IoError::new("write", &path, err))?;
+
+            info!("{action} {:?}", path.display());
+        }
+
+        Ok(())
+    }
+}
+
+/// Inserts `key` into the object stored under `field`, creating the object if needed.
+fn insert_into(map: &mut Map<String, Value>, field: &str, key: &str, value: Value) {
+    let entry = map
+        .entry(field)
+        .or_insert_with(|| Value::Object(Map::new()));
+
+    if !entry.is_object() {
+        *entry = Value::Object(Map::new());
+    }
+
+    if let Some(obj) = entry.as_object_mut() {
+        if obj.contains_key(key) {
+            warn!("Replacing existing entry {key:?} in {field:?}");
+        }
+        obj.insert(key.to_string(), value);
+    }
+}
+
+fn update_lang(
+    ctx: &AddContext,
+    changes: &mut Changes,
+    resource: &Path,
+    entries: &[(String, String)],
+) -> Result<(), AddError> {
+    let texts = resource.join("texts");
+    let lang_path = texts.join(format!("{}.lang", ctx.primary_language));
+    let languages_path = texts.join("languages.json");
+
+    let mut text = if lang_path.exists() {
+        fs::read_to_string(&lang_path).map_err(|err| IoError::new("read", &lang_path, err))?
+    } else {
+        String::new()
+    };
+
+    let existing: Vec<String> = text
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim().to_string())
+        .collect();
+
+    for (key, value) in entries {
+        if existing.contains(key) {
+            warn!(
+                "Lang key {key:?} already exists in {:?}",
+                lang_path.display()
+            );
+            continue;
+        }
+
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text += &format!("{key}={value}\n");
+    }
+
+    changes.updated.push((lang_path, text));
+
+    if !languages_path.exists() {
+        changes.updated.push((
+            languages_path,
+            serde_json::to_string_pretty(&json!([ctx.primary_language])).unwrap_or_default(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn add_entity(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    changes.create_json(
+        behaviour
+            .join("entities")
+            .join(format!("{}.json", ctx.name)),
+        json!({
+            "format_version": "1.20.80",
+            "minecraft:entity": {
+                "description": {
+                    "identifier": ctx.identifier,
+                    "is_spawnable": true,
+                    "is_summonable": true,
+                    "is_experimental": false
+                },
+                "components": {
+                    "minecraft:type_family": { "family": [ ctx.name, "mob" ] },
+                    "minecraft:health": { "value": 10, "max": 10 },
+                    "minecraft:collision_box": { "width": 0.6, "height": 1.8 },
+                    "minecraft:physics": {},
+                    "minecraft:pushable": { "is_pushable": true, "is_pushable_by_piston": true }
+                }
+            }
+        }),
+    );
+
+    let Some(resource) = &ctx.resource else {
+        warn!("No Resource project found, skipping the client entity");
+        return Ok(());
+    };
+
+    let geometry = format!("geometry.{}.{}", ctx.namespace, ctx.name);
+    let texture = format!("textures/entity/{}", ctx.name);
+
+    changes.create_json(
+        resource
+            .join("entity")
+            .join(format!("{}.entity.json", ctx.name)),
+        json!({
+            "format_version": "1.10.0",
+            "minecraft:client_entity": {
+                "description": {
+                    "identifier": ctx.identifier,
+                    "materials": { "default": "entity_alphatest" },
+                    "textures": { "default": texture },
+                    "geometry": { "default": geometry },
+                    "render_controllers": [ "controller.render.default" ],
+                    "spawn_egg": { "base_color": "#ffffff", "overlay_color": "#000000" }
+                }
+            }
+        }),
+    );
+
+    changes.create_json(
+        resource
+            .join("models")
+            .join("entity")
+            .join(format!("{}.geo.json", ctx.name)),
+        json!({
+            "format_version": "1.12.0",
+            "minecraft:geometry": [
+                {
+                    "description": {
+                        "identifier": geometry,
+                        "texture_width": 64,
+                        "texture_height": 64,
+                        "visible_bounds_width": 1,
+                        "visible_bounds_height": 2,
+                        "visible_bounds_offset": [ 0, 1, 0 ]
+                    },
+                    "bones": [
+                        {
+                            "name": "body",
+                            "pivot": [ 0, 0, 0 ],
+                            "cubes": [
+                                { "origin": [ -4, 0, -4 ], "size": [ 8, 16, 8 ], "uv": [ 0, 0 ] }
+                            ]
+                        }
+                    ]
+                }
+            ]
+        }),
+    );
+
+    info!(
+        "Remember to add the entity texture at {:?}",
+        resource.join(format!("{texture}.png")).display()
+    );
+
+    update_lang(
+        ctx,
+        changes,
+        resource,
+        &[
+            (
+                format!("entity.{}.name", ctx.identifier),
+                ctx.display_name.clone(),
+            ),
+            (
+                format!("item.spawn_egg.entity.{}.name", ctx.identifier),
+                format!("Spawn {}", ctx.display_name),
+            ),
+        ],
+    )
+}
+
+fn add_item(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    let lang_key = format!("item.{}.name", ctx.identifier);
+
+    changes.create_json(
+        behaviour.join("items").join(format!("{}.json", ctx.name)),
+        json!({
+            "format_version": "1.20.80",
+            "minecraft:item": {
+                "description": {
+                    "identifier": ctx.identifier,
+                    "menu_category": { "category": "items" }
+                },
+                "components": {
+                    "minecraft:icon": { "texture": ctx.name },
+                    "minecraft:display_name": { "value": lang_key },
+                    "minecraft:max_stack_size": 64
+                }
+            }
+        }),
+    );
+
+    let Some(resource) = &ctx.resource else {
+        warn!("No Resource project found, skipping the item texture and lang entries");
+        return Ok(());
+    };
+
+    let texture = format!("textures/items/{}", ctx.name);
+
+    changes.update_registry(
+        resource.join("textures").join("item_texture.json"),
+        json!({
+            "resource_pack_name": ctx.namespace,
+            "texture_name": "atlas.items",
+            "texture_data": {}
+        }),
+        |map| {
+            insert_into(
+                map,
+                "texture_data",
+                &ctx.name,
+                json!({ "textures": texture }),
+            )
+        },
+    )?;
+
+    info!(
+        "Remember to add the item texture at {:?}",
+        resource.join(format!("{texture}.png")).display()
+    );
+
+    update_lang(
+        ctx,
+        changes,
+        resource,
+        &[(lang_key, ctx.display_name.clone())],
+    )
+}
+
+fn add_block(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    let lang_key = format!("tile.{}.name", ctx.identifier);
+
+    changes.create_json(
+        behaviour.join("blocks").join(format!("{}.json", ctx.name)),
+        json!({
+            "format_version": "1.20.80",
+            "minecraft:block": {
+                "description": {
+                    "identifier": ctx.identifier,
+                    "menu_category": { "category": "construction" }
+                },
+                "components": {
+                    "minecraft:display_name": lang_key,
+                    "minecraft:destructible_by_mining": { "seconds_to_destroy": 1 },
+                    "minecraft:material_instances": {
+                        "*": { "texture": ctx.name, "render_method": "opaque" }
+                    }
+                }
+            }
+        }),
+    );
+
+    let Some(resource) = &ctx.resource else {
+        warn!("No Resource project found, skipping the block textures and lang entries");
+        return Ok(());
+    };
+
+    let texture = format!("textures/blocks/{}", ctx.name);
+
+    changes.update_registry(
+        resource.join("textures").join("terrain_texture.json"),
+        json!({
+            "resource_pack_name": ctx.namespace,
+            "texture_name": "atlas.terrain",
+            "padding": 8,
+            "num_mip_levels": 4,
+            "texture_data": {}
+        }),
+        |map| {
+            insert_into(
+                map,
+                "texture_data",
+                &ctx.name,
+                json!({ "textures": texture }),
+            )
+        },
+    )?;
+
+    changes.update_registry(
+        resource.join("blocks.json"),
+        json!({ "format_version": [ 1, 1, 0 ] }),
+        |map| {
+            if map.contains_key(&ctx.identifier) {
+                warn!(
+                    "Replacing existing entry {:?} in \"blocks.json\"",
+                    ctx.identifier
+                );
+            }
+            map.insert(
+                ctx.identifier.clone(),
+                json!({ "textures": ctx.name, "sound": "stone" }),
+            );
+        },
+    )?;
+
+    info!(
+        "Remember to add the block texture at {:?}",
+        resource.join(format!("{texture}.png")).display()
+    );
+
+    update_lang(
+        ctx,
+        changes,
+        resource,
+        &[(lang_key, ctx.display_name.clone())],
+    )
+}
+
+fn add_recipe(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    changes.create_json(
+        behaviour.join("recipes").join(format!("{}.json", ctx.name)),
+        json!({
+            "format_version": "1.20.10",
+            "minecraft:recipe_shaped": {
+                "description": { "identifier": ctx.identifier },
+                "tags": [ "crafting_table" ],
+                "pattern": [ "##", "##" ],
+                "key": { "#": { "item": "minecraft:stick" } },
+                "result": { "item": ctx.identifier, "count": 1 }
+            }
+        }),
+    );
+
+    Ok(())
+}
+
+fn add_loot_table(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    changes.create_json(
+        behaviour
+            .join("loot_tables")
+            .join(&ctx.namespace)
+            .join(format!("{}.json", ctx.name)),
+        json!({
+            "pools": [
+                {
+                    "rolls": 1,
+                    "entries": [
+                        { "type": "item", "name": "minecraft:stick", "weight": 1 }
+                    ]
+                }
+            ]
+        }),
+    );
+
+    Ok(())
+}
+
+fn add_function(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let behaviour = require(&ctx.behaviour, StrixConfigPackType::Behaviour)?;
+
+    changes.create_text(
+        behaviour
+            .join("functions")
+            .join(&ctx.namespace)
+            .join(format!("{}.mcfunction", ctx.name)),
+        format!(
+            "# Called with `function {}/{}`\nsay Hello from {}\n",
+            ctx.namespace, ctx.name, ctx.identifier
+        ),
+    );
+
+    Ok(())
+}
+
+fn add_particle(ctx: &AddContext, changes: &mut Changes) -> Result<(), AddError> {
+    let resource = require(&ctx.resource, StrixConfigPackType::Resource)?;
+
+    changes.create_json(
+        resource
+            .join("particles")
+            .join(format!("{}.json", ctx.name)),
+        json!({
+            "format_version": "1.10.0",
+            "particle_effect": {
+                "description": {
+                    "identifier": ctx.identifier,
+                    "basic_render_parameters": {
+                        "material": "particles_alpha",
+                        "texture": "textures/particle/particles"
+                    }
+                },
+                "components": {
+                    "minecraft:emitter_rate_instant": { "num_particles": 8 },
+                    "minecraft:emitter_lifetime_once": { "active_time": 1 },
+                    "minecraft:emitter_shape_point": { "direction": [ 0, 1, 0 ] },
+                    "minecraft:particle_lifetime_expression": { "max_lifetime": 1 },
+                    "minecraft:particle_initial_speed": 2,
+                    "minecraft:particle_motion_dynamic": { "linear_acceleration": [ 0, -9.8, 0 ] },
+                    "minecraft:particle_appearance_billboard": {
+                        "size": [ 0.1, 0.1 ],
+                        "facing_camera_mode": "lookat_xyz",
+                        "uv": {
+                            "texture_width": 128,
+                            "texture_height": 128,
+                            "uv": [ 0, 0 ],
+                            "uv_size": [ 8, 8 ]
+                        }
+                    }
+                }
+            }
+        }),
+    );
+
+    Ok(())
+}
@@ -1,10 +1,22 @@
-use clap::{Args, Parser, Subcommand};
+use crate::error::EXIT_CODES_HELP;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug, Clone)]
+#[command(after_help = EXIT_CODES_HELP)]
 pub struct CliInput {
     #[command(subcommand)]
     pub command: CliSubCommand,
+    /// How diagnostics, artifacts and the summary are reported, `json` prints one object per line to stdout
+    #[arg(long, global = true, value_enum, default_value_t = CliMessageFormat::Human)]
+    pub message_format: CliMessageFormat,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliMessageFormat {
+    Human,
+    Json,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -13,6 +25,27 @@ pub enum CliSubCommand {
     Build(CliBuildSubCommand),
     Config(CliFmtSubCommand),
     Fmt(CliFmtSubCommand),
+    Add(CliAddSubCommand),
+    Import(CliImportSubCommand),
+    Init(CliInitSubCommand),
+    Lint(CliLintSubCommand),
+    Lang(CliLangSubCommand),
+}
+
+impl CliSubCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CliSubCommand::New(_) => "new",
+            CliSubCommand::Build(_) => "build",
+            CliSubCommand::Config(_) => "config",
+            CliSubCommand::Fmt(_) => "fmt",
+            CliSubCommand::Add(_) => "add",
+            CliSubCommand::Import(_) => "import",
+            CliSubCommand::Init(_) => "init",
+            CliSubCommand::Lint(_) => "lint",
+            CliSubCommand::Lang(_) => "lang",
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
@@ -21,12 +54,41 @@ pub struct CliNewSubCommand {
     pub path: Option<PathBuf>,
 }
 
+#[derive(Args, Debug, Clone)]
+pub struct CliInitSubCommand {
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+    /// Overwrite an existing `strix.json`
+    #[arg(short, long)]
+    pub force: bool,
+}
+
 #[derive(Args, Debug, Clone)]
 pub struct CliBuildSubCommand {
     #[arg(short, long)]
     pub quiet: bool,
     #[arg(long)]
     pub profile: Option<String>,
+    /// How many files are processed at once, the number of CPUs by default
+    #[arg(short, long)]
+    pub jobs: Option<NonZeroUsize>,
+    /// Build twice and fail if the artifacts differ
+    #[arg(long)]
+    pub verify_reproducible: bool,
+    #[command(flatten)]
+    pub report: CliReportArgs,
+}
+
+// Report files for CI, written by every validation run. A plain comment, since clap would use a
+// doc comment as the about text of every subcommand flattening these args
+#[derive(Args, Debug, Clone)]
+pub struct CliReportArgs {
+    /// Write a SARIF 2.1.0 report to this file
+    #[arg(long)]
+    pub sarif: Option<PathBuf>,
+    /// Write a JUnit XML report to this file
+    #[arg(long)]
+    pub junit: Option<PathBuf>,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -47,4 +109,90 @@ pub struct CliFmtSubCommand {
     pub always_semicolons: Option<bool>,
     #[arg(short, long)]
     pub single_quote: Option<bool>,
+    /// Sort the keys of `.lang` files
+    #[arg(long)]
+    pub sort_lang_keys: Option<bool>,
+    /// Normalize the spacing of Molang expressions in JSON files
+    #[arg(long)]
+    pub format_molang: Option<bool>,
+    #[command(flatten)]
+    pub report: CliReportArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliAddSubCommand {
+    #[arg(value_enum)]
+    pub kind: CliAddKind,
+    /// Name of the content, optionally namespaced like `namespace:name`
+    pub name: String,
+    /// Overwrite files that already exist
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CliAddKind {
+    Entity,
+    Item,
+    Block,
+    Recipe,
+    #[value(name = "loot_table")]
+    LootTable,
+    Function,
+    Particle,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliImportSubCommand {
+    /// The `.mcaddon` or `.mcpack` file to import
+    pub file: PathBuf,
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+    /// Overwrite pack folders that already exist
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliLintSubCommand {
+    #[arg(short, long)]
+    pub quiet: bool,
+    #[command(flatten)]
+    pub report: CliReportArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliLangSubCommand {
+    #[command(subcommand)]
+    pub command: CliLangCommand,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CliLangCommand {
+    /// Write all `texts/*.lang` keys into a translation table
+    Export(CliLangExportSubCommand),
+    /// Merge translations from a translation table back into the `.lang` files
+    Import(CliLangImportSubCommand),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliLangExportSubCommand {
+    #[arg(long, value_enum, default_value_t = CliTranslationFormat::Csv)]
+    pub format: CliTranslationFormat,
+    /// The CSV file, or the folder for the PO files
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CliLangImportSubCommand {
+    /// CSV or PO files to import, the format is picked by the extension
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliTranslationFormat {
+    Csv,
+    Po,
 }
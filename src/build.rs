@@ -1,23 +1,55 @@
 use crate::args::CliBuildSubCommand;
+use crate::build_info::{hash_dir, hash_file, BuildInfo};
 use crate::config::{
-    StrixBuildConfigProfile, StrixConfig, StrixConfigPackType, StrixConfigProjectType, STRIX_CONFIG,
+    ConfigError, StrixBuildConfigProfile, StrixBuildHooks, StrixConfig, StrixConfigPackType,
+    StrixConfigProjectType, StrixFlipbookConfig, STRIX_CONFIG,
 };
+use crate::diagnostic::{Diagnostic, Diagnostics};
+use crate::error::{ExitCode, IoError};
+use crate::files::FileFilter;
+use crate::filter::{absolute, run_filter, FilterError, FilterOutput};
+use crate::flipbook::{build_flipbook, update_flipbook_textures, FlipbookError};
+use crate::hook::{run_hook, HookError, HookOutput, HookStage};
+use crate::index::IndexDiff;
+use crate::json;
+use crate::lang;
+use crate::lint::{run_rules, LintContext};
+use crate::manifest::{validate_manifest, MANIFEST};
+use crate::mcfunction;
+use crate::molang;
+use crate::plugin::{load_plugins, Plugin, PluginError};
+use crate::progress::CliProgress;
+use crate::project::Project;
+use crate::report::Report;
 use anyhow::Context;
+use chrono::{Datelike, Timelike};
 use json_comments::StripComments;
 use log::{error, info, warn};
 use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
 use std::env::current_dir;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
 use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::{Read, Write};
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::process::{exit, Command};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinHandle};
 use uuid::Uuid;
 use walkdir::WalkDir;
 use zip::write::SimpleFileOptions;
-use zip::CompressionMethod;
+use zip::{CompressionMethod, DateTime};
+
+/// Deflate level of the packages, pinned so the archives don't change with the defaults of `zip`
+const COMPRESSION_LEVEL: i64 = 6;
 
 #[inline(always)]
 fn try_rm_prefix(path: &Path) -> PathBuf {
@@ -26,54 +58,274 @@ fn try_rm_prefix(path: &Path) -> PathBuf {
         .to_path_buf()
 }
 
-fn get_mojang_folder() -> PathBuf {
+fn get_mojang_folder() -> Result<PathBuf, BuildError> {
     if let Some(dir) = directories::BaseDirs::new() {
-        dir.home_dir()
+        Ok(dir
+            .home_dir()
             .join("AppData")
             .join("Local")
             .join("Packages")
             .join("Microsoft.MinecraftUWP_8wekyb3d8bbwe")
             .join("LocalState")
             .join("games")
-            .join("com.mojang")
+            .join("com.mojang"))
     } else {
-        error!("Couldn't get BaseDirs");
-        exit(1);
+        Err(BuildError::MissingHomeDir)
     }
 }
 
-fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-    fs::create_dir_all(&dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
-        }
-    }
-    Ok(())
+fn count_files(path: &Path) -> usize {
+    walk_files(path).len()
+}
+
+/// Every file inside a folder, sorted by path
+fn walk_files(path: &Path) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_file())
+        .map(|v| v.into_path())
+        .collect()
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|v| v.len()).unwrap_or_default()
 }
 
 macro_rules! try_make_dir {
     ($path:path) => {
         if !$path.is_dir() || !$path.exists() {
-            match fs::create_dir(&$path) {
-                Ok(_) => {}
-                Err(err) => {
-                    error!(
-                        "An unexpected Error occurred while trying to create {:?}, Err: {err}",
-                        $path
-                    );
-                    return true;
-                }
-            }
+            fs::create_dir(&$path).map_err(|err| IoError::new("create", &$path, err))?;
         };
     };
 }
 
-pub async fn build(build: CliBuildSubCommand, config: Option<StrixConfig>) -> bool {
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("Couldn't find build profile {name:?}, in {available:?}")]
+    MissingProfile {
+        name: String,
+        available: Vec<String>,
+    },
+    #[error("Couldn't get BaseDirs")]
+    MissingHomeDir,
+    #[error("Found invalid manifests, aborting the build")]
+    InvalidManifests(Report),
+    #[error("Found invalid files, aborting the build")]
+    InvalidFiles(Report),
+    #[error("An unexpected Error occurred while trying to copy {from:?} to {to:?}, Err: {source}")]
+    Copy {
+        from: String,
+        to: PathBuf,
+        source: io::Error,
+    },
+    #[error("An unexpected Error occurred while trying to zip {name:?}, Err: {source}")]
+    Package { name: String, source: anyhow::Error },
+    #[error("SOURCE_DATE_EPOCH {0:?} isn't a unix timestamp between 1980 and 2107")]
+    SourceDateEpoch(String),
+    #[error("{0:?} projects can't be built yet, only Vanilla ones")]
+    Unsupported(StrixConfigProjectType),
+    #[error("The build isn't reproducible, {0:?} differ between two builds")]
+    NotReproducible(Vec<PathBuf>),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Filter(#[from] FilterError),
+    #[error(transparent)]
+    Flipbook(#[from] FlipbookError),
+    #[error(transparent)]
+    Plugin(#[from] PluginError),
+    #[error(transparent)]
+    Hook(#[from] HookError),
+    #[error("An unexpected Error occurred while trying to join a thread, Err: {0}")]
+    Join(#[from] JoinError),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl BuildError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            BuildError::MissingProfile { .. }
+            | BuildError::Unsupported(_)
+            | BuildError::SourceDateEpoch(_)
+            | BuildError::Config(_) => ExitCode::Config,
+            BuildError::InvalidManifests(_)
+            | BuildError::InvalidFiles(_)
+            | BuildError::NotReproducible(_) => ExitCode::Validation,
+            BuildError::Filter(_) | BuildError::Plugin(_) | BuildError::Hook(_) => {
+                ExitCode::External
+            }
+            BuildError::Flipbook(err) => err.exit_code(),
+            BuildError::MissingHomeDir
+            | BuildError::Copy { .. }
+            | BuildError::Package { .. }
+            | BuildError::Join(_)
+            | BuildError::Io(_) => ExitCode::Io,
+        }
+    }
+
+    /// The validation results that aborted the build
+    pub fn report(&self) -> Option<&Report> {
+        match self {
+            BuildError::InvalidManifests(report) | BuildError::InvalidFiles(report) => Some(report),
+            _ => None,
+        }
+    }
+}
+
+/// A step of [`run`], reported to a [`BuildProgress`] as it happens
+#[derive(Debug, Clone, Copy)]
+pub enum BuildEvent<'a> {
+    /// All files are about to be validated against the bundled schemas
+    Validating,
+    /// A hook of the profile is about to run
+    HookStarted { stage: HookStage, command: &'a str },
+    /// A hook exited successfully
+    HookFinished {
+        stage: HookStage,
+        command: &'a str,
+        output: &'a HookOutput,
+    },
+    /// A filter of the profile is about to run on the build folder
+    FilterStarted { name: &'a str },
+    /// A filter exited successfully
+    FilterFinished {
+        name: &'a str,
+        output: &'a FilterOutput,
+    },
+    /// A plugin of the profile was loaded and transforms the files matching `globs`
+    PluginLoaded { name: &'a str, globs: &'a [String] },
+    /// A stage is about to process `files` files of a project
+    StageStarted {
+        stage: BuildStage,
+        project: &'a str,
+        files: usize,
+    },
+    /// A stage processed a file, with its size before and after
+    FileDone {
+        stage: BuildStage,
+        project: &'a str,
+        path: &'a Path,
+        before: u64,
+        after: u64,
+    },
+    /// A stage is done with all files of a project
+    StageFinished {
+        stage: BuildStage,
+        project: &'a str,
+        duration: Duration,
+    },
+    /// A checked-in index file of a project disagrees with the generated one, which replaces it
+    IndexOutdated {
+        project: &'a str,
+        path: &'a Path,
+        diff: &'a IndexDiff,
+    },
+    /// A project is fully processed inside the build folder
+    ProjectBuilt { project: &'a str, path: &'a Path },
+    /// A processed project was copied into a `development_*_packs` folder
+    DevFolderCopied { project: &'a str, path: &'a Path },
+    /// The `.mcaddon` is finished, the files inside were reported by the [`BuildStage::Package`]
+    Packaged { path: &'a Path },
+    /// The `build-info.json` describing the artifacts was written
+    BuildInfoWritten { path: &'a Path },
+}
+
+/// The steps of a build which go over files, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BuildStage {
+    /// Copying a project into the build folder
+    Copy,
+    /// Minifying JSON, `.lang` and `.mcfunction` files
+    Minify,
+    /// Compressing sounds with `ffmpeg`
+    Compress,
+    /// Stacking folders of frames into flipbook textures
+    Flipbook,
+    /// Regenerating index files like `contents.json`
+    Index,
+    /// Copying a built project into its `development_*_packs` folder
+    Deploy,
+    /// Zipping the built projects into the `.mcaddon`
+    Package,
+}
+
+impl Display for BuildStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildStage::Copy => write!(f, "copy"),
+            BuildStage::Minify => write!(f, "minify"),
+            BuildStage::Compress => write!(f, "compress"),
+            BuildStage::Flipbook => write!(f, "flipbook"),
+            BuildStage::Index => write!(f, "index"),
+            BuildStage::Package => write!(f, "package"),
+            BuildStage::Deploy => write!(f, "deploy"),
+        }
+    }
+}
+
+/// Receives the progress of a build, implemented for `()` and any `FnMut(BuildEvent)`
+pub trait BuildProgress {
+    fn event(&mut self, event: BuildEvent);
+}
+
+impl BuildProgress for () {
+    fn event(&mut self, _: BuildEvent) {}
+}
+
+impl<F: FnMut(BuildEvent)> BuildProgress for F {
+    fn event(&mut self, event: BuildEvent) {
+        self(event)
+    }
+}
+
+/// How a build runs, independent of what it produces
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// How many files are copied and processed at once, across all projects
+    pub jobs: usize,
+    /// Where the build folder, the package and `build-info.json` go, the `build_path` of the
+    /// config if `None`
+    pub output: Option<PathBuf>,
+    /// If the hooks of the profile run
+    pub hooks: bool,
+    /// If the projects are copied into the `development_*_packs` folders, when the profile asks
+    /// for it
+    pub deploy: bool,
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            jobs: num_cpus::get(),
+            output: None,
+            hooks: true,
+            deploy: true,
+        }
+    }
+}
+
+/// Everything a successful build produced
+#[derive(Debug, Default)]
+pub struct BuildOutput {
+    /// The validation results, without errors
+    pub report: Report,
+    /// The processed projects inside the build folder
+    pub projects: Vec<PathBuf>,
+    /// The projects copied into the `development_*_packs` folders, if the profile deploys
+    pub deployed: Vec<PathBuf>,
+    /// The `.mcaddon`, if the profile packages
+    pub package: Option<PathBuf>,
+    /// The `build-info.json` next to the `.mcaddon`
+    pub build_info: PathBuf,
+}
+
+pub(crate) async fn build(
+    build: CliBuildSubCommand,
+    config: Option<StrixConfig>,
+) -> Result<(), BuildError> {
     let config = config.unwrap_or_else(|| {
         warn!(
             "Couldn't find {:?}, it is recommend to create it for enabling more build options",
@@ -82,21 +334,8 @@ pub async fn build(build: CliBuildSubCommand, config: Option<StrixConfig>) -> bo
         StrixConfig::default()
     });
 
-    let profile_name = build
-        .profile
-        .unwrap_or(config.build.default_profile.clone());
-
-    let profile = match config.build.profiles.get(&profile_name) {
-        None => {
-            error!(
-                "Couldn't find build profile {:?}, in {:?}",
-                profile_name,
-                config.build.profiles.keys()
-            );
-            return true;
-        }
-        Some(v) => v,
-    };
+    let project = Project::new(PathBuf::new(), config);
+    let (profile_name, profile) = project.profile(build.profile.as_deref())?;
 
     if !build.quiet {
         info!("Starting build on Profile {:?}", profile_name);
@@ -106,9 +345,175 @@ pub async fn build(build: CliBuildSubCommand, config: Option<StrixConfig>) -> bo
         info!("[{profile_name}] encrypt:    {}", profile.encrypt);
         info!("[{profile_name}] dev folder: {}", profile.dev_folder);
         info!("[{profile_name}] package:    {}", profile.package);
+        info!("[{profile_name}] validate:   {}", profile.validate);
     }
 
-    let target_folder = PathBuf::from(config.build.build_path.clone());
+    let options = BuildOptions {
+        jobs: build.jobs.map_or_else(num_cpus::get, NonZeroUsize::get),
+        ..Default::default()
+    };
+
+    let mut progress = CliProgress::new(build.quiet);
+    let result = run(&project, profile_name, &options, &mut progress).await;
+    progress.finish(result.is_ok());
+
+    let output = match result {
+        Ok(output) => {
+            output.report.write(
+                build.report.sarif.as_deref(),
+                build.report.junit.as_deref(),
+                "build",
+            )?;
+            output
+        }
+        Err(err) => {
+            if let Some(report) = err.report() {
+                report.write(
+                    build.report.sarif.as_deref(),
+                    build.report.junit.as_deref(),
+                    "build",
+                )?;
+            }
+            return Err(err);
+        }
+    };
+
+    if build.verify_reproducible {
+        verify_reproducible(&project, profile_name, &options, &output, build.quiet).await?;
+    }
+
+    Ok(())
+}
+
+/// Builds a second time and compares the hashes of all artifacts with the first build
+///
+/// The second build goes into a temporary folder inside the build folder, without hooks or
+/// deploying, so the artifacts of the first build stay as they are.
+async fn verify_reproducible(
+    project: &Project,
+    profile_name: &str,
+    options: &BuildOptions,
+    first: &BuildOutput,
+    quiet: bool,
+) -> Result<(), BuildError> {
+    if !quiet {
+        info!("Building again to verify the build is reproducible");
+    }
+
+    let first_folder = match &options.output {
+        Some(output) => output.clone(),
+        None => project.path(&project.config.build.build_path),
+    };
+    let first = hash_output(first, &first_folder)?;
+
+    let folder = project
+        .path(&project.config.build.build_path)
+        .join("build")
+        .join(format!("{}", Uuid::new_v4()));
+    try_make_dir!(folder);
+
+    let options = BuildOptions {
+        output: Some(folder.clone()),
+        hooks: false,
+        deploy: false,
+        ..options.clone()
+    };
+
+    let mut progress = CliProgress::new(true);
+    let result = run(project, profile_name, &options, &mut progress).await;
+    progress.finish(result.is_ok());
+
+    let second = result.and_then(|v| hash_output(&v, &folder));
+    fs::remove_dir_all(&folder).map_err(|err| IoError::new("remove", &folder, err))?;
+    let second = second?;
+
+    let changed = compare_hashes(&first, &second);
+    if !changed.is_empty() {
+        return Err(BuildError::NotReproducible(changed));
+    }
+
+    if !quiet {
+        for (path, hash) in &first {
+            info!("{:?} is reproducible, sha256 {hash}", try_rm_prefix(path));
+        }
+    }
+
+    Ok(())
+}
+
+/// The artifacts which changed or are missing in the second build, then the ones only it produced
+fn compare_hashes(
+    first: &BTreeMap<PathBuf, String>,
+    second: &BTreeMap<PathBuf, String>,
+) -> Vec<PathBuf> {
+    let mut changed = vec![];
+
+    for (path, hash) in first {
+        match second.get(path) {
+            Some(other) if other != hash => {
+                error!("{path:?} changed, sha256 {hash} before and {other} after");
+                changed.push(path.clone());
+            }
+            Some(_) => {}
+            None => {
+                error!("{path:?} is missing from the second build");
+                changed.push(path.clone());
+            }
+        }
+    }
+
+    for path in second.keys().filter(|v| !first.contains_key(*v)) {
+        error!("{path:?} was only produced by the second build");
+        changed.push(path.clone());
+    }
+
+    changed
+}
+
+/// The SHA-256 of the package and every project, by their path relative to the output folder
+///
+/// Projects are named after the project instead of their build folder, which is new every build.
+fn hash_output(
+    output: &BuildOutput,
+    folder: &Path,
+) -> Result<BTreeMap<PathBuf, String>, BuildError> {
+    let mut hashes = BTreeMap::new();
+
+    for project in &output.projects {
+        let name = project.file_name().map(PathBuf::from).unwrap_or_default();
+        hashes.insert(name, hash_dir(project)?.sha256);
+    }
+
+    if let Some(package) = &output.package {
+        let name = package.strip_prefix(folder).unwrap_or(package).to_owned();
+        hashes.insert(name, hash_file(package)?.1);
+    }
+
+    Ok(hashes)
+}
+
+/// Runs the whole build pipeline of a project with a profile
+///
+/// Runs the pre build hooks, validates, copies every project into a fresh build folder, runs the
+/// filters, processes the files with the plugins and built-in steps in parallel, copies them into
+/// the `development_*_packs` folders, runs the post build hooks and packages them, as far as the
+/// profile asks for it. A `build-info.json` with the checksums of the artifacts is written next to
+/// them, before the post package hooks run last.
+/// Files are spread over `options.jobs` workers, the output and the events are the same for any
+/// number of jobs.
+/// Diagnostics are logged as they are found and returned in the [`BuildOutput`] or the error.
+pub async fn run(
+    project: &Project,
+    profile_name: &str,
+    options: &BuildOptions,
+    progress: &mut impl BuildProgress,
+) -> Result<BuildOutput, BuildError> {
+    let (profile_name, profile) = project.profile(Some(profile_name))?;
+
+    let target_folder = match &options.output {
+        Some(output) => output.clone(),
+        None => project.path(&project.config.build.build_path),
+    };
     try_make_dir!(target_folder);
 
     let build_folder = target_folder.join("build");
@@ -117,230 +522,958 @@ pub async fn build(build: CliBuildSubCommand, config: Option<StrixConfig>) -> bo
     let temp_build_folder = build_folder.join(format!("{}", Uuid::new_v4()));
     try_make_dir!(temp_build_folder);
 
-    match config.project_type {
+    match &project.config.project_type {
         StrixConfigProjectType::Vanilla => {
             build_vanilla(
+                profile_name,
                 profile,
-                &config,
+                options,
+                project,
                 &temp_build_folder,
                 &target_folder,
-                build.quiet,
+                progress,
             )
             .await
         }
-        StrixConfigProjectType::Regolith => {
-            unimplemented!()
-        }
-        StrixConfigProjectType::Dash => {
-            unimplemented!()
-        }
+        project_type => Err(BuildError::Unsupported(project_type.clone())),
     }
 }
 
 async fn build_vanilla(
+    profile_name: &str,
     profile: &StrixBuildConfigProfile,
-    config: &StrixConfig,
-    temp_build_folder: &PathBuf,
+    options: &BuildOptions,
+    project: &Project,
+    temp_build_folder: &Path,
     target_folder: &Path,
-    quiet: bool,
-) -> bool {
-    let mut project_paths = vec![];
+    progress: &mut impl BuildProgress,
+) -> Result<BuildOutput, BuildError> {
+    let config = &project.config;
+
+    let filter = project.files()?;
+
+    let hooks = match options.hooks {
+        true => profile.hooks.clone(),
+        false => StrixBuildHooks::default(),
+    };
+
+    let mut env = hook_env(profile_name, project, temp_build_folder);
+    run_hooks(
+        HookStage::PreBuild,
+        &hooks.pre_build,
+        project,
+        &env,
+        progress,
+    )
+    .await?;
+
+    let diagnostics = Diagnostics::default();
+    let mut files = vec![];
+
+    let invalid_manifests = validate_manifests(project, &diagnostics, &mut files);
+    let invalid_files = !invalid_manifests
+        && profile.validate
+        && validate_files(project, &filter, progress, &diagnostics, &mut files);
+
+    let report = Report {
+        files,
+        diagnostics: diagnostics.into_vec(),
+    };
+
+    if invalid_manifests {
+        return Err(BuildError::InvalidManifests(report));
+    }
+
+    if invalid_files {
+        return Err(BuildError::InvalidFiles(report));
+    }
+
+    let mut output = BuildOutput {
+        report,
+        ..Default::default()
+    };
+
+    // Sorted, so everything happens in the same order for any number of jobs
+    let mut projects: Vec<(&String, &StrixConfigPackType)> = config.projects.iter().collect();
+    projects.sort_by_key(|(name, _)| name.as_str());
 
-    for (project, project_type) in &config.projects {
-        let project_path = temp_build_folder.join(project);
+    let jobs = Arc::new(Semaphore::new(options.jobs.max(1)));
+
+    // All projects are spawned before the first one is joined, so they share the worker pool
+    let mut copies = vec![];
+    for (name, _) in &projects {
+        let project_path = temp_build_folder.join(name);
         try_make_dir!(project_path);
 
-        project_paths.push((
-            WalkDir::new(project_path.clone()),
-            Path::new(project),
-            Path::new(temp_build_folder),
-        ));
+        let source = project.path(name);
+        copies.push(spawn_copy(
+            BuildStage::Copy,
+            name,
+            filter.walk(&source),
+            &source,
+            &project_path,
+            &jobs,
+        )?);
+    }
+    for copy in copies {
+        copy.join(progress).await?;
+    }
 
-        match copy_dir_all(project, &project_path) {
-            Ok(_) => {}
-            Err(err) => {
-                error!("An unexpected Error occurred while trying to copy {project:?} to {project_path:?}, Err: {err}");
-                return true;
+    for filter in &profile.filters {
+        progress.event(BuildEvent::FilterStarted { name: &filter.name });
+
+        let filter_output = run_filter(filter, project, temp_build_folder).await?;
+
+        progress.event(BuildEvent::FilterFinished {
+            name: &filter.name,
+            output: &filter_output,
+        });
+    }
+
+    let plugins = Arc::new(load_plugins(project, &profile.plugins)?);
+
+    for plugin in plugins.iter() {
+        progress.event(BuildEvent::PluginLoaded {
+            name: &plugin.name,
+            globs: &plugin.globs,
+        });
+    }
+
+    let mut processes = vec![];
+    for (name, _) in &projects {
+        let project_path = temp_build_folder.join(name);
+        processes.push(spawn_process(name, &project_path, profile, &plugins, &jobs));
+    }
+    let mut flipbooks = BTreeSet::new();
+    for (process, (_, pack_type)) in processes.into_iter().zip(&projects) {
+        let name = process.project;
+        process.join(progress).await?;
+
+        let project_path = temp_build_folder.join(name);
+        if **pack_type == StrixConfigPackType::Resource {
+            build_flipbooks(name, &project_path, profile, &mut flipbooks, progress)?;
+        }
+        generate_indexes(name, pack_type, &project_path, profile, progress)?;
+
+        progress.event(BuildEvent::ProjectBuilt {
+            project: name,
+            path: &project_path,
+        });
+        output.projects.push(project_path);
+    }
+
+    if let Some(flipbook) = profile
+        .flipbooks
+        .iter()
+        .find(|v| !flipbooks.contains(v.frames.as_str()))
+    {
+        return Err(FlipbookError::Missing(flipbook.frames.clone()).into());
+    }
+
+    if profile.dev_folder && options.deploy {
+        let mojang_folder = get_mojang_folder()?;
+
+        let mut deploys = vec![];
+        for (name, project_type) in &projects {
+            let folder = match project_type {
+                StrixConfigPackType::Behaviour => "development_behavior_packs",
+                StrixConfigPackType::Resource => "development_resource_packs",
+                _ => continue,
+            };
+
+            let path = mojang_folder.join(folder).join(name);
+            try_make_dir!(path);
+
+            let built = temp_build_folder.join(name);
+            let copy = spawn_copy(
+                BuildStage::Deploy,
+                name,
+                walk_files(&built),
+                &built,
+                &path,
+                &jobs,
+            )?;
+            deploys.push((copy, path));
+        }
+        for (copy, path) in deploys {
+            let name = copy.project;
+            copy.join(progress).await?;
+
+            progress.event(BuildEvent::DevFolderCopied {
+                project: name,
+                path: &path,
+            });
+            output.deployed.push(path);
+        }
+    }
+
+    env.push((
+        "STRIX_ARTIFACTS",
+        join_paths(output.projects.iter().map(|v| absolute(v))),
+    ));
+    run_hooks(
+        HookStage::PostBuild,
+        &hooks.post_build,
+        project,
+        &env,
+        progress,
+    )
+    .await?;
+
+    if profile.package {
+        let package_path = target_folder.join(format!("{}.mcaddon", config.name));
+        let names: Vec<&str> = projects.iter().map(|(name, _)| name.as_str()).collect();
+
+        package(temp_build_folder, &names, &package_path, progress)?;
+        output.package = Some(package_path);
+    }
+
+    output.build_info =
+        BuildInfo::collect(project, profile_name, &output, target_folder)?.write(target_folder)?;
+    progress.event(BuildEvent::BuildInfoWritten {
+        path: &output.build_info,
+    });
+
+    if let Some(package_path) = &output.package {
+        env.push(("STRIX_PACKAGE", absolute(package_path).into_os_string()));
+        run_hooks(
+            HookStage::PostPackage,
+            &hooks.post_package,
+            project,
+            &env,
+            progress,
+        )
+        .await?;
+    }
+
+    Ok(output)
+}
+
+/// Stacks the flipbook frames found in a built resource pack, `found` collects the frame folders
+fn build_flipbooks<'a>(
+    project: &str,
+    project_path: &Path,
+    profile: &'a StrixBuildConfigProfile,
+    found: &mut BTreeSet<&'a str>,
+    progress: &mut impl BuildProgress,
+) -> Result<(), BuildError> {
+    let flipbooks: Vec<&StrixFlipbookConfig> = profile
+        .flipbooks
+        .iter()
+        .filter(|v| project_path.join(&v.frames).is_dir())
+        .collect();
+
+    if flipbooks.is_empty() {
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    progress.event(BuildEvent::StageStarted {
+        stage: BuildStage::Flipbook,
+        project,
+        files: flipbooks.len(),
+    });
+
+    for flipbook in &flipbooks {
+        let strip = build_flipbook(project_path, flipbook)?;
+        found.insert(flipbook.frames.as_str());
+
+        progress.event(BuildEvent::FileDone {
+            stage: BuildStage::Flipbook,
+            project,
+            path: &strip.path,
+            before: strip.before,
+            after: strip.after,
+        });
+    }
+
+    update_flipbook_textures(project_path, &flipbooks, profile.minify)?;
+
+    progress.event(BuildEvent::StageFinished {
+        stage: BuildStage::Flipbook,
+        project,
+        duration: start.elapsed(),
+    });
+
+    Ok(())
+}
+
+/// Regenerates the index files of a built project, after every file is processed
+fn generate_indexes(
+    project: &str,
+    pack_type: &StrixConfigPackType,
+    project_path: &Path,
+    profile: &StrixBuildConfigProfile,
+    progress: &mut impl BuildProgress,
+) -> Result<(), BuildError> {
+    let indexes: Vec<_> = profile
+        .indexes
+        .files()
+        .into_iter()
+        .filter(|v| v.applies_to(pack_type))
+        .collect();
+
+    if indexes.is_empty() {
+        return Ok(());
+    }
+
+    let start = Instant::now();
+    progress.event(BuildEvent::StageStarted {
+        stage: BuildStage::Index,
+        project,
+        files: indexes.len(),
+    });
+
+    for index in indexes {
+        let path = project_path.join(index.path());
+        let entries = index.entries(project_path);
+        let before = file_size(&path);
+
+        if path.is_file() {
+            let diff = IndexDiff::new(&index.read(&path).unwrap_or_default(), &entries);
+
+            if !diff.is_empty() {
+                let checked_in = Path::new(project).join(index.path());
+                progress.event(BuildEvent::IndexOutdated {
+                    project,
+                    path: &checked_in,
+                    diff: &diff,
+                });
             }
         }
 
-        let walk: Vec<_> = WalkDir::new(&project_path)
-            .into_iter()
-            .filter(|v| {
-                if let Ok(v) = v {
-                    v.file_type().is_file()
-                } else {
-                    false
-                }
-            })
-            .collect();
-
-        for entry in walk.into_iter().flatten() {
-            if let Some(ext) = entry.path().extension().and_then(OsStr::to_str) {
-                if profile.minify {
-                    match ext {
-                        "json" => {
-                            let mut text = match fs::read_to_string(entry.path()) {
-                                Ok(v) => v,
-                                Err(_) => continue,
-                            };
-
-                            let stripped = StripComments::new(text.as_bytes());
-                            let json: Value = match serde_json::from_reader(stripped) {
-                                Ok(v) => v,
-                                Err(err) => {
-                                    error!(
-                                        "An unexpected Error occurred while trying to deserialize {:?}\n{}",
-                                        try_rm_prefix(entry.path()).display(),
-                                        err
-                                    );
-                                    continue;
-                                }
-                            };
-
-                            match serde_json::to_string(&json) {
-                                Ok(v) => text = v,
-                                Err(err) => {
-                                    error!(
-                                        "An unexpected Error occurred while trying to serialize {:?}\n{}",
-                                        try_rm_prefix(entry.path()).display(),
-                                        err
-                                    );
-                                }
-                            };
-
-                            match fs::write(entry.path(), text) {
-                                Ok(_) => {}
-                                Err(err) => {
-                                    error!(
-                                        "An unexpected Error occurred while trying to write {:?}\n{}",
-                                        try_rm_prefix(entry.path()).display(),
-                                        err
-                                    );
-                                }
-                            };
-                        }
-                        _ => {}
-                    }
-                }
-
-                if profile.compress {
-                    match ext {
-                        "ogg" => {
-                            match Command::new("ffmpeg")
-                                .args([
-                                    "-y",
-                                    "-i", &current_dir().unwrap().join(entry.path().display().to_string()).display().to_string(),
-                                    "-qscale:a", "8",
-                                    "-f", "ogg",
-                                    &current_dir().unwrap().join(entry.path().display().to_string() + ".tmp").display().to_string()
-                                ])
-                                .output() {
-                                Ok(output) => {
-                                    if output.status.success() {
-                                        if let Err(err) = fs::rename(
-                                            current_dir().unwrap().join(entry.path().display().to_string() + ".tmp"),
-                                            current_dir().unwrap().join(entry.path().display().to_string())
-                                        ) {
-                                            error!(
-                                                "An unexpected Error occurred while trying to rename {:?} to {:?}\n{}",
-                                                &current_dir().unwrap().join(entry.path().display().to_string() + ".tmp"),
-                                                &current_dir().unwrap().join(entry.path().display().to_string()),
-                                                err
-                                            );
-                                        };
-                                    } else {
-                                        error!(
-                                            "An unexpected Error occurred while trying to execute ffmpeg for {:?}\n{}",
-                                            try_rm_prefix(entry.path()).display(),
-                                            String::from_utf8_lossy(output.stderr.as_slice())
-                                        );
-                                    }
-                                },
-                                Err(err) => {
-                                    error!(
-                                        "An unexpected Error occurred while trying to execute ffmpeg for {:?}\n{}",
-                                        try_rm_prefix(entry.path()).display(),
-                                        err
-                                    );
-                                    continue;
-                                }
-                            }
-                        },
-                        _ => {}
-                    }
-                }
+        let json = index.to_json(&entries);
+        let text = match profile.minify {
+            true => serde_json::to_string(&json),
+            false => serde_json::to_string_pretty(&json),
+        }
+        .unwrap_or_default();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| IoError::new("create", parent, err))?;
+        }
+        fs::write(&path, &text).map_err(|err| IoError::new("write", &path, err))?;
+
+        progress.event(BuildEvent::FileDone {
+            stage: BuildStage::Index,
+            project,
+            path: &path,
+            before,
+            after: text.len() as u64,
+        });
+    }
+
+    progress.event(BuildEvent::StageFinished {
+        stage: BuildStage::Index,
+        project,
+        duration: start.elapsed(),
+    });
+
+    Ok(())
+}
+
+/// The environment of every hook, with absolute paths and lists split like `PATH`
+fn hook_env(
+    profile_name: &str,
+    project: &Project,
+    temp_build_folder: &Path,
+) -> Vec<(&'static str, OsString)> {
+    let mut projects: Vec<&String> = project.config.projects.keys().collect();
+    projects.sort();
+
+    vec![
+        ("STRIX_PROFILE", OsString::from(profile_name)),
+        (
+            "STRIX_PROJECT_ROOT",
+            absolute(&project.root).into_os_string(),
+        ),
+        (
+            "STRIX_BUILD_DIR",
+            absolute(temp_build_folder).into_os_string(),
+        ),
+        ("STRIX_PROJECTS", join_paths(projects)),
+    ]
+}
+
+fn join_paths<T: AsRef<OsStr>>(paths: impl IntoIterator<Item = T>) -> OsString {
+    let paths: Vec<T> = paths.into_iter().collect();
+
+    // Only fails if a path contains the separator itself, which is then passed as it is
+    env::join_paths(&paths).unwrap_or_else(|_| {
+        paths
+            .iter()
+            .map(|v| v.as_ref().to_os_string())
+            .collect::<Vec<_>>()
+            .join(OsStr::new(if cfg!(windows) { ";" } else { ":" }))
+    })
+}
+
+async fn run_hooks(
+    stage: HookStage,
+    commands: &[String],
+    project: &Project,
+    env: &[(&'static str, OsString)],
+    progress: &mut impl BuildProgress,
+) -> Result<(), BuildError> {
+    let cwd = absolute(&project.root);
+
+    for command in commands {
+        progress.event(BuildEvent::HookStarted { stage, command });
+
+        let output = run_hook(stage, command, &cwd, env).await?;
+
+        progress.event(BuildEvent::HookFinished {
+            stage,
+            command,
+            output: &output,
+        });
+    }
+
+    Ok(())
+}
+
+/// Zips the built `projects` inside `build_folder` into one `.mcaddon`, one folder per project
+///
+/// The same files always give the same archive, every entry is dated `SOURCE_DATE_EPOCH` if set.
+pub fn package(
+    build_folder: &Path,
+    projects: &[&str],
+    output: &Path,
+    progress: &mut impl BuildProgress,
+) -> Result<(), BuildError> {
+    let name = output
+        .file_stem()
+        .map(|v| v.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let it = projects
+        .iter()
+        .map(|v| {
+            (
+                WalkDir::new(build_folder.join(v)),
+                Path::new(*v),
+                build_folder,
+            )
+        })
+        .collect();
+
+    progress.event(BuildEvent::StageStarted {
+        stage: BuildStage::Package,
+        project: &name,
+        files: projects
+            .iter()
+            .map(|v| count_files(&build_folder.join(v)))
+            .sum(),
+    });
+
+    let start = Instant::now();
+    let modified = source_date()?;
+
+    zip_dir(it, output, modified, &mut |path, bytes| {
+        progress.event(BuildEvent::FileDone {
+            stage: BuildStage::Package,
+            project: &name,
+            path,
+            before: bytes,
+            after: bytes,
+        })
+    })
+    .map_err(|source| BuildError::Package {
+        name: name.clone(),
+        source,
+    })?;
+
+    progress.event(BuildEvent::StageFinished {
+        stage: BuildStage::Package,
+        project: &name,
+        duration: start.elapsed(),
+    });
+    progress.event(BuildEvent::Packaged { path: output });
+    Ok(())
+}
+
+/// A file handled by a stage, with its size before and after
+struct ProcessedFile {
+    stage: BuildStage,
+    path: PathBuf,
+    before: u64,
+    after: u64,
+}
+
+type FileJob = JoinHandle<Result<Result<Option<ProcessedFile>, BuildError>, JoinError>>;
+
+/// The files of a project in the worker pool
+struct ProjectJobs<'a> {
+    project: &'a str,
+    /// How many files each stage gets
+    stages: BTreeMap<BuildStage, usize>,
+    start: Instant,
+    files: Vec<FileJob>,
+}
+
+impl ProjectJobs<'_> {
+    /// Waits for the files in the order they were spawned, so the events and the first error
+    /// don't depend on the scheduling
+    async fn join(mut self, progress: &mut impl BuildProgress) -> Result<(), BuildError> {
+        for (&stage, &files) in &self.stages {
+            progress.event(BuildEvent::StageStarted {
+                stage,
+                project: self.project,
+                files,
+            });
+        }
+
+        // Popped from the back, whatever is left gets aborted on an error
+        self.files.reverse();
+        while let Some(file) = self.files.pop() {
+            if let Some(file) = file.await??? {
+                progress.event(BuildEvent::FileDone {
+                    stage: file.stage,
+                    project: self.project,
+                    path: &file.path,
+                    before: file.before,
+                    after: file.after,
+                });
             }
+        }
+
+        for &stage in self.stages.keys() {
+            progress.event(BuildEvent::StageFinished {
+                stage,
+                project: self.project,
+                duration: self.start.elapsed(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ProjectJobs<'_> {
+    /// Files that haven't started yet are dropped once the build failed
+    fn drop(&mut self) {
+        for file in &self.files {
+            file.abort();
+        }
+    }
+}
+
+/// Runs blocking work on the worker pool, at most `jobs` at once
+fn spawn_job(
+    jobs: &Arc<Semaphore>,
+    job: impl FnOnce() -> Result<Option<ProcessedFile>, BuildError> + Send + 'static,
+) -> FileJob {
+    let jobs = jobs.clone();
+
+    tokio::spawn(async move {
+        // The semaphore is never closed
+        let _permit = jobs.acquire_owned().await;
+        tokio::task::spawn_blocking(job).await
+    })
+}
+
+/// Copies `paths` inside `from` over to `to` as a stage of a project, the folders are created
+/// right away
+fn spawn_copy<'a>(
+    stage: BuildStage,
+    project: &'a str,
+    paths: Vec<PathBuf>,
+    from: &Path,
+    to: &Path,
+    jobs: &Arc<Semaphore>,
+) -> Result<ProjectJobs<'a>, BuildError> {
+    let start = Instant::now();
+    let mut files = vec![];
+    let mut folders = BTreeSet::new();
+
+    for path in paths {
+        let target = to.join(path.strip_prefix(from).unwrap_or(&path));
+
+        if let Some(parent) = target.parent() {
+            if folders.insert(parent.to_path_buf()) {
+                fs::create_dir_all(parent).map_err(|err| IoError::new("create", parent, err))?;
+            }
+        }
+
+        let project = project.to_string();
+        files.push(spawn_job(jobs, move || {
+            let bytes = fs::copy(&path, &target).map_err(|source| BuildError::Copy {
+                from: project,
+                to: target.clone(),
+                source,
+            })?;
+
+            Ok(Some(ProcessedFile {
+                stage,
+                path: target,
+                before: bytes,
+                after: bytes,
+            }))
+        }));
+    }
+
+    Ok(ProjectJobs {
+        project,
+        stages: BTreeMap::from([(stage, files.len())]),
+        start,
+        files,
+    })
+}
+
+/// Runs the plugins and the built-in stages on every file of a built project
+fn spawn_process<'a>(
+    project: &'a str,
+    project_path: &Path,
+    profile: &StrixBuildConfigProfile,
+    plugins: &Arc<Vec<Plugin>>,
+    jobs: &Arc<Semaphore>,
+) -> ProjectJobs<'a> {
+    let start = Instant::now();
+    let mut files = vec![];
+    let mut stages: BTreeMap<BuildStage, usize> = BTreeMap::new();
+
+    let walk = WalkDir::new(project_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_file());
+
+    for entry in walk {
+        let plugins = plugins.clone();
+        let stage = file_stage(entry.path(), profile);
+        let relative = entry
+            .path()
+            .strip_prefix(project_path)
+            .unwrap_or(entry.path())
+            .components()
+            .map(|v| v.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if let Some(stage) = stage {
+            *stages.entry(stage).or_default() += 1;
+        }
+
+        files.push(spawn_job(jobs, move || {
+            apply_plugins(&plugins, entry.path(), &relative)?;
 
             /* DO SOME PROCESSING LIKE MINIFICATION, COMPRESSION, ENCRYPTION, OBFUSCATION */
+
+            let Some(stage) = stage else {
+                return Ok(None);
+            };
+            let (before, after) = process_file(entry.path());
+
+            Ok(Some(ProcessedFile {
+                stage,
+                path: entry.into_path(),
+                before,
+                after,
+            }))
+        }));
+    }
+
+    ProjectJobs {
+        project,
+        stages,
+        start,
+        files,
+    }
+}
+
+/// The built-in stage a file goes through, picked by its extension
+fn file_stage(path: &Path, profile: &StrixBuildConfigProfile) -> Option<BuildStage> {
+    match path.extension().and_then(OsStr::to_str)? {
+        "json" | "lang" | "mcfunction" if profile.minify => Some(BuildStage::Minify),
+        "ogg" if profile.compress => Some(BuildStage::Compress),
+        _ => None,
+    }
+}
+
+/// Runs the built-in stage of a file, returns its size before and after
+fn process_file(path: &Path) -> (u64, u64) {
+    let before = file_size(path);
+
+    match path.extension().and_then(OsStr::to_str) {
+        Some("json") => minify_json(path),
+        Some("lang") => minify_lang(path),
+        Some("mcfunction") => minify_mcfunction(path),
+        Some("ogg") => compress_ogg(path),
+        _ => {}
+    }
+
+    (before, file_size(path))
+}
+
+/// Runs every plugin matching the file in order, each one gets the output of the previous one
+fn apply_plugins(plugins: &[Plugin], path: &Path, relative: &str) -> Result<(), BuildError> {
+    let mut matching = plugins.iter().filter(|v| v.matches(relative)).peekable();
+    if matching.peek().is_none() {
+        return Ok(());
+    }
+
+    let mut content = fs::read(path).map_err(|err| IoError::new("read", path, err))?;
+    let mut changed = false;
+
+    for plugin in matching {
+        if let Some(output) = plugin.transform(relative, content.clone())? {
+            content = output;
+            changed = true;
         }
+    }
+
+    if changed {
+        fs::write(path, content).map_err(|err| IoError::new("write", path, err))?;
+    }
+
+    Ok(())
+}
+
+/// Checks every project manifest before anything gets copied or packaged
+fn validate_manifests(
+    project: &Project,
+    diagnostics: &Diagnostics,
+    files: &mut Vec<PathBuf>,
+) -> bool {
+    let mut error_out = false;
 
-        if profile.dev_folder {
-            let mojang_folder = get_mojang_folder();
-
-            match project_type {
-                StrixConfigPackType::Behaviour => {
-                    let path = mojang_folder
-                        .join("development_behavior_packs")
-                        .join(project);
-                    try_make_dir!(path);
-
-                    match copy_dir_all(&project_path, &path) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            error!("An unexpected Error occurred while trying to copy {project:?} to {project_path:?}, Err: {err}");
-                            return true;
-                        }
-                    }
-                }
-                StrixConfigPackType::Resource => {
-                    let path = mojang_folder
-                        .join("development_resource_packs")
-                        .join(project);
-                    try_make_dir!(path);
-
-                    match copy_dir_all(&project_path, &path) {
-                        Ok(_) => {}
-                        Err(err) => {
-                            error!("An unexpected Error occurred while trying to copy {project:?} to {project_path:?}, Err: {err}");
-                            return true;
-                        }
-                    }
-                }
-                _ => {}
+    let mut projects: Vec<(&String, &StrixConfigPackType)> =
+        project.config.projects.iter().collect();
+    projects.sort_by_key(|(name, _)| name.as_str());
+
+    for (name, pack_type) in projects {
+        let path = project.path(name).join(MANIFEST);
+        files.push(path.clone());
+
+        let text = match fs::read_to_string(&path) {
+            Ok(v) => v,
+            Err(err) => {
+                error!(
+                    "An unexpected Error occurred while trying to read {:?}, Err: {err}",
+                    path.display()
+                );
+                error_out = true;
+                continue;
             }
+        };
+
+        let json = match json::parse(&text) {
+            Ok(v) => v,
+            Err((range, message)) => {
+                diagnostics.report(Diagnostic::new("broken-json", &path, Some(range), message));
+                error_out = true;
+                continue;
+            }
+        };
+
+        for problem in validate_manifest(&json, pack_type) {
+            diagnostics.report(Diagnostic::new(
+                "invalid-manifest",
+                &path,
+                json::locate_range(&text, &problem.path),
+                problem.message,
+            ));
+            error_out = true;
         }
     }
 
-    if profile.package {
-        if let Err(err) = zip_dir(
-            project_paths,
-            &target_folder.join(format!("{}.mcaddon", config.name)),
-            quiet,
-        ) {
+    error_out
+}
+
+/// Validates all JSON files against the bundled schemas
+fn validate_files(
+    project: &Project,
+    filter: &FileFilter,
+    progress: &mut impl BuildProgress,
+    diagnostics: &Diagnostics,
+    files: &mut Vec<PathBuf>,
+) -> bool {
+    progress.event(BuildEvent::Validating);
+
+    let ctx = LintContext::load(&project.root, &project.config, filter);
+    diagnostics.extend(run_rules(
+        &ctx,
+        &project.config.lint,
+        Some(&["broken-json", "schema"]),
+    ));
+    files.extend(ctx.files.into_iter().map(|v| v.path));
+
+    diagnostics.has_errors()
+}
+
+fn minify_json(path: &Path) {
+    let text = match fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+
+    let stripped = StripComments::new(text.as_bytes());
+    let mut json: Value = match serde_json::from_reader(stripped) {
+        Ok(v) => v,
+        Err(err) => {
+            error!(
+                "An unexpected Error occurred while trying to deserialize {:?}\n{}",
+                try_rm_prefix(path).display(),
+                err
+            );
+            return;
+        }
+    };
+
+    molang::minify(&mut json);
+
+    let text = match serde_json::to_string(&json) {
+        Ok(v) => v,
+        Err(err) => {
+            error!(
+                "An unexpected Error occurred while trying to serialize {:?}\n{}",
+                try_rm_prefix(path).display(),
+                err
+            );
+            text
+        }
+    };
+
+    if let Err(err) = fs::write(path, text) {
+        error!(
+            "An unexpected Error occurred while trying to write {:?}\n{}",
+            try_rm_prefix(path).display(),
+            err
+        );
+    };
+}
+
+fn minify_lang(path: &Path) {
+    let text = match fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+
+    if let Err(err) = fs::write(path, lang::minify(&text)) {
+        error!(
+            "An unexpected Error occurred while trying to write {:?}\n{}",
+            try_rm_prefix(path).display(),
+            err
+        );
+    };
+}
+
+fn minify_mcfunction(path: &Path) {
+    let text = match fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(_) => return,
+    };
+
+    if let Err(err) = fs::write(path, mcfunction::minify(&text)) {
+        error!(
+            "An unexpected Error occurred while trying to write {:?}\n{}",
+            try_rm_prefix(path).display(),
+            err
+        );
+    };
+}
+
+fn compress_ogg(path: &Path) {
+    let input = current_dir().unwrap().join(path);
+    let output_path = current_dir()
+        .unwrap()
+        .join(path.display().to_string() + ".tmp");
+
+    match Command::new("ffmpeg")
+        .args([
+            "-y",
+            "-i",
+            &input.display().to_string(),
+            "-qscale:a",
+            "8",
+            "-f",
+            "ogg",
+            &output_path.display().to_string(),
+        ])
+        .output()
+    {
+        Ok(output) => {
+            if output.status.success() {
+                if let Err(err) = fs::rename(&output_path, &input) {
+                    error!(
+                        "An unexpected Error occurred while trying to rename {:?} to {:?}\n{}",
+                        output_path, input, err
+                    );
+                };
+            } else {
+                error!(
+                    "An unexpected Error occurred while trying to execute ffmpeg for {:?}\n{}",
+                    try_rm_prefix(path).display(),
+                    String::from_utf8_lossy(output.stderr.as_slice())
+                );
+            }
+        }
+        Err(err) => {
             error!(
-                "An unexpected Error occurred while trying to zip {:?}, Err: {err}",
-                config.name
+                "An unexpected Error occurred while trying to execute ffmpeg for {:?}\n{}",
+                try_rm_prefix(path).display(),
+                err
             );
-            return true;
         }
     }
+}
 
-    false
+/// The timestamp of every entry in a package, `SOURCE_DATE_EPOCH` or 1980-01-01 without it
+///
+/// See <https://reproducible-builds.org/specs/source-date-epoch/>
+fn source_date() -> Result<DateTime, BuildError> {
+    parse_source_date(env::var("SOURCE_DATE_EPOCH").ok())
 }
 
-fn zip_dir(it: Vec<(WalkDir, &Path, &Path)>, path: &Path, quiet: bool) -> anyhow::Result<()> {
+/// The package timestamp for a `SOURCE_DATE_EPOCH` value, in seconds since the unix epoch
+fn parse_source_date(value: Option<String>) -> Result<DateTime, BuildError> {
+    let Some(value) = value else {
+        return Ok(DateTime::default());
+    };
+
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|v| chrono::DateTime::from_timestamp(v, 0))
+        .and_then(|v| {
+            DateTime::from_date_and_time(
+                u16::try_from(v.year()).ok()?,
+                v.month() as u8,
+                v.day() as u8,
+                v.hour() as u8,
+                v.minute() as u8,
+                v.second() as u8,
+            )
+            .ok()
+        })
+        .ok_or(BuildError::SourceDateEpoch(value))
+}
+
+/// Zips the folders, `on_file` gets every zipped file with its size
+///
+/// The archive only depends on the files, entries are sorted by name and get the same timestamp,
+/// permissions and compression level.
+fn zip_dir(
+    it: Vec<(WalkDir, &Path, &Path)>,
+    path: &Path,
+    modified: DateTime,
+    on_file: &mut dyn FnMut(&Path, u64),
+) -> anyhow::Result<()> {
     let file = File::create(path)?;
 
     let mut zip = zip::ZipWriter::new(file);
     let options = SimpleFileOptions::default()
         .compression_method(CompressionMethod::Deflated)
-        .unix_permissions(0o755);
+        .compression_level(Some(COMPRESSION_LEVEL))
+        .last_modified_time(modified);
+    let file_options = options.unix_permissions(0o644);
+    let dir_options = options.unix_permissions(0o755);
 
     let mut buffer = Vec::new();
 
     for (walk, parent, prefix) in it {
-        let walk = walk.into_iter();
+        let walk = walk.sort_by_file_name().into_iter();
         let walk = walk.filter_map(|e| e.ok());
 
         for entry in walk {
@@ -352,18 +1485,15 @@ fn zip_dir(it: Vec<(WalkDir, &Path, &Path)>, path: &Path, quiet: bool) -> anyhow
                 .with_context(|| format!("{name:?} Is a Non UTF-8 Path"))?;
 
             if path.is_file() {
-                if !quiet {
-                    println!("Zipping file {path:?}");
-                }
-
-                zip.start_file(path_as_string, options)?;
+                zip.start_file(path_as_string, file_options)?;
                 let mut f = File::open(path)?;
 
                 f.read_to_end(&mut buffer)?;
                 zip.write_all(&buffer)?;
+                on_file(path, buffer.len() as u64);
                 buffer.clear();
             } else if !name.as_os_str().is_empty() {
-                zip.add_directory(path_as_string, options)?;
+                zip.add_directory(path_as_string, dir_options)?;
             }
         }
     }
@@ -371,3 +1501,292 @@ fn zip_dir(it: Vec<(WalkDir, &Path, &Path)>, path: &Path, quiet: bool) -> anyhow
     zip.finish()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A project with a behaviour and a resource pack, inside a fresh temp folder
+    fn fixture(name: &str) -> Project {
+        let root = env::temp_dir().join(format!("strix-build-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let files = [
+            (
+                "FooBP/manifest.json",
+                r#"{ "format_version": 2, "header": { "name": "Foo", "description": "", "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11", "version": [1, 0, 0], "min_engine_version": [1, 20, 80] }, "modules": [{ "type": "data", "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a12", "version": [1, 0, 0] }] }"#,
+            ),
+            (
+                "FooBP/functions/hello.mcfunction",
+                "# greets everyone\nsay hello\n\n",
+            ),
+            ("FooBP/texts/en_US.lang", "## header\npack.name=Foo\t#\n"),
+            (
+                "FooRP/manifest.json",
+                r#"{ "format_version": 2, "header": { "name": "Foo", "description": "", "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a13", "version": [1, 0, 0], "min_engine_version": [1, 20, 80] }, "modules": [{ "type": "resources", "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a14", "version": [1, 0, 0] }] }"#,
+            ),
+            ("FooRP/texts/en_US.lang", "pack.name=Foo\n"),
+        ];
+        for (path, text) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        for i in 0..16 {
+            let path = root.join(format!("FooRP/data/file_{i}.json"));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(
+                path,
+                format!("{{\n  \"value\": {i},\n  \"list\": [ 1, 2, 3 ]\n}}\n"),
+            )
+            .unwrap();
+        }
+
+        let mut config = StrixConfig {
+            name: String::from("Foo"),
+            ..StrixConfig::default()
+        };
+        config
+            .projects
+            .insert(String::from("FooBP"), StrixConfigPackType::Behaviour);
+        config
+            .projects
+            .insert(String::from("FooRP"), StrixConfigPackType::Resource);
+        for profile in config.build.profiles.values_mut() {
+            profile.dev_folder = false;
+        }
+
+        Project::new(root, config)
+    }
+
+    /// Every file below `path` with its content, relative to `path`
+    fn read_tree(path: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+        walk_files(path)
+            .into_iter()
+            .map(|v| {
+                (
+                    v.strip_prefix(path).unwrap().to_owned(),
+                    fs::read(&v).unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    async fn build_with(project: &Project, jobs: usize, output: &str) -> BuildOutput {
+        let options = BuildOptions {
+            jobs,
+            output: Some(project.path(output)),
+            ..Default::default()
+        };
+        run(project, "release", &options, &mut ()).await.unwrap()
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hooks_get_the_build_env() {
+        let mut project = fixture("hook-env");
+        let profile = project.config.build.profiles.get_mut("release").unwrap();
+        profile.hooks.pre_build = vec![String::from("env | grep ^STRIX_ | sort > pre.env")];
+        profile.hooks.post_build = vec![String::from("env | grep ^STRIX_ | sort > post.env")];
+        profile.hooks.post_package = vec![String::from("env | grep ^STRIX_ | sort > package.env")];
+
+        let output = build_with(&project, 2, "target").await;
+        let read_env = |name: &str| -> BTreeMap<String, String> {
+            fs::read_to_string(project.path(name))
+                .unwrap()
+                .lines()
+                .filter_map(|v| v.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        };
+
+        let pre = read_env("pre.env");
+        let build_dir = absolute(output.projects[0].parent().unwrap());
+        assert_eq!(pre["STRIX_PROFILE"], "release");
+        assert_eq!(pre["STRIX_PROJECTS"], "FooBP:FooRP");
+        assert_eq!(
+            pre["STRIX_PROJECT_ROOT"],
+            absolute(&project.root).to_string_lossy()
+        );
+        assert_eq!(pre["STRIX_BUILD_DIR"], build_dir.to_string_lossy());
+        assert!(!pre.contains_key("STRIX_ARTIFACTS"));
+
+        let post = read_env("post.env");
+        assert_eq!(
+            post["STRIX_ARTIFACTS"],
+            format!(
+                "{}:{}",
+                build_dir.join("FooBP").display(),
+                build_dir.join("FooRP").display()
+            )
+        );
+        assert!(!post.contains_key("STRIX_PACKAGE"));
+
+        let package = read_env("package.env");
+        assert_eq!(
+            package["STRIX_PACKAGE"],
+            absolute(&output.package.unwrap()).to_string_lossy()
+        );
+
+        fs::remove_dir_all(&project.root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failing_hooks_abort_the_build() {
+        let mut project = fixture("hook-fail");
+        let profile = project.config.build.profiles.get_mut("release").unwrap();
+        profile.hooks.pre_build = vec![
+            String::from("echo generating; echo broken >&2; exit 1"),
+            String::from("touch second"),
+        ];
+
+        let options = BuildOptions {
+            output: Some(project.path("target")),
+            ..Default::default()
+        };
+        let err = run(&project, "release", &options, &mut ())
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            &err,
+            BuildError::Hook(HookError::Failed { output, .. }) if output == "generating\nbroken"
+        ));
+        assert_eq!(err.exit_code(), ExitCode::External);
+        assert!(!project.path("second").exists());
+        assert!(!project.path("target/Foo.mcaddon").exists());
+
+        fs::remove_dir_all(&project.root).unwrap();
+    }
+
+    /// Writes the files in the given order, all with the same modification time
+    fn write_tree(path: &Path, files: &[&str], modified: std::time::SystemTime) {
+        for name in files {
+            let file = path.join(name);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(&file, format!("content of {name}")).unwrap();
+            File::options()
+                .write(true)
+                .open(&file)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+    }
+
+    fn zip_hash(root: &Path, modified: DateTime) -> String {
+        let output = root.with_extension("zip");
+        let it = vec![(WalkDir::new(root.join("Pack")), Path::new("Pack"), root)];
+        zip_dir(it, &output, modified, &mut |_, _| {}).unwrap();
+        hash_file(&output).unwrap().1
+    }
+
+    #[test]
+    fn zips_are_reproducible() {
+        let root = env::temp_dir().join(format!("strix-zip-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let files = [
+            "Pack/b.json",
+            "Pack/a/c.json",
+            "Pack/a.json",
+            "Pack/z/y/x.lang",
+        ];
+
+        let first = root.join("first");
+        write_tree(&first, &files, std::time::UNIX_EPOCH);
+
+        let mut reversed = files;
+        reversed.reverse();
+        let second = root.join("second");
+        write_tree(&second, &reversed, std::time::SystemTime::now());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = second.join("Pack/b.json");
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+            let path = second.join("Pack/a");
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o700)).unwrap();
+        }
+
+        let modified = parse_source_date(Some(String::from("1700000000"))).unwrap();
+        assert_eq!(zip_hash(&first, modified), zip_hash(&second, modified));
+        assert_ne!(
+            zip_hash(&first, modified),
+            zip_hash(&first, DateTime::default())
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn compares_hashes_by_path() {
+        let first = BTreeMap::from([
+            (PathBuf::from("Foo.mcaddon"), String::from("a")),
+            (PathBuf::from("FooBP"), String::from("b")),
+            (PathBuf::from("FooRP"), String::from("c")),
+        ]);
+        let second = BTreeMap::from([
+            (PathBuf::from("Foo.mcaddon"), String::from("a")),
+            (PathBuf::from("FooRP"), String::from("d")),
+            (PathBuf::from("FooSkins"), String::from("e")),
+        ]);
+
+        assert!(compare_hashes(&first, &first).is_empty());
+        assert_eq!(
+            compare_hashes(&first, &second),
+            [
+                PathBuf::from("FooBP"),
+                PathBuf::from("FooRP"),
+                PathBuf::from("FooSkins")
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_source_date_epoch() {
+        let date = parse_source_date(Some(String::from(" 1700000000\n"))).unwrap();
+        assert_eq!(
+            (
+                date.year(),
+                date.month(),
+                date.day(),
+                date.hour(),
+                date.minute()
+            ),
+            (2023, 11, 14, 22, 13)
+        );
+
+        assert_eq!(parse_source_date(None).unwrap(), DateTime::default());
+        assert!(matches!(
+            parse_source_date(Some(String::from("yesterday"))),
+            Err(BuildError::SourceDateEpoch(_))
+        ));
+        // Zip timestamps start in 1980
+        assert!(parse_source_date(Some(String::from("0"))).is_err());
+    }
+
+    #[tokio::test]
+    async fn parallel_build_matches_serial_build() {
+        let project = fixture("jobs");
+
+        let serial = build_with(&project, 1, "serial").await;
+        let parallel = build_with(&project, 8, "parallel").await;
+
+        assert_eq!(serial.projects.len(), 2);
+        for (serial, parallel) in serial.projects.iter().zip(&parallel.projects) {
+            assert_eq!(serial.file_name(), parallel.file_name());
+
+            let tree = read_tree(serial);
+            assert!(!tree.is_empty());
+            assert_eq!(tree, read_tree(parallel));
+        }
+
+        let serial = fs::read(serial.package.unwrap()).unwrap();
+        let parallel = fs::read(parallel.package.unwrap()).unwrap();
+        assert!(serial == parallel, "the packages differ");
+
+        fs::remove_dir_all(&project.root).unwrap();
+    }
+}
@@ -0,0 +1,326 @@
+use crate::build::BuildOutput;
+use crate::config::StrixConfigPackType;
+use crate::error::IoError;
+use crate::manifest::{read_manifest, MANIFEST};
+use crate::message::ArtifactKind;
+use crate::project::Project;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+pub const BUILD_INFO: &str = "build-info.json";
+
+/// What a build produced, written as `build-info.json` next to the artifacts for release tooling
+#[derive(Serialize, Debug, Clone)]
+pub struct BuildInfo {
+    pub strix_version: String,
+    pub profile: String,
+    /// The commit checked out in the project root, if it is a git repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_commit: Option<String>,
+    pub projects: Vec<ProjectInfo>,
+    pub artifacts: Vec<ArtifactInfo>,
+}
+
+/// A built project, the UUID and version are read from the header of its manifest
+#[derive(Serialize, Debug, Clone)]
+pub struct ProjectInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub pack_type: StrixConfigPackType,
+    pub uuid: Option<String>,
+    pub version: Option<String>,
+    pub files: usize,
+}
+
+/// The package or a dev folder a build produced, folders are hashed with [`hash_dir`]
+///
+/// The build folder isn't listed, it gets a new name every build.
+#[derive(Serialize, Debug, Clone)]
+pub struct ArtifactInfo {
+    pub kind: ArtifactKind,
+    /// Relative to the `build-info.json` and separated by `/`, dev folders are absolute
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// The size and SHA-256 of a folder, with its file count
+#[derive(Debug, Clone)]
+pub struct DirHash {
+    pub files: usize,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl BuildInfo {
+    /// Collects the info of a finished build, artifact paths are made relative to `folder`
+    pub fn collect(
+        project: &Project,
+        profile_name: &str,
+        output: &BuildOutput,
+        folder: &Path,
+    ) -> Result<Self, IoError> {
+        let mut projects = vec![];
+        let mut artifacts = vec![];
+
+        for path in &output.projects {
+            let name = path
+                .file_name()
+                .map(|v| v.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let manifest = read_manifest(&path.join(MANIFEST)).unwrap_or_default();
+
+            projects.push(ProjectInfo {
+                pack_type: project
+                    .config
+                    .projects
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_default(),
+                name,
+                uuid: header(&manifest, "uuid").and_then(|v| v.as_str().map(str::to_string)),
+                version: header(&manifest, "version").and_then(version_text),
+                files: hash_dir(path)?.files,
+            });
+        }
+
+        if let Some(path) = &output.package {
+            let (size, sha256) = hash_file(path)?;
+
+            artifacts.push(ArtifactInfo {
+                kind: ArtifactKind::Package,
+                path: relative(path, folder),
+                size,
+                sha256,
+            });
+        }
+
+        for path in &output.deployed {
+            let hash = hash_dir(path)?;
+
+            artifacts.push(ArtifactInfo {
+                kind: ArtifactKind::DevFolder,
+                path: relative(path, folder),
+                size: hash.size,
+                sha256: hash.sha256,
+            });
+        }
+
+        Ok(Self {
+            strix_version: env!("CARGO_PKG_VERSION").to_string(),
+            profile: profile_name.to_string(),
+            git_commit: git_commit(&project.root),
+            projects,
+            artifacts,
+        })
+    }
+
+    /// Writes the info as `build-info.json` into `folder`
+    pub fn write(&self, folder: &Path) -> Result<PathBuf, IoError> {
+        let path = folder.join(BUILD_INFO);
+        let text = serde_json::to_string_pretty(self).unwrap_or_default();
+
+        fs::write(&path, text + "\n").map_err(|err| IoError::new("write", &path, err))?;
+        Ok(path)
+    }
+}
+
+/// The size and SHA-256 of a file
+pub fn hash_file(path: &Path) -> Result<(u64, String), IoError> {
+    let bytes = fs::read(path).map_err(|err| IoError::new("read", path, err))?;
+    Ok((bytes.len() as u64, format!("{:x}", Sha256::digest(&bytes))))
+}
+
+/// Hashes the relative paths and contents of everything inside a folder, in sorted order
+///
+/// The hash doesn't depend on where the folder is, so builds in fresh folders can be compared.
+pub fn hash_dir(path: &Path) -> Result<DirHash, IoError> {
+    let mut hasher = Sha256::new();
+    let mut files = 0;
+    let mut size = 0;
+
+    for entry in WalkDir::new(path).sort_by_file_name() {
+        let entry = entry.map_err(|err| IoError::new("read", path, err.into()))?;
+        let relative = entry.path().strip_prefix(path).unwrap_or(entry.path());
+
+        hasher.update(relative.to_string_lossy().as_bytes());
+        hasher.update([0]);
+
+        if entry.file_type().is_file() {
+            let bytes =
+                fs::read(entry.path()).map_err(|err| IoError::new("read", entry.path(), err))?;
+            hasher.update((bytes.len() as u64).to_le_bytes());
+            hasher.update(&bytes);
+
+            files += 1;
+            size += bytes.len() as u64;
+        }
+    }
+
+    Ok(DirHash {
+        files,
+        size,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+fn header<'a>(manifest: &'a Value, field: &str) -> Option<&'a Value> {
+    manifest.get("header")?.get(field)
+}
+
+/// Versions are either `[1, 0, 0]` or `"1.0.0"`, both are written as `1.0.0`
+fn version_text(version: &Value) -> Option<String> {
+    match version {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(parts) => Some(
+            parts
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+        ),
+        _ => None,
+    }
+}
+
+/// Paths outside of `folder`, like the dev folders, stay absolute
+fn relative(path: &Path, folder: &Path) -> String {
+    match path.strip_prefix(folder) {
+        Ok(relative) => relative
+            .components()
+            .map(|v| v.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// `git rev-parse HEAD` inside the project root, `None` outside of a repository or without git
+fn git_commit(root: &Path) -> Option<String> {
+    let root = match root.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => root,
+    };
+
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(root)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!commit.is_empty()).then_some(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StrixConfig;
+
+    /// `sha256sum` of `hello`
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn hashes_folders_independent_of_their_location() {
+        let root = std::env::temp_dir().join(format!("strix-hash-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        for folder in ["a", "b/c"] {
+            write(&root.join(folder).join("x/y.json"), "{}");
+            write(&root.join(folder).join("z.lang"), "hello");
+        }
+
+        let a = hash_dir(&root.join("a")).unwrap();
+        assert_eq!((a.files, a.size), (2, 7));
+        assert_eq!(a.sha256, hash_dir(&root.join("b/c")).unwrap().sha256);
+
+        write(&root.join("a/z.lang"), "hellp");
+        assert_ne!(a.sha256, hash_dir(&root.join("a")).unwrap().sha256);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn writes_build_info() {
+        let root = std::env::temp_dir().join(format!("strix-build-info-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let folder = root.join("target");
+        let built = folder.join("build/1234/FooBP");
+        write(
+            &built.join(MANIFEST),
+            r#"{ "header": { "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11", "version": [1, 2, 3] } }"#,
+        );
+        write(&built.join("functions/a.mcfunction"), "say hi\n");
+        write(&folder.join("Foo.mcaddon"), "hello");
+        let deployed = root.join("development_behavior_packs/FooBP");
+        write(&deployed.join(MANIFEST), "{}");
+
+        let mut config = StrixConfig::default();
+        config
+            .projects
+            .insert(String::from("FooBP"), StrixConfigPackType::Behaviour);
+        let project = Project::new(&root, config);
+
+        let output = BuildOutput {
+            projects: vec![built],
+            deployed: vec![deployed.clone()],
+            package: Some(folder.join("Foo.mcaddon")),
+            ..Default::default()
+        };
+
+        let path = BuildInfo::collect(&project, "release", &output, &folder)
+            .unwrap()
+            .write(&folder)
+            .unwrap();
+        assert_eq!(path, folder.join(BUILD_INFO));
+
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let deployed_hash = hash_dir(&deployed).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "strix_version": env!("CARGO_PKG_VERSION"),
+                "profile": "release",
+                "projects": [{
+                    "name": "FooBP",
+                    "type": "Behaviour",
+                    "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11",
+                    "version": "1.2.3",
+                    "files": 2,
+                }],
+                "artifacts": [
+                    {
+                        "kind": "package",
+                        "path": "Foo.mcaddon",
+                        "size": 5,
+                        "sha256": HELLO_SHA256,
+                    },
+                    {
+                        "kind": "dev-folder",
+                        "path": deployed.display().to_string(),
+                        "size": 2,
+                        "sha256": deployed_hash.sha256,
+                    },
+                ],
+            })
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+use crate::add::add;
+use crate::args::{CliInput, CliSubCommand};
+use crate::build::build;
+use crate::config::get_config;
+use crate::error::{ExitCode, StrixError};
+use crate::fmt::fmt;
+use crate::import::import;
+use crate::init::init;
+use crate::lint::lint;
+use crate::message::{emit_summary, set_message_format};
+use crate::new::new;
+use crate::progress;
+use crate::translation::lang;
+use chrono::Local;
+use clap::Parser;
+use fern::colors::{Color, ColoredLevelConfig};
+use fern::Output;
+use log::{error, info, LevelFilter};
+use std::process::exit;
+use tokio::runtime::Builder;
+use tokio::time::Instant;
+
+/// Logs go to stderr, so stdout can be piped, like the objects of `--message-format json`
+fn setup_logger() {
+    let colors = ColoredLevelConfig::new()
+        .debug(Color::Magenta)
+        .info(Color::Cyan)
+        .warn(Color::Yellow)
+        .error(Color::Red);
+
+    let dispatch = fern::Dispatch::new()
+        .format(move |out, message, record| {
+            out.finish(format_args!(
+                "[{} {}] {}",
+                Local::now().format("%H:%M:%S%.3f"),
+                colors.color(record.level()),
+                message
+            ))
+        })
+        .level_for("globset", LevelFilter::Warn)
+        .level_for("ignore", LevelFilter::Warn);
+
+    // Progress bars are hidden while a line is printed, so they don't get torn apart
+    let dispatch = dispatch.chain(Output::call(|record| {
+        progress::suspend(|| eprintln!("{}", record.args()))
+    }));
+
+    dispatch.apply().unwrap_or_else(|err| {
+        eprintln!("An unexpected Error occurred while trying to setup the logger, Err: {err}");
+        exit(1);
+    });
+}
+
+/// Parses the command line and runs the command, what the `strix` binary does
+///
+/// Logs go to stderr and the process exits with an [`ExitCode`] if the command failed.
+pub fn run_cli() {
+    let num_threads = num_cpus::get();
+
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(num_threads)
+        .enable_all()
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "An unexpected Error occurred while trying to setup the tokio runtime, Err: {err}"
+            );
+            exit(1);
+        });
+
+    runtime.block_on(tokio_main());
+}
+
+async fn tokio_main() {
+    let start = Instant::now();
+
+    let args = CliInput::parse();
+
+    set_message_format(args.message_format);
+    setup_logger();
+
+    let command = args.command.name();
+
+    let result = run(args).await;
+
+    let exit_code = match &result {
+        Ok(_) => ExitCode::Success,
+        Err(err) => {
+            error!("{err}");
+            err.exit_code()
+        }
+    };
+
+    let duration = Instant::now().duration_since(start);
+
+    info!("Finished in {}", humantime::format_duration(duration));
+    emit_summary(command, result.is_ok(), duration);
+
+    if exit_code != ExitCode::Success {
+        exit(exit_code as i32);
+    }
+}
+
+async fn run(args: CliInput) -> Result<(), StrixError> {
+    let config = get_config(&args)?;
+
+    match args.command {
+        CliSubCommand::New(v) => new(v).await?,
+        CliSubCommand::Build(v) => build(v, config).await?,
+        CliSubCommand::Config(_) => {}
+        CliSubCommand::Fmt(v) => fmt(v, config).await?,
+        CliSubCommand::Add(v) => add(v, config).await?,
+        CliSubCommand::Import(v) => import(v).await?,
+        CliSubCommand::Init(v) => init(v).await?,
+        CliSubCommand::Lint(v) => lint(v, config).await?,
+        CliSubCommand::Lang(v) => lang(v, config).await?,
+    }
+
+    Ok(())
+}
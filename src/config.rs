@@ -1,29 +1,41 @@
 use crate::args::{CliInput, CliSubCommand};
-use log::error;
+use crate::diagnostic::Severity;
+use crate::index::IndexFile;
+use crate::lang::DEFAULT_PRIMARY_LANGUAGE;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
-use std::process::exit;
+use std::path::Path;
+use thiserror::Error;
 
 pub const STRIX_CONFIG: &str = "strix.json";
 
-pub fn get_config(command: &CliInput) -> Option<StrixConfig> {
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Couldn't find {STRIX_CONFIG:?}, it is required for {0}")]
+    Missing(&'static str),
+    #[error("An unexpected Error occurred while trying to load {STRIX_CONFIG:?} {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error("Invalid glob {glob:?} in {STRIX_CONFIG:?}, Err: {source}")]
+    Glob {
+        glob: String,
+        source: globset::Error,
+    },
+}
+
+pub(crate) fn get_config(command: &CliInput) -> Result<Option<StrixConfig>, ConfigError> {
     match command.command {
-        CliSubCommand::New(_) => None,
-        _ => config_read(),
+        CliSubCommand::New(_) | CliSubCommand::Import(_) | CliSubCommand::Init(_) => Ok(None),
+        _ => read_config(Path::new("")),
     }
 }
 
-fn config_read() -> Option<StrixConfig> {
-    match fs::read_to_string(STRIX_CONFIG) {
-        Ok(text) => match serde_json::from_str(&text) {
-            Ok(v) => Some(v),
-            Err(err) => {
-                error!("An unexpected Error occurred while trying to load {STRIX_CONFIG:?} {err}");
-                exit(1);
-            }
-        },
-        Err(_) => None,
+/// Reads the `strix.json` inside `root`, `None` if there is none
+pub fn read_config(root: &Path) -> Result<Option<StrixConfig>, ConfigError> {
+    match fs::read_to_string(root.join(STRIX_CONFIG)) {
+        Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+        Err(_) => Ok(None),
     }
 }
 
@@ -32,11 +44,18 @@ fn config_read() -> Option<StrixConfig> {
 pub struct StrixConfig {
     pub name: String,
     pub description: String,
+    /// Namespace used for identifiers of generated content, like `namespace:my_entity`
+    #[serde(default)]
+    pub namespace: String,
     pub authors: Option<Vec<String>>,
     pub project_type: StrixConfigProjectType,
     pub projects: HashMap<String, StrixConfigPackType>,
     pub build: StrixBuildConfig,
     pub fmt: StrixFmtConfig,
+    #[serde(default)]
+    pub lint: StrixLintConfig,
+    #[serde(default)]
+    pub files: StrixFilesConfig,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -47,7 +66,7 @@ pub enum StrixConfigProjectType {
     Dash,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub enum StrixConfigPackType {
     #[default]
     Behaviour,
@@ -63,6 +82,10 @@ pub struct StrixFmtConfig {
     pub indent_width: u8,
     pub always_semicolons: bool,
     pub single_quote: bool,
+    #[serde(default)]
+    pub sort_lang_keys: bool,
+    #[serde(default)]
+    pub format_molang: bool,
 }
 
 impl Default for StrixFmtConfig {
@@ -73,10 +96,67 @@ impl Default for StrixFmtConfig {
             indent_width: 4,
             always_semicolons: false,
             single_quote: false,
+            sort_lang_keys: false,
+            format_molang: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StrixLintConfig {
+    /// Severity overrides per rule code, like `"missing-texture": "Warning"`
+    pub rules: HashMap<String, Severity>,
+    /// The language other `.lang` files are compared against, `en_US` if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_language: Option<String>,
+}
+
+impl StrixLintConfig {
+    /// The configured primary language, `en_US` if unset
+    pub fn primary_language(&self) -> &str {
+        self.primary_language
+            .as_deref()
+            .unwrap_or(DEFAULT_PRIMARY_LANGUAGE)
+    }
+}
+
+/// Which files of the projects are built, formatted and linted, see [`crate::files::FileFilter`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct StrixFilesConfig {
+    /// Skip files ignored by `.gitignore` files, `.strixignore` files are always honored
+    pub gitignore: bool,
+    /// Globs relative to every project folder, all files are included if there are none
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    /// Globs relative to every project folder, like `**/*.psd`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+    /// Globs of a single project, added to the ones of every project
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub projects: HashMap<String, StrixProjectFilesConfig>,
+}
+
+impl Default for StrixFilesConfig {
+    fn default() -> Self {
+        Self {
+            gitignore: true,
+            include: vec![],
+            exclude: vec![],
+            projects: HashMap::new(),
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct StrixProjectFilesConfig {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct StrixBuildConfig {
     pub build_path: String,
@@ -99,6 +179,12 @@ impl Default for StrixBuildConfig {
                         encrypt: false,
                         dev_folder: true,
                         package: false,
+                        validate: true,
+                        filters: vec![],
+                        plugins: vec![],
+                        hooks: StrixBuildHooks::default(),
+                        indexes: StrixBuildIndexes::default(),
+                        flipbooks: vec![],
                     },
                 ),
                 (
@@ -110,6 +196,12 @@ impl Default for StrixBuildConfig {
                         encrypt: true,
                         dev_folder: false,
                         package: true,
+                        validate: true,
+                        filters: vec![],
+                        plugins: vec![],
+                        hooks: StrixBuildHooks::default(),
+                        indexes: StrixBuildIndexes::default(),
+                        flipbooks: vec![],
                     },
                 ),
             ]),
@@ -131,4 +223,131 @@ pub struct StrixBuildConfigProfile {
     pub dev_folder: bool,
     /// Package all projects into one `.mcaddon` file
     pub package: bool,
+    /// Validate JSON files against the bundled Bedrock schemas before building
+    #[serde(default)]
+    pub validate: bool,
+    /// External filters run in order on the build folder, after copying and before processing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<StrixFilterConfig>,
+    /// WebAssembly plugins which transform the files matching their globs, relative to the root
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<String>,
+    /// Shell commands run before and after the steps of the build
+    #[serde(default, skip_serializing_if = "StrixBuildHooks::is_empty")]
+    pub hooks: StrixBuildHooks,
+    /// Index files regenerated from the processed files of every project
+    #[serde(default, skip_serializing_if = "StrixBuildIndexes::is_empty")]
+    pub indexes: StrixBuildIndexes,
+    /// Folders of numbered frames stacked into animated textures, before the indexes are written
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flipbooks: Vec<StrixFlipbookConfig>,
+}
+
+/// Shell commands run in the project root, a failing command aborts the build
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StrixBuildHooks {
+    /// Run before anything is validated or copied, like code generation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_build: Vec<String>,
+    /// Run after all projects are processed and copied into the dev folders, before packaging
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_build: Vec<String>,
+    /// Run after the `.mcaddon` is written, only if the profile packages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_package: Vec<String>,
+}
+
+impl StrixBuildHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_build.is_empty() && self.post_build.is_empty() && self.post_package.is_empty()
+    }
+}
+
+/// Index files written into the built projects, checked-in ones are overwritten with a warning
+/// if they disagree. Every index is off unless the profile enables it, in the built-in profiles
+/// too, so existing projects build the same files as before
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StrixBuildIndexes {
+    /// `textures/textures_list.json` of resource packs, off by default
+    #[serde(default)]
+    pub textures_list: bool,
+    /// `contents.json` of every pack, off by default
+    #[serde(default)]
+    pub contents: bool,
+}
+
+impl StrixBuildIndexes {
+    pub fn is_empty(&self) -> bool {
+        !self.textures_list && !self.contents
+    }
+
+    /// The enabled indexes, `contents.json` last so it lists the other ones
+    pub fn files(&self) -> Vec<IndexFile> {
+        let mut files = vec![];
+
+        if self.textures_list {
+            files.push(IndexFile::TexturesList);
+        }
+        if self.contents {
+            files.push(IndexFile::Contents);
+        }
+
+        files
+    }
+}
+
+/// A folder of frames like `0.png` … `7.png` inside a resource pack, which becomes a vertical strip
+/// with an entry in `textures/flipbook_textures.json`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StrixFlipbookConfig {
+    /// The frames folder relative to the pack, like `textures/blocks/my_block`, the strip is
+    /// written to `textures/blocks/my_block.png`
+    pub frames: String,
+    /// The `atlas_tile` of the entry, new entries use the name of the frames folder if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas_tile: Option<String>,
+    /// Game ticks every frame is shown for, there are 20 ticks per second
+    pub ticks_per_frame: u32,
+}
+
+/// An external command, or a script run by an installed interpreter, which edits the build folder
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StrixFilterConfig {
+    /// Shown in logs and errors
+    pub name: String,
+    /// The program to run, like `python` or `./filters/sort.sh`, relative to the project root
+    pub command: String,
+    /// A script passed to `command` as first argument, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Passed to the filter as JSON in the last argument
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub settings: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexes_default_to_off() {
+        let profile: StrixBuildConfigProfile = serde_json::from_str(
+            r#"{
+                "minify": false,
+                "obfuscate": false,
+                "compress": false,
+                "encrypt": false,
+                "dev_folder": true,
+                "package": false
+            }"#,
+        )
+        .unwrap();
+        assert!(profile.indexes.files().is_empty());
+
+        for (name, profile) in StrixBuildConfig::default().profiles {
+            assert!(profile.indexes.files().is_empty(), "{name}");
+        }
+    }
 }
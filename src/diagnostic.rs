@@ -0,0 +1,176 @@
+use crate::message::{self, Message};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Off => write!(f, "off"),
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A 1-based line and column inside a file
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Converts a byte offset into `text` into a line and column, columns count characters
+    pub fn from_offset(text: &str, offset: usize) -> Self {
+        let mut offset = offset.min(text.len());
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &text[..offset];
+
+        let line = before.matches('\n').count() + 1;
+        let column = match before.rfind('\n') {
+            Some(v) => before[v + 1..].chars().count() + 1,
+            None => before.chars().count() + 1,
+        };
+
+        Self { line, column }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl TextRange {
+    pub fn from_offsets(text: &str, start: usize, end: usize) -> Self {
+        Self {
+            start: Position::from_offset(text, start),
+            end: Position::from_offset(text, end),
+        }
+    }
+
+    pub fn line(line: usize, column: usize) -> Self {
+        let position = Position { line, column };
+
+        Self {
+            start: position,
+            end: position,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub file: PathBuf,
+    pub range: Option<TextRange>,
+    /// More places in the file the diagnostic applies to, like the later hunks of an
+    /// unformatted file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_ranges: Vec<TextRange>,
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(
+        code: impl Into<String>,
+        file: impl Into<PathBuf>,
+        range: Option<TextRange>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            file: file.into(),
+            range,
+            additional_ranges: vec![],
+            severity: Severity::Error,
+            code: code.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Logs the diagnostic, or prints it as JSON with `--message-format json`
+    pub fn log(&self) {
+        message::count(self.severity);
+
+        if message::is_json() {
+            message::emit(&Message::Diagnostic(self));
+            return;
+        }
+
+        let location = match &self.range {
+            Some(range) => format!(
+                "{}:{}:{}",
+                self.file.display(),
+                range.start.line,
+                range.start.column
+            ),
+            None => format!("{}", self.file.display()),
+        };
+
+        match self.severity {
+            Severity::Off => {}
+            Severity::Info => info!("{location}: [{}] {}", self.code, self.message),
+            Severity::Warning => warn!("{location}: [{}] {}", self.code, self.message),
+            Severity::Error => error!("{location}: [{}] {}", self.code, self.message),
+        }
+    }
+}
+
+/// Collects the diagnostics of a command, shared between threads
+#[derive(Debug, Default)]
+pub struct Diagnostics(Mutex<Vec<Diagnostic>>);
+
+impl Diagnostics {
+    /// Logs the diagnostic and keeps it for the summary and reports
+    pub fn report(&self, diagnostic: Diagnostic) {
+        diagnostic.log();
+        self.push(diagnostic);
+    }
+
+    /// Keeps the diagnostic without logging it, for callers that show it in their own way
+    pub fn push(&self, diagnostic: Diagnostic) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(diagnostic);
+    }
+
+    pub fn extend(&self, diagnostics: impl IntoIterator<Item = Diagnostic>) {
+        for diagnostic in diagnostics {
+            self.report(diagnostic);
+        }
+    }
+
+    pub fn count(&self, severity: Severity) -> usize {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|v| v.severity == severity)
+            .count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.count(Severity::Error) > 0
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.0.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::add::AddError;
+use crate::build::BuildError;
+use crate::config::ConfigError;
+use crate::fmt::FmtError;
+use crate::import::ImportError;
+use crate::init::InitError;
+use crate::lint::LintError;
+use crate::new::NewError;
+use crate::translation::LangError;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The exit codes of `strix`, so wrappers can tell "needs formatting" apart from a crash
+///
+/// | Code | Meaning                                                        |
+/// |------|----------------------------------------------------------------|
+/// | 0    | Success                                                        |
+/// | 1    | `fmt --check` found files that need formatting                 |
+/// | 2    | Invalid command line arguments, reported by `clap`             |
+/// | 3    | Validation errors, like lint errors or an unreproducible build |
+/// | 4    | A missing or invalid `strix.json`, or an unknown profile       |
+/// | 5    | Files or prompts couldn't be read or written                   |
+/// | 6    | A build filter, plugin or hook failed                          |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success = 0,
+    Unformatted = 1,
+    Validation = 3,
+    Config = 4,
+    Io = 5,
+    External = 6,
+}
+
+pub const EXIT_CODES_HELP: &str = "Exit codes:
+  0  Success
+  1  `fmt --check` found files that need formatting
+  2  Invalid command line arguments
+  3  Validation errors, like lint errors or an unreproducible build
+  4  A missing or invalid `strix.json`, or an unknown profile
+  5  Files or prompts couldn't be read or written
+  6  A build filter, plugin or hook failed";
+
+/// The error of any command
+#[derive(Error, Debug)]
+pub(crate) enum StrixError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    New(#[from] NewError),
+    #[error(transparent)]
+    Build(#[from] BuildError),
+    #[error(transparent)]
+    Fmt(#[from] FmtError),
+    #[error(transparent)]
+    Add(#[from] AddError),
+    #[error(transparent)]
+    Import(#[from] ImportError),
+    #[error(transparent)]
+    Init(#[from] InitError),
+    #[error(transparent)]
+    Lint(#[from] LintError),
+    #[error(transparent)]
+    Lang(#[from] LangError),
+}
+
+impl StrixError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            StrixError::Config(_) => ExitCode::Config,
+            StrixError::New(err) => err.exit_code(),
+            StrixError::Build(err) => err.exit_code(),
+            StrixError::Fmt(err) => err.exit_code(),
+            StrixError::Add(err) => err.exit_code(),
+            StrixError::Import(err) => err.exit_code(),
+            StrixError::Init(err) => err.exit_code(),
+            StrixError::Lint(err) => err.exit_code(),
+            StrixError::Lang(err) => err.exit_code(),
+        }
+    }
+}
+
+/// A file system operation that failed, shared by all commands
+#[derive(Error, Debug)]
+#[error("An unexpected Error occurred while trying to {action} {path:?}, Err: {source}")]
+pub struct IoError {
+    pub action: &'static str,
+    pub path: PathBuf,
+    #[source]
+    pub source: io::Error,
+}
+
+impl IoError {
+    pub fn new(action: &'static str, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self {
+            action,
+            path: path.into(),
+            source,
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use crate::config::{ConfigError, StrixConfig};
+use crate::filter::absolute;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// Ignore files with the syntax of `.gitignore`, honored in every folder and its parents
+pub const STRIX_IGNORE: &str = ".strixignore";
+
+/// Decides which files `build`, `fmt` and `lint` look at
+///
+/// Files ignored by a `.strixignore`, or a `.gitignore` unless `files.gitignore` is off, are
+/// skipped everywhere. Files inside a project folder also have to match the `include` globs of
+/// the config, if there are any, and none of the `exclude` globs. Globs are relative to the
+/// project folder, like `textures/**/*.psd`.
+#[derive(Debug, Clone)]
+pub struct FileFilter {
+    gitignore: bool,
+    projects: Vec<ProjectGlobs>,
+}
+
+#[derive(Debug, Clone)]
+struct ProjectGlobs {
+    /// Absolute, so paths from any walk can be matched
+    folder: PathBuf,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl FileFilter {
+    /// Compiles the globs of the config, project folders are relative to `root`
+    pub fn new(root: &Path, config: &StrixConfig) -> Result<Self, ConfigError> {
+        let files = &config.files;
+        let mut projects = vec![];
+
+        for name in config.projects.keys() {
+            let project = files.projects.get(name);
+            let include: Vec<&String> = files
+                .include
+                .iter()
+                .chain(project.iter().flat_map(|v| &v.include))
+                .collect();
+            let exclude: Vec<&String> = files
+                .exclude
+                .iter()
+                .chain(project.iter().flat_map(|v| &v.exclude))
+                .collect();
+
+            projects.push(ProjectGlobs {
+                folder: absolute(&root.join(name)),
+                include: match include.is_empty() {
+                    true => None,
+                    false => Some(glob_set(&include)?),
+                },
+                exclude: glob_set(&exclude)?,
+            });
+        }
+
+        Ok(Self {
+            gitignore: files.gitignore,
+            projects,
+        })
+    }
+
+    /// Every file below `path` which isn't filtered out, sorted by path
+    pub fn walk(&self, path: &Path) -> Vec<PathBuf> {
+        WalkBuilder::new(path)
+            .standard_filters(false)
+            .hidden(false)
+            .parents(true)
+            .git_ignore(self.gitignore)
+            .git_exclude(self.gitignore)
+            .require_git(false)
+            .add_custom_ignore_filename(STRIX_IGNORE)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build()
+            .filter_map(|v| v.ok())
+            .filter(|v| v.file_type().is_some_and(|v| v.is_file()))
+            .map(|v| v.into_path())
+            .filter(|v| self.is_included(v))
+            .collect()
+    }
+
+    /// If a file passes the globs of the project it is in, ignore files aren't checked
+    pub fn is_included(&self, path: &Path) -> bool {
+        if path.file_name().is_some_and(|v| v == STRIX_IGNORE) {
+            return false;
+        }
+
+        let path = absolute(path);
+
+        for project in &self.projects {
+            let Ok(relative) = path.strip_prefix(&project.folder) else {
+                continue;
+            };
+
+            return project
+                .include
+                .as_ref()
+                .is_none_or(|v| v.is_match(relative))
+                && !project.exclude.is_match(relative);
+        }
+
+        true
+    }
+}
+
+fn glob_set(globs: &[&String]) -> Result<GlobSet, ConfigError> {
+    let mut builder = GlobSetBuilder::new();
+
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|source| ConfigError::Glob {
+            glob: glob.to_string(),
+            source,
+        })?);
+    }
+
+    builder.build().map_err(|source| ConfigError::Glob {
+        glob: globs
+            .iter()
+            .map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{StrixConfigPackType, StrixProjectFilesConfig};
+    use std::fs;
+
+    fn tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("strix-files-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let files = [
+            (".gitignore", "*.log\n"),
+            ("A/.strixignore", "*.tmp\n!keep.tmp\n!important.log\n"),
+            ("A/a.json", ""),
+            ("A/debug.log", ""),
+            ("A/important.log", ""),
+            ("A/x.tmp", ""),
+            ("A/keep.tmp", ""),
+            ("A/secret/b.json", ""),
+            ("B/c.json", ""),
+            ("B/d.png", ""),
+            ("B/e.psd", ""),
+        ];
+        for (path, text) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+
+        root
+    }
+
+    fn config(gitignore: bool) -> StrixConfig {
+        let mut config = StrixConfig::default();
+        config
+            .projects
+            .insert(String::from("A"), StrixConfigPackType::Behaviour);
+        config
+            .projects
+            .insert(String::from("B"), StrixConfigPackType::Resource);
+
+        config.files.gitignore = gitignore;
+        config.files.exclude = vec![String::from("**/*.psd")];
+        config.files.projects.insert(
+            String::from("A"),
+            StrixProjectFilesConfig {
+                include: vec![],
+                exclude: vec![String::from("secret/**")],
+            },
+        );
+        config.files.projects.insert(
+            String::from("B"),
+            StrixProjectFilesConfig {
+                include: vec![String::from("**/*.png"), String::from("**/*.psd")],
+                exclude: vec![],
+            },
+        );
+        config
+    }
+
+    fn walk(filter: &FileFilter, path: &Path) -> Vec<String> {
+        filter
+            .walk(path)
+            .into_iter()
+            .map(|v| v.strip_prefix(path).unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn honors_ignore_files_and_negation() {
+        let root = tree("ignore");
+        let filter = FileFilter::new(&root, &config(true)).unwrap();
+
+        // `.strixignore` takes precedence over `.gitignore`, so `!important.log` wins
+        assert_eq!(
+            walk(&filter, &root.join("A")),
+            ["a.json", "important.log", "keep.tmp"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn gitignore_can_be_turned_off() {
+        let root = tree("no-gitignore");
+        let filter = FileFilter::new(&root, &config(false)).unwrap();
+
+        assert_eq!(
+            walk(&filter, &root.join("A")),
+            ["a.json", "debug.log", "important.log", "keep.tmp"]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn combines_global_and_project_globs() {
+        let root = tree("globs");
+        let filter = FileFilter::new(&root, &config(true)).unwrap();
+
+        // The project includes `*.psd`, the global exclude still removes it
+        assert_eq!(walk(&filter, &root.join("B")), ["d.png"]);
+
+        assert!(!filter.is_included(&root.join("A/secret/b.json")));
+        assert!(!filter.is_included(&root.join("A/.strixignore")));
+        assert!(filter.is_included(&root.join("A/x.tmp")));
+        assert!(filter.is_included(&root.join("outside.psd")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_globs() {
+        let mut config = config(true);
+        config.files.include = vec![String::from("a/**[")];
+
+        assert!(matches!(
+            FileFilter::new(Path::new(""), &config),
+            Err(ConfigError::Glob { .. })
+        ));
+    }
+}
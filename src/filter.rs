@@ -0,0 +1,117 @@
+use crate::config::StrixFilterConfig;
+use crate::project::Project;
+use std::env::current_dir;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Error, Debug)]
+pub enum FilterError {
+    #[error("An unexpected Error occurred while trying to run the filter {name:?} ({command:?}), Err: {source}")]
+    Spawn {
+        name: String,
+        command: String,
+        source: io::Error,
+    },
+    #[error("An unexpected Error occurred while trying to serialize the settings of the filter {name:?}, Err: {source}")]
+    Settings {
+        name: String,
+        source: serde_json::Error,
+    },
+    #[error("The filter {name:?} failed with {status}\n{stderr}")]
+    Failed {
+        name: String,
+        status: ExitStatus,
+        stderr: String,
+    },
+}
+
+/// What a successful filter printed
+#[derive(Debug, Clone)]
+pub struct FilterOutput {
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs a filter inside `build_folder`, which holds one folder per project
+///
+/// The filter is run as `command [script] [args...] <settings>`, with the settings as JSON.
+/// `STRIX_BUILD_DIR` and `STRIX_PROJECT_ROOT` hold absolute paths of both folders.
+pub async fn run_filter(
+    filter: &StrixFilterConfig,
+    project: &Project,
+    build_folder: &Path,
+) -> Result<FilterOutput, FilterError> {
+    let root = absolute(&project.root);
+    let settings = match &filter.settings {
+        serde_json::Value::Null => String::from("{}"),
+        v => serde_json::to_string(v).map_err(|source| FilterError::Settings {
+            name: filter.name.clone(),
+            source,
+        })?,
+    };
+
+    let mut command = Command::new(resolve_program(&filter.command, &root));
+    if let Some(script) = &filter.script {
+        command.arg(root.join(script));
+    }
+
+    command
+        .args(&filter.args)
+        .arg(settings)
+        .current_dir(build_folder)
+        .env("STRIX_BUILD_DIR", absolute(build_folder))
+        .env("STRIX_PROJECT_ROOT", &root)
+        .stdin(Stdio::null());
+
+    let start = Instant::now();
+    let output = command
+        .output()
+        .await
+        .map_err(|source| FilterError::Spawn {
+            name: filter.name.clone(),
+            command: filter.command.clone(),
+            source,
+        })?;
+    let duration = start.elapsed();
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    if !output.status.success() {
+        return Err(FilterError::Failed {
+            name: filter.name.clone(),
+            status: output.status,
+            stderr: stderr.trim_end().to_string(),
+        });
+    }
+
+    Ok(FilterOutput {
+        duration,
+        stdout,
+        stderr,
+    })
+}
+
+/// Programs with a path, like `./filters/run.sh`, are relative to the project root,
+/// plain names like `python` are looked up in `PATH`
+fn resolve_program(command: &str, root: &Path) -> PathBuf {
+    if command.contains(['/', '\\']) {
+        root.join(command)
+    } else {
+        PathBuf::from(command)
+    }
+}
+
+/// Joins relative paths onto the working directory, external processes run somewhere else
+pub fn absolute(path: &Path) -> PathBuf {
+    current_dir()
+        .unwrap_or_default()
+        .join(path)
+        .components()
+        .collect()
+}
@@ -0,0 +1,414 @@
+use crate::config::StrixFlipbookConfig;
+use crate::error::{ExitCode, IoError};
+use json_comments::StripComments;
+use serde_json::{json, Value};
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub const FLIPBOOK_TEXTURES: &str = "textures/flipbook_textures.json";
+
+#[derive(Error, Debug)]
+pub enum FlipbookError {
+    #[error("Couldn't find the flipbook frames {0:?} in any resource pack")]
+    Missing(String),
+    #[error("The flipbook {0:?} has no frames, name them 0.png, 1.png and so on")]
+    Empty(PathBuf),
+    #[error("{0:?} isn't a flipbook frame, frames are named 0.png, 1.png and so on")]
+    NotFrame(PathBuf),
+    #[error("The flipbook {folder:?} is missing frame {frame}")]
+    MissingFrame { folder: PathBuf, frame: usize },
+    #[error("The flipbook frame {path:?} is {width}x{height}, frames have to be square")]
+    NotSquare {
+        path: PathBuf,
+        width: u32,
+        height: u32,
+    },
+    #[error("The flipbook frame {path:?} is {found}px wide, the first frame is {expected}px")]
+    Size {
+        path: PathBuf,
+        expected: u32,
+        found: u32,
+    },
+    #[error("An unexpected Error occurred while trying to decode {path:?}, Err: {source}")]
+    Decode {
+        path: PathBuf,
+        source: png::DecodingError,
+    },
+    #[error("An unexpected Error occurred while trying to encode {path:?}, Err: {source}")]
+    Encode {
+        path: PathBuf,
+        source: png::EncodingError,
+    },
+    #[error("{path:?} isn't a list of flipbook textures")]
+    InvalidTextures { path: PathBuf },
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl FlipbookError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            FlipbookError::Missing(_) => ExitCode::Config,
+            FlipbookError::Encode { .. } | FlipbookError::Io(_) => ExitCode::Io,
+            _ => ExitCode::Validation,
+        }
+    }
+}
+
+/// A strip written by [`build_flipbook`]
+#[derive(Debug, Clone)]
+pub struct Flipbook {
+    /// The `.png` next to the frames folder
+    pub path: PathBuf,
+    pub frames: usize,
+    /// The size of all frames together
+    pub before: u64,
+    pub after: u64,
+}
+
+/// Stacks the numbered frames of `config.frames` inside `pack` into one strip and removes them
+///
+/// Every frame has to be square and as big as the first one. The strip replaces the folder, so
+/// `textures/blocks/fire/0.png` ends up in `textures/blocks/fire.png`.
+pub fn build_flipbook(
+    pack: &Path,
+    config: &StrixFlipbookConfig,
+) -> Result<Flipbook, FlipbookError> {
+    let folder = pack.join(&config.frames);
+    let frames = frame_paths(&folder)?;
+
+    let mut size = None;
+    let mut before = 0;
+    let mut strip = vec![];
+
+    for path in &frames {
+        let (width, height, pixels) = read_rgba(path)?;
+
+        if width != height {
+            return Err(FlipbookError::NotSquare {
+                path: path.clone(),
+                width,
+                height,
+            });
+        }
+
+        match size {
+            Some(expected) if expected != width => {
+                return Err(FlipbookError::Size {
+                    path: path.clone(),
+                    expected,
+                    found: width,
+                })
+            }
+            _ => size = Some(width),
+        }
+
+        before += fs::metadata(path).map(|v| v.len()).unwrap_or_default();
+        strip.extend(pixels);
+    }
+
+    let size = size.unwrap_or_default();
+    let path = folder.with_extension("png");
+    write_rgba(&path, size, size * frames.len() as u32, &strip)?;
+
+    fs::remove_dir_all(&folder).map_err(|err| IoError::new("remove", &folder, err))?;
+
+    Ok(Flipbook {
+        after: fs::metadata(&path).map(|v| v.len()).unwrap_or_default(),
+        path,
+        frames: frames.len(),
+        before,
+    })
+}
+
+/// Adds or replaces the entries of `flipbooks` in the `flipbook_textures.json` of `pack`
+///
+/// Entries are matched by `flipbook_texture`, other fields of existing entries, and their
+/// `atlas_tile` unless it is configured, are kept.
+pub fn update_flipbook_textures(
+    pack: &Path,
+    flipbooks: &[&StrixFlipbookConfig],
+    minify: bool,
+) -> Result<PathBuf, FlipbookError> {
+    let path = pack.join(FLIPBOOK_TEXTURES);
+
+    let mut entries = match fs::read_to_string(&path) {
+        Ok(text) => match serde_json::from_reader(StripComments::new(text.as_bytes())) {
+            Ok(Value::Array(entries)) => entries,
+            _ => return Err(FlipbookError::InvalidTextures { path }),
+        },
+        Err(_) => vec![],
+    };
+
+    for flipbook in flipbooks {
+        let texture = flipbook.frames.trim_end_matches('/');
+        let existing = entries
+            .iter_mut()
+            .find(|v| v.get("flipbook_texture").and_then(Value::as_str) == Some(texture));
+
+        match existing {
+            Some(entry) => {
+                if let Some(atlas_tile) = &flipbook.atlas_tile {
+                    entry["atlas_tile"] = json!(atlas_tile);
+                }
+                entry["ticks_per_frame"] = json!(flipbook.ticks_per_frame);
+            }
+            None => entries.push(json!({
+                "flipbook_texture": texture,
+                "atlas_tile": flipbook.atlas_tile.as_deref().unwrap_or_else(|| {
+                    texture.rsplit('/').next().unwrap_or(texture)
+                }),
+                "ticks_per_frame": flipbook.ticks_per_frame,
+            })),
+        }
+    }
+
+    let entries = Value::Array(entries);
+    let text = match minify {
+        true => serde_json::to_string(&entries),
+        false => serde_json::to_string_pretty(&entries),
+    }
+    .unwrap_or_default();
+
+    fs::write(&path, text).map_err(|err| IoError::new("write", &path, err))?;
+    Ok(path)
+}
+
+/// The frames of a folder in order, every file has to be a frame and no number may be skipped
+fn frame_paths(folder: &Path) -> Result<Vec<PathBuf>, FlipbookError> {
+    let read = fs::read_dir(folder).map_err(|err| IoError::new("read", folder, err))?;
+    let mut frames = vec![];
+
+    for entry in read {
+        let path = entry
+            .map_err(|err| IoError::new("read", folder, err))?
+            .path();
+
+        let frame = match path.extension().is_some_and(|v| v == "png") {
+            true => path
+                .file_stem()
+                .and_then(|v| v.to_str())
+                .and_then(|v| v.parse::<usize>().ok()),
+            false => None,
+        };
+
+        match frame {
+            Some(frame) => frames.push((frame, path)),
+            None => return Err(FlipbookError::NotFrame(path)),
+        }
+    }
+
+    if frames.is_empty() {
+        return Err(FlipbookError::Empty(folder.to_path_buf()));
+    }
+
+    frames.sort();
+
+    for (index, (frame, _)) in frames.iter().enumerate() {
+        if *frame != index {
+            return Err(FlipbookError::MissingFrame {
+                folder: folder.to_path_buf(),
+                frame: index,
+            });
+        }
+    }
+
+    Ok(frames.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Decodes a PNG of any color type into 8 bit RGBA
+fn read_rgba(path: &Path) -> Result<(u32, u32, Vec<u8>), FlipbookError> {
+    let decode = |source| FlipbookError::Decode {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let file = File::open(path).map_err(|err| IoError::new("read", path, err))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(decode)?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(decode)?;
+    let pixels = &buffer[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => pixels.to_vec(),
+        png::ColorType::Rgb => pixels
+            .chunks_exact(3)
+            .flat_map(|v| [v[0], v[1], v[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => pixels
+            .chunks_exact(2)
+            .flat_map(|v| [v[0], v[0], v[0], v[1]])
+            .collect(),
+        // Indexed images are expanded to RGB or RGBA by the decoder
+        png::ColorType::Grayscale | png::ColorType::Indexed => {
+            pixels.iter().flat_map(|v| [*v, *v, *v, 255]).collect()
+        }
+    };
+
+    Ok((info.width, info.height, rgba))
+}
+
+fn write_rgba(path: &Path, width: u32, height: u32, pixels: &[u8]) -> Result<(), FlipbookError> {
+    let encode = |source| FlipbookError::Encode {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let file = File::create(path).map_err(|err| IoError::new("create", path, err))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(encode)?;
+    writer.write_image_data(pixels).map_err(encode)?;
+    writer.finish().map_err(encode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh folder for a test, removed by the test itself
+    fn temp_pack(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("strix-flipbook-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("textures/blocks/fire")).unwrap();
+        path
+    }
+
+    /// Writes a frame filled with one color, the red channel tells frames apart
+    fn frame(pack: &Path, name: &str, width: u32, height: u32, red: u8) {
+        let pixels: Vec<u8> = (0..width * height).flat_map(|_| [red, 0, 0, 255]).collect();
+        write_rgba(
+            &pack.join("textures/blocks/fire").join(name),
+            width,
+            height,
+            &pixels,
+        )
+        .unwrap();
+    }
+
+    fn config(atlas_tile: Option<&str>) -> StrixFlipbookConfig {
+        StrixFlipbookConfig {
+            frames: "textures/blocks/fire".to_string(),
+            atlas_tile: atlas_tile.map(str::to_string),
+            ticks_per_frame: 2,
+        }
+    }
+
+    #[test]
+    fn stacks_frames_in_numeric_order() {
+        let pack = temp_pack("order");
+        for i in 0..11 {
+            frame(&pack, &format!("{i}.png"), 2, 2, i as u8 * 10);
+        }
+
+        let flipbook = build_flipbook(&pack, &config(None)).unwrap();
+        let (width, height, pixels) = read_rgba(&flipbook.path).unwrap();
+
+        assert_eq!(flipbook.frames, 11);
+        assert_eq!((width, height), (2, 22));
+        assert!(!pack.join("textures/blocks/fire").exists());
+
+        let reds: Vec<u8> = pixels.chunks_exact(2 * 2 * 4).map(|v| v[0]).collect();
+        assert_eq!(reds, (0..11).map(|i| i * 10).collect::<Vec<u8>>());
+
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[test]
+    fn rejects_mismatched_frame_sizes() {
+        let pack = temp_pack("size");
+        frame(&pack, "0.png", 4, 4, 0);
+        frame(&pack, "1.png", 8, 8, 0);
+
+        let result = build_flipbook(&pack, &config(None));
+        assert!(matches!(
+            result,
+            Err(FlipbookError::Size {
+                expected: 4,
+                found: 8,
+                ..
+            })
+        ));
+        assert!(pack.join("textures/blocks/fire/0.png").exists());
+
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[test]
+    fn rejects_invalid_frames() {
+        let pack = temp_pack("invalid");
+        frame(&pack, "0.png", 4, 2, 0);
+        assert!(matches!(
+            build_flipbook(&pack, &config(None)),
+            Err(FlipbookError::NotSquare { .. })
+        ));
+
+        frame(&pack, "0.png", 4, 4, 0);
+        frame(&pack, "2.png", 4, 4, 0);
+        assert!(matches!(
+            build_flipbook(&pack, &config(None)),
+            Err(FlipbookError::MissingFrame { frame: 1, .. })
+        ));
+
+        fs::write(pack.join("textures/blocks/fire/notes.txt"), "").unwrap();
+        assert!(matches!(
+            build_flipbook(&pack, &config(None)),
+            Err(FlipbookError::NotFrame(_))
+        ));
+
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[test]
+    fn merges_flipbook_textures() {
+        let pack = temp_pack("merge");
+        let path = pack.join(FLIPBOOK_TEXTURES);
+        fs::write(
+            &path,
+            r#"[
+                // Entries of other textures stay untouched
+                { "flipbook_texture": "textures/blocks/water", "atlas_tile": "water", "ticks_per_frame": 4 },
+                { "flipbook_texture": "textures/blocks/fire", "atlas_tile": "flame", "ticks_per_frame": 1, "blend_frames": false }
+            ]"#,
+        )
+        .unwrap();
+
+        let lava = StrixFlipbookConfig {
+            frames: "textures/blocks/lava/".to_string(),
+            atlas_tile: None,
+            ticks_per_frame: 3,
+        };
+        update_flipbook_textures(&pack, &[&config(None), &lava], true).unwrap();
+
+        let entries: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            entries,
+            json!([
+                { "flipbook_texture": "textures/blocks/water", "atlas_tile": "water", "ticks_per_frame": 4 },
+                { "flipbook_texture": "textures/blocks/fire", "atlas_tile": "flame", "ticks_per_frame": 2, "blend_frames": false },
+                { "flipbook_texture": "textures/blocks/lava", "atlas_tile": "lava", "ticks_per_frame": 3 }
+            ])
+        );
+
+        update_flipbook_textures(&pack, &[&config(Some("fire"))], true).unwrap();
+        let entries: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(entries[1]["atlas_tile"], "fire");
+
+        fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            update_flipbook_textures(&pack, &[&config(None)], true),
+            Err(FlipbookError::InvalidTextures { .. })
+        ));
+
+        fs::remove_dir_all(&pack).unwrap();
+    }
+}
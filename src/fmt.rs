@@ -1,5 +1,13 @@
 use crate::args::CliFmtSubCommand;
-use crate::config::StrixConfig;
+use crate::config::{ConfigError, StrixConfig, StrixFmtConfig};
+use crate::diagnostic::{Diagnostic, Diagnostics, Position, Severity, TextRange};
+use crate::error::{ExitCode, IoError};
+use crate::files::FileFilter;
+use crate::lang;
+use crate::mcfunction;
+use crate::message;
+use crate::molang;
+use crate::report::Report;
 use console::Style;
 use dprint_plugin_biome::configuration::{
     ArrowParentheses, Configuration, IndentStyle, QuoteProperties, QuoteStyle, Semicolons,
@@ -11,12 +19,12 @@ use similar::{ChangeTag, TextDiff};
 use std::env::current_dir;
 use std::ffi::OsStr;
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
-use walkdir::{DirEntry, WalkDir};
+use thiserror::Error;
 
-const SUPPORTED_EXTENSIONS: &[&str] = &["json", "js", "ts"];
+const SUPPORTED_EXTENSIONS: &[&str] = &["json", "js", "ts", "lang", "mcfunction"];
 
 #[inline(always)]
 fn try_rm_prefix(path: &Path) -> PathBuf {
@@ -25,218 +33,352 @@ fn try_rm_prefix(path: &Path) -> PathBuf {
         .to_path_buf()
 }
 
-fn fmt_build_config(fmt: &CliFmtSubCommand, config: &Option<StrixConfig>) -> Configuration {
-    let config = config.clone().unwrap_or_default();
+/// Resolved formatter options for every supported file type
+pub struct FmtConfig {
+    biome: Configuration,
+    sort_lang_keys: bool,
+    format_molang: bool,
+}
 
-    Configuration {
-        javascript_indent_style: match fmt.use_tabs {
-            Some(true) => Some(IndentStyle::Tab),
-            _ => match config.fmt.use_tabs {
-                true => Some(IndentStyle::Tab),
-                false => Some(IndentStyle::Space),
-            },
-        },
-        javascript_indent_size: match fmt.indent_width {
-            Some(v) => Some(v),
-            None => Some(config.fmt.indent_width),
-        },
-        javascript_line_width: match fmt.line_width {
-            Some(v) => Some(v),
-            None => Some(config.fmt.line_width),
-        },
-        json_indent_style: match fmt.use_tabs {
-            Some(true) => Some(IndentStyle::Tab),
-            _ => match config.fmt.use_tabs {
-                true => Some(IndentStyle::Tab),
-                false => Some(IndentStyle::Space),
-            },
-        },
-        json_indent_size: match fmt.indent_width {
-            Some(v) => Some(v),
-            None => Some(config.fmt.indent_width),
-        },
-        json_line_width: match fmt.line_width {
-            Some(v) => Some(v),
-            None => Some(config.fmt.line_width),
-        },
-        semicolons: match fmt.always_semicolons {
-            Some(true) => Some(Semicolons::Always),
-            _ => match config.fmt.always_semicolons {
+impl FmtConfig {
+    pub fn new(config: &StrixFmtConfig) -> Self {
+        let indent_style = match config.use_tabs {
+            true => IndentStyle::Tab,
+            false => IndentStyle::Space,
+        };
+        let quote_style = match config.single_quote {
+            true => QuoteStyle::Single,
+            false => QuoteStyle::Double,
+        };
+
+        let biome = Configuration {
+            javascript_indent_style: Some(indent_style),
+            javascript_indent_size: Some(config.indent_width),
+            javascript_line_width: Some(config.line_width),
+            json_indent_style: Some(indent_style),
+            json_indent_size: Some(config.indent_width),
+            json_line_width: Some(config.line_width),
+            semicolons: match config.always_semicolons {
                 true => Some(Semicolons::Always),
                 false => Some(Semicolons::AsNeeded),
             },
-        },
-        quote_style: match fmt.single_quote {
-            Some(true) => Some(QuoteStyle::Single),
-            _ => match config.fmt.single_quote {
-                true => Some(QuoteStyle::Single),
-                false => Some(QuoteStyle::Double),
-            },
-        },
-        jsx_quote_style: match fmt.single_quote {
-            Some(true) => Some(QuoteStyle::Single),
-            _ => match config.fmt.single_quote {
-                true => Some(QuoteStyle::Single),
-                false => Some(QuoteStyle::Double),
-            },
-        },
-        quote_properties: Some(QuoteProperties::Preserve),
-        arrow_parentheses: Some(ArrowParentheses::AsNeeded),
-        trailing_comma: Some(TrailingComma::Es5),
+            quote_style: Some(quote_style),
+            jsx_quote_style: Some(quote_style),
+            quote_properties: Some(QuoteProperties::Preserve),
+            arrow_parentheses: Some(ArrowParentheses::AsNeeded),
+            trailing_comma: Some(TrailingComma::Es5),
+        };
+
+        Self {
+            biome,
+            sort_lang_keys: config.sort_lang_keys,
+            format_molang: config.format_molang,
+        }
+    }
+}
+
+/// The formatter options of `strix.json` with the overrides of the command line applied
+pub(crate) fn fmt_build_config(fmt: &CliFmtSubCommand, config: &Option<StrixConfig>) -> FmtConfig {
+    let mut options = config.clone().unwrap_or_default().fmt;
+
+    // Boolean flags can only turn options on, like they always did
+    if fmt.use_tabs == Some(true) {
+        options.use_tabs = true;
+    }
+    if fmt.always_semicolons == Some(true) {
+        options.always_semicolons = true;
+    }
+    if fmt.single_quote == Some(true) {
+        options.single_quote = true;
     }
+    if let Some(v) = fmt.indent_width {
+        options.indent_width = v;
+    }
+    if let Some(v) = fmt.line_width {
+        options.line_width = v;
+    }
+    if let Some(v) = fmt.sort_lang_keys {
+        options.sort_lang_keys = v;
+    }
+    if let Some(v) = fmt.format_molang {
+        options.format_molang = v;
+    }
+
+    FmtConfig::new(&options)
+}
+
+#[derive(Error, Debug)]
+pub enum FmtError {
+    #[error("Found {0} files that need formatting")]
+    Unformatted(usize),
+    #[error("Couldn't format {0} files")]
+    Failed(usize),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl FmtError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            FmtError::Unformatted(_) => ExitCode::Unformatted,
+            FmtError::Config(_) => ExitCode::Config,
+            FmtError::Failed(_) | FmtError::Io(_) => ExitCode::Io,
+        }
+    }
+}
+
+/// Why a single file couldn't be formatted, logged right away so every file gets reported
+#[derive(Error, Debug)]
+enum FmtFileError {
+    #[error("An unexpected Error occurred while trying to {action} {path:?}\n{source}")]
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+    #[error("An unexpected Error occurred while trying to check {path:?}\n{message}")]
+    Check { path: PathBuf, message: String },
+    #[error("An unexpected Error occurred while trying to reformat {path:?}\n{message}")]
+    Reformat { path: PathBuf, message: String },
 }
 
-pub async fn fmt(fmt: CliFmtSubCommand, config: Option<StrixConfig>) -> bool {
+pub(crate) async fn fmt(
+    fmt: CliFmtSubCommand,
+    config: Option<StrixConfig>,
+) -> Result<(), FmtError> {
     let fmt_config = Arc::new(fmt_build_config(&fmt, &config));
 
-    let mut count = 0;
-    let walk: Vec<_> = WalkDir::new(fmt.path.unwrap_or(current_dir().unwrap()))
-        .into_iter()
-        .filter(|v| {
-            if let Ok(v) = v {
-                v.file_type().is_file()
-            } else {
-                false
-            }
-        })
-        .inspect(|_| count += 1)
-        .collect();
+    let filter = match &config {
+        Some(config) => FileFilter::new(Path::new(""), config)?,
+        None => FileFilter::new(Path::new(""), &StrixConfig::default())?,
+    };
+    let walk = filter.walk(&fmt.path.unwrap_or(current_dir().unwrap()));
 
     let mut handles = vec![];
-    let error_out = Arc::new(AtomicBool::new(false));
+    let diagnostics = Arc::new(Diagnostics::default());
 
-    for entry in walk.into_iter().flatten() {
+    for entry in walk {
         let fmt_config = fmt_config.clone();
-        let error_out = error_out.clone();
+        let diagnostics = diagnostics.clone();
 
         if let Some(config) = &config {
-            if try_rm_prefix(entry.path()).starts_with(Path::new(&config.build.build_path)) {
+            if try_rm_prefix(&entry).starts_with(Path::new(&config.build.build_path)) {
                 continue;
             }
         }
 
-        if let Some(ext) = entry.path().extension().and_then(OsStr::to_str) {
+        if let Some(ext) = entry.extension().and_then(OsStr::to_str) {
             if SUPPORTED_EXTENSIONS.contains(&ext) {
                 handles.push(tokio::task::spawn_blocking(move || {
-                    fmt_handle_entry(&entry, fmt.check, fmt.quiet, &fmt_config, &error_out);
+                    let result =
+                        fmt_handle_entry(&entry, fmt.check, fmt.quiet, &fmt_config, &diagnostics);
+
+                    if let Err(err) = &result {
+                        error!("{err}");
+                    }
+
+                    (try_rm_prefix(&entry), result.is_err())
                 }));
             }
-        } else if let Some(file_name) = entry.file_name().to_str() {
+        } else if let Some(file_name) = entry.file_name().and_then(OsStr::to_str) {
             if file_name.starts_with('.') {
                 // We'll just ignore all dot files
             }
         } else {
             warn!(
                 "Found file without extension: {:?}",
-                try_rm_prefix(entry.path()).display()
+                try_rm_prefix(&entry).display()
             );
         };
     }
 
+    let mut files = vec![];
+    let mut failed = 0;
+
     for handle in handles {
-        handle.await.unwrap_or_else(|err| {
-            error!("An unexpected Error occurred while trying to join a thread, Err: {err}");
-            unsafe { *error_out.as_ptr() = true };
-        })
+        match handle.await {
+            Ok((path, error)) => {
+                files.push(path);
+                failed += usize::from(error);
+            }
+            Err(err) => {
+                error!("An unexpected Error occurred while trying to join a thread, Err: {err}");
+                failed += 1;
+            }
+        }
+    }
+
+    let unformatted = diagnostics.count(Severity::Error);
+
+    if fmt.check {
+        let report = Report {
+            files,
+            diagnostics: Arc::into_inner(diagnostics)
+                .map(Diagnostics::into_vec)
+                .unwrap_or_default(),
+        };
+        report.write(
+            fmt.report.sarif.as_deref(),
+            fmt.report.junit.as_deref(),
+            "fmt",
+        )?;
     }
 
-    unsafe { *error_out.as_ptr() }
+    if failed > 0 {
+        return Err(FmtError::Failed(failed));
+    }
+
+    if unformatted > 0 {
+        return Err(FmtError::Unformatted(unformatted));
+    }
+
+    Ok(())
 }
 
 fn fmt_handle_entry(
-    entry: &DirEntry,
+    file: &Path,
     check: bool,
     quiet: bool,
-    config: &Arc<Configuration>,
-    error_out: &Arc<AtomicBool>,
-) {
+    config: &FmtConfig,
+    diagnostics: &Diagnostics,
+) -> Result<(), FmtFileError> {
+    let path = try_rm_prefix(file);
+
     if !quiet {
-        info!("Processing: {:?}", try_rm_prefix(entry.path()).display());
+        info!("Processing: {:?}", path.display());
     }
 
-    if !entry.path().is_file() {
-        return;
+    if !file.is_file() {
+        return Ok(());
     }
 
-    match fs::read_to_string(entry.path()) {
-        Ok(text) => {
-            if text.trim().is_empty() {
-                warn!(
-                    "Found empty file at {:?}",
-                    try_rm_prefix(entry.path()).display()
-                )
-            } else if check {
-                match fmt_check(entry.path(), &text, config) {
-                    Ok(_) => {}
-                    Err(Ok(diff)) => {
-                        error!(
-                            "Difference found in file {:?}:\n{}",
-                            try_rm_prefix(entry.path()).display(),
-                            diff
-                        );
-                        unsafe { *error_out.as_ptr() = true };
-                    }
-                    Err(Err(err)) => {
-                        error!(
-                            "An unexpected Error occurred while trying to check {:?}\n{}",
-                            try_rm_prefix(entry.path()).display(),
-                            err
-                        );
-                        unsafe { *error_out.as_ptr() = true };
-                    }
-                };
-            } else {
-                match fmt_reformat(entry.path(), &text, config) {
-                    Ok(Some(text)) => {
-                        info!(
-                            "Reformating file {:?}",
-                            try_rm_prefix(entry.path()).display()
-                        );
-
-                        fs::write(entry.path(), text).unwrap_or_else(|err| {
-                            error!(
-                                "An unexpected Error occurred while trying to write {:?}\n{}",
-                                try_rm_prefix(entry.path()).display(),
-                                err
-                            );
-                            unsafe { *error_out.as_ptr() = true };
-                        })
-                    }
-                    Ok(None) => {}
-                    Err(err) => {
-                        error!(
-                            "An unexpected Error occurred while trying to reformat {:?}\n{}",
-                            try_rm_prefix(entry.path()).display(),
-                            err
-                        );
-                        unsafe { *error_out.as_ptr() = true };
-                    }
-                };
+    let text = fs::read_to_string(file).map_err(|source| FmtFileError::Io {
+        action: "read",
+        path: path.clone(),
+        source,
+    })?;
+
+    if text.trim().is_empty() {
+        warn!("Found empty file at {:?}", path.display());
+        return Ok(());
+    }
+
+    if check {
+        match fmt_check(file, &text, config) {
+            Ok(_) => {}
+            Err(Ok(difference)) => {
+                if message::is_json() {
+                    diagnostics.report(difference.diagnostic);
+                } else {
+                    error!(
+                        "Difference found in file {:?}:\n{}",
+                        path.display(),
+                        difference.diff
+                    );
+                    diagnostics.push(difference.diagnostic);
+                }
             }
+            Err(Err(message)) => return Err(FmtFileError::Check { path, message }),
+        };
+
+        return Ok(());
+    }
+
+    match format_buffer(file, &text, config) {
+        Ok(Some(text)) => {
+            info!("Reformating file {:?}", path.display());
+
+            fs::write(file, text).map_err(|source| FmtFileError::Io {
+                action: "write",
+                path,
+                source,
+            })
         }
-        Err(err) => {
-            error!(
-                "An unexpected Error occurred while trying to read {:?}\n{}",
-                try_rm_prefix(entry.path()).display(),
-                err
-            );
-            unsafe { *error_out.as_ptr() = true };
+        Ok(None) => Ok(()),
+        Err(message) => Err(FmtFileError::Reformat { path, message }),
+    }
+}
+
+/// Formats a single buffer, the formatter is picked by the extension of `path`
+///
+/// Returns `None` if the text is already formatted, and the message of the formatter if the
+/// text couldn't be parsed. The file at `path` is never touched.
+pub fn format_buffer(
+    path: &Path,
+    text: &str,
+    config: &FmtConfig,
+) -> Result<Option<String>, String> {
+    match path.extension().and_then(OsStr::to_str) {
+        Some("lang") => {
+            let new = lang::format(text, config.sort_lang_keys);
+            return Ok((new != text).then_some(new));
+        }
+        Some("mcfunction") => {
+            let new = mcfunction::format(text);
+            return Ok((new != text).then_some(new));
         }
+        _ => {}
     }
+
+    let formatted = match format_biome(path, text, &config.biome) {
+        Ok(formatted) => formatted,
+        Err(err) => return Err(format!("{err}")),
+    };
+
+    if config.format_molang && path.extension().and_then(OsStr::to_str) == Some("json") {
+        let current = formatted.as_deref().unwrap_or(text);
+
+        if let Some(formatted) = molang::format_json(current, false) {
+            return Ok(Some(formatted));
+        }
+    }
+
+    Ok(formatted)
 }
 
-fn fmt_reformat(path: &Path, text: &str, config: &Configuration) -> Result<Option<String>, String> {
-    match format_biome(path, text, config) {
-        Ok(text) => Ok(text),
-        Err(err) => Err(format!("{err}")),
+/// An unformatted file, the diagnostic points at every changed hunk
+struct FmtDifference {
+    diagnostic: Diagnostic,
+    /// A colored diff of all changes
+    diff: String,
+}
+
+/// The lines of `old` a hunk replaces, pure insertions point at the line they're inserted before
+fn hunk_range(old: &str, lines: std::ops::Range<usize>) -> TextRange {
+    let start = lines.start + 1;
+    let end = lines.end.max(start);
+    let end_column = old.lines().nth(end - 1).map_or(0, |v| v.chars().count()) + 1;
+
+    TextRange {
+        start: Position {
+            line: start,
+            column: 1,
+        },
+        end: Position {
+            line: end,
+            column: end_column,
+        },
     }
 }
 
-fn fmt_check(path: &Path, old: &str, config: &Configuration) -> Result<(), Result<String, String>> {
-    match format_biome(path, old, config) {
+fn fmt_check(
+    path: &Path,
+    old: &str,
+    config: &FmtConfig,
+) -> Result<(), Result<Box<FmtDifference>, String>> {
+    match format_buffer(path, old, config) {
         Ok(Some(new)) => {
             let diff = TextDiff::from_lines(old, &new);
 
+            // Without context lines, every group of changed lines is a hunk of its own
+            let mut ranges = diff.grouped_ops(0).into_iter().filter_map(|group| {
+                let start = group.first()?.old_range().start;
+                let end = group.last()?.old_range().end;
+                Some(hunk_range(old, start..end))
+            });
+
             let mut string = String::new();
 
             for op in diff.ops() {
@@ -250,9 +392,73 @@ fn fmt_check(path: &Path, old: &str, config: &Configuration) -> Result<(), Resul
                 }
             }
 
-            Err(Ok(string))
+            let mut diagnostic = Diagnostic::new(
+                "unformatted",
+                try_rm_prefix(path),
+                ranges.next(),
+                "File isn't formatted",
+            );
+            diagnostic.additional_ranges = ranges.collect();
+
+            Err(Ok(Box::new(FmtDifference {
+                diagnostic,
+                diff: string,
+            })))
         }
         Ok(None) => Ok(()),
-        Err(err) => Err(Err(format!("{err}"))),
+        Err(err) => Err(Err(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn reports_every_changed_hunk() {
+        let config = FmtConfig::new(&StrixFmtConfig::default());
+        let old = "a = 1\nb=2\nc=3\nd=4\ne = 5\nf=6\n";
+
+        let Err(Ok(difference)) = fmt_check(Path::new("texts/en_US.lang"), old, &config) else {
+            panic!("expected a difference");
+        };
+        let diagnostic = difference.diagnostic;
+
+        assert_eq!(diagnostic.range, Some(hunk_range(old, 0..1)));
+        assert_eq!(diagnostic.additional_ranges, [hunk_range(old, 4..5)]);
+        assert_eq!(
+            hunk_range(old, 4..5),
+            TextRange {
+                start: Position { line: 5, column: 1 },
+                end: Position { line: 5, column: 6 },
+            }
+        );
+
+        let report = Report {
+            files: vec![diagnostic.file.clone()],
+            diagnostics: vec![diagnostic],
+        };
+        let sarif = report.sarif();
+        let lines: Vec<&Value> = sarif["runs"][0]["results"][0]["locations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| &v["physicalLocation"]["region"]["startLine"])
+            .collect();
+        assert_eq!(lines, [1, 5]);
+
+        assert!(fmt_check(Path::new("texts/en_US.lang"), "a=1\n", &config).is_ok());
+    }
+
+    #[test]
+    fn points_insertions_at_the_next_line() {
+        assert_eq!(
+            hunk_range("a\nb\n", 1..1),
+            TextRange {
+                start: Position { line: 2, column: 1 },
+                end: Position { line: 2, column: 2 },
+            }
+        );
     }
 }
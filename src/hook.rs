@@ -0,0 +1,147 @@
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    PreBuild,
+    PostBuild,
+    PostPackage,
+}
+
+impl Display for HookStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookStage::PreBuild => write!(f, "pre_build"),
+            HookStage::PostBuild => write!(f, "post_build"),
+            HookStage::PostPackage => write!(f, "post_package"),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum HookError {
+    #[error("An unexpected Error occurred while trying to run the {stage} hook {command:?}, Err: {source}")]
+    Spawn {
+        stage: HookStage,
+        command: String,
+        source: io::Error,
+    },
+    #[error("The {stage} hook {command:?} failed with {status}\n{output}")]
+    Failed {
+        stage: HookStage,
+        command: String,
+        status: ExitStatus,
+        /// Everything the hook printed, stdout followed by stderr
+        output: String,
+    },
+}
+
+/// What a successful hook printed
+#[derive(Debug, Clone)]
+pub struct HookOutput {
+    pub duration: Duration,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Runs a hook through the shell of the platform inside `cwd`, with `env` added to its environment
+pub async fn run_hook(
+    stage: HookStage,
+    command: &str,
+    cwd: &Path,
+    env: &[(&'static str, OsString)],
+) -> Result<HookOutput, HookError> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+
+    process
+        .current_dir(cwd)
+        .envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null());
+
+    let start = Instant::now();
+    let output = process.output().await.map_err(|source| HookError::Spawn {
+        stage,
+        command: command.to_string(),
+        source,
+    })?;
+    let duration = start.elapsed();
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    if !output.status.success() {
+        return Err(HookError::Failed {
+            stage,
+            command: command.to_string(),
+            status: output.status,
+            output: format!("{stdout}{stderr}").trim_end().to_string(),
+        });
+    }
+
+    Ok(HookOutput {
+        duration,
+        stdout,
+        stderr,
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn passes_env_and_captures_output() {
+        let env = [("STRIX_PROFILE", OsString::from("release"))];
+        let output = run_hook(
+            HookStage::PreBuild,
+            "echo \"$STRIX_PROFILE in $(pwd)\"; echo warning >&2",
+            Path::new("/"),
+            &env,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(output.stdout, "release in /\n");
+        assert_eq!(output.stderr, "warning\n");
+    }
+
+    #[tokio::test]
+    async fn fails_with_the_output() {
+        let err = run_hook(
+            HookStage::PostBuild,
+            "echo out; echo err >&2; exit 1",
+            Path::new("/"),
+            &[],
+        )
+        .await
+        .unwrap_err();
+
+        let HookError::Failed {
+            stage,
+            status,
+            output,
+            ..
+        } = &err
+        else {
+            panic!("expected a failed hook, got {err:?}");
+        };
+        assert_eq!(*stage, HookStage::PostBuild);
+        assert_eq!(status.code(), Some(1));
+        assert_eq!(output, "out\nerr");
+        assert!(err.to_string().ends_with("\nout\nerr"));
+    }
+}
@@ -0,0 +1,437 @@
+use crate::args::CliImportSubCommand;
+use crate::config::{ConfigError, StrixConfig, StrixConfigPackType, STRIX_CONFIG};
+use crate::error::{ExitCode, IoError};
+use crate::manifest::{header_text, pack_type_of, parse_manifest, MANIFEST};
+use anyhow::Context;
+use log::{error, info, warn};
+use serde_json::Value;
+use std::ffi::OsStr;
+use std::fs;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("An unexpected Error occurred while trying to read {path:?}, Err: {source}")]
+    Archive { path: PathBuf, source: ZipError },
+    #[error("An unexpected Error occurred while trying to read an archive entry, Err: {0}")]
+    ArchiveEntry(ZipError),
+    #[error("An unexpected Error occurred while trying to read {path:?}, Err: {source}")]
+    ReadEntry {
+        path: PathBuf,
+        source: anyhow::Error,
+    },
+    #[error("An unexpected Error occurred while trying to deserialize {path:?}, Err: {message}")]
+    InvalidManifest { path: PathBuf, message: String },
+    #[error("Couldn't find any {MANIFEST:?} in {0:?}")]
+    NoPacks(PathBuf),
+    #[error("{0:?} already exists, use --force to overwrite it")]
+    Exists(PathBuf),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl ImportError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            ImportError::InvalidManifest { .. } | ImportError::NoPacks(_) => ExitCode::Validation,
+            ImportError::Config(_) => ExitCode::Config,
+            ImportError::Archive { .. }
+            | ImportError::ArchiveEntry(_)
+            | ImportError::ReadEntry { .. }
+            | ImportError::Exists(_)
+            | ImportError::Io(_) => ExitCode::Io,
+        }
+    }
+}
+
+struct ImportedPack {
+    folder: String,
+    pack_type: StrixConfigPackType,
+    manifest: Value,
+}
+
+pub async fn import(import: CliImportSubCommand) -> Result<(), ImportError> {
+    let path = import.path.unwrap_or_default();
+
+    if !path.as_os_str().is_empty() && !path.is_dir() {
+        fs::create_dir_all(&path).map_err(|err| IoError::new("create", &path, err))?;
+    }
+
+    let file = File::open(&import.file).map_err(|err| IoError::new("open", &import.file, err))?;
+
+    let mut archive = ZipArchive::new(file).map_err(|source| ImportError::Archive {
+        path: import.file.clone(),
+        source,
+    })?;
+
+    let fallback_name = import
+        .file
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or("Imported")
+        .to_string();
+
+    let mut packs = vec![];
+    import_archive(&mut archive, &fallback_name, &path, false, &mut packs)?;
+
+    if packs.is_empty() {
+        return Err(ImportError::NoPacks(import.file));
+    }
+
+    // Every target, nested packs included, is checked before anything is extracted
+    for pack in &packs {
+        let target = path.join(&pack.folder);
+        if target.exists() && !import.force {
+            return Err(ImportError::Exists(target));
+        }
+    }
+
+    packs.clear();
+    import_archive(&mut archive, &fallback_name, &path, true, &mut packs)?;
+
+    for pack in &packs {
+        prettify_json(&path.join(&pack.folder));
+    }
+
+    write_config(&path, &fallback_name, &packs)
+}
+
+/// Collects the packs of an archive and its bundled `.mcpack`s, and extracts them if `extract`
+fn import_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    fallback_name: &str,
+    dest: &Path,
+    extract: bool,
+    packs: &mut Vec<ImportedPack>,
+) -> Result<(), ImportError> {
+    let names: Vec<PathBuf> = (0..archive.len())
+        .filter_map(|i| archive.by_index(i).ok()?.enclosed_name())
+        .collect();
+
+    // Every folder holding a manifest is a pack, nested manifests belong to their outer pack
+    let mut roots: Vec<PathBuf> = names
+        .iter()
+        .filter(|v| v.file_name() == Some(OsStr::new(MANIFEST)))
+        .map(|v| v.parent().map(Path::to_path_buf).unwrap_or_default())
+        .collect();
+    roots.sort_by_key(|v| v.components().count());
+
+    let mut pack_roots: Vec<PathBuf> = vec![];
+    for root in roots {
+        if !pack_roots.iter().any(|v| root.starts_with(v)) {
+            pack_roots.push(root);
+        }
+    }
+
+    for root in &pack_roots {
+        let folder = match root.file_name().and_then(OsStr::to_str) {
+            Some(v) => v.to_string(),
+            None => fallback_name.to_string(),
+        };
+
+        let manifest_path = root.join(MANIFEST);
+        let text =
+            read_entry(archive, &manifest_path).map_err(|source| ImportError::ReadEntry {
+                path: manifest_path.clone(),
+                source,
+            })?;
+        let manifest = parse_manifest(&String::from_utf8_lossy(&text)).map_err(|message| {
+            ImportError::InvalidManifest {
+                path: manifest_path,
+                message,
+            }
+        })?;
+
+        let pack_type = match pack_type_of(&manifest) {
+            Some(v) => v,
+            None => {
+                warn!(
+                    "Couldn't infer the pack type of {:?}, skipping it",
+                    root.join(MANIFEST)
+                );
+                continue;
+            }
+        };
+
+        let unique = unique_folder(&folder, &pack_type, packs);
+        if extract && unique != folder {
+            warn!("Another pack of the archive already goes into {folder:?}, using {unique:?}");
+        }
+        let folder = unique;
+
+        if extract {
+            let target = dest.join(&folder);
+            info!("Importing {:?} pack into {:?}", pack_type, target.display());
+            extract_pack(archive, root, &target)?;
+        }
+
+        packs.push(ImportedPack {
+            folder,
+            pack_type,
+            manifest,
+        });
+    }
+
+    // A `.mcaddon` may also just bundle several `.mcpack` archives
+    let nested: Vec<&PathBuf> = names
+        .iter()
+        .filter(|v| !pack_roots.iter().any(|root| v.starts_with(root)))
+        .filter(|v| v.extension() == Some(OsStr::new("mcpack")))
+        .collect();
+
+    for name in nested {
+        let data = read_entry(archive, name).map_err(|source| ImportError::ReadEntry {
+            path: name.clone(),
+            source,
+        })?;
+
+        let mut inner =
+            ZipArchive::new(Cursor::new(data)).map_err(|source| ImportError::Archive {
+                path: name.clone(),
+                source,
+            })?;
+
+        let stem = name
+            .file_stem()
+            .and_then(OsStr::to_str)
+            .unwrap_or(fallback_name);
+
+        import_archive(&mut inner, stem, dest, extract, packs)?;
+    }
+
+    Ok(())
+}
+
+/// The folder of a pack, suffixed with its pack type or a number if another pack of the archive
+/// already goes into it, so both passes over an archive pick the same folders
+fn unique_folder(folder: &str, pack_type: &StrixConfigPackType, packs: &[ImportedPack]) -> String {
+    let taken = |name: &str| packs.iter().any(|v| v.folder == name);
+
+    if !taken(folder) {
+        return folder.to_string();
+    }
+
+    let suffixed = format!("{folder}_{pack_type:?}");
+    std::iter::once(suffixed.clone())
+        .chain((2..).map(|i| format!("{suffixed}_{i}")))
+        .find(|v| !taken(v))
+        .unwrap_or(suffixed)
+}
+
+/// Extracts the entries below `root` into `target`
+fn extract_pack<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    root: &Path,
+    target: &Path,
+) -> Result<(), ImportError> {
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(ImportError::ArchiveEntry)?;
+
+        let Some(name) = entry.enclosed_name() else {
+            warn!("Skipping unsafe archive entry {:?}", entry.name());
+            continue;
+        };
+
+        let Ok(relative) = name.strip_prefix(root) else {
+            continue;
+        };
+
+        let out = target.join(relative);
+
+        let result = if entry.is_dir() {
+            fs::create_dir_all(&out)
+        } else {
+            out.parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .and_then(|_| File::create(&out))
+                .and_then(|mut file| std::io::copy(&mut entry, &mut file).map(|_| ()))
+        };
+
+        result.map_err(|err| IoError::new("extract", &out, err))?;
+    }
+
+    Ok(())
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &Path) -> anyhow::Result<Vec<u8>> {
+    let index = (0..archive.len())
+        .find(|i| {
+            archive
+                .by_index(*i)
+                .ok()
+                .and_then(|v| v.enclosed_name())
+                .is_some_and(|v| v == name)
+        })
+        .with_context(|| format!("Couldn't find {name:?} in the archive"))?;
+
+    let mut entry = archive.by_index(index)?;
+    let mut data = vec![];
+    entry.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Pretty prints minified JSON files, so `strix fmt` can take over from there
+fn prettify_json(path: &Path) {
+    let walk = WalkDir::new(path)
+        .into_iter()
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_file())
+        .filter(|v| v.path().extension() == Some(OsStr::new("json")));
+
+    for entry in walk {
+        let Ok(text) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+
+        if text.trim().lines().count() > 1 {
+            continue;
+        }
+
+        let json: Value = match serde_json::from_str(&text) {
+            Ok(v) => v,
+            Err(err) => {
+                warn!(
+                    "Couldn't deserialize {:?}, leaving it untouched, Err: {err}",
+                    entry.path().display()
+                );
+                continue;
+            }
+        };
+
+        if let Err(err) = fs::write(
+            entry.path(),
+            serde_json::to_string_pretty(&json).unwrap_or(text),
+        ) {
+            error!(
+                "An unexpected Error occurred while trying to write {:?}, Err: {err}",
+                entry.path().display()
+            );
+        }
+    }
+}
+
+fn write_config(
+    path: &Path,
+    fallback_name: &str,
+    packs: &[ImportedPack],
+) -> Result<(), ImportError> {
+    let config_path = path.join(STRIX_CONFIG);
+
+    let mut config = if config_path.exists() {
+        let text = fs::read_to_string(&config_path)
+            .map_err(|err| IoError::new("read", &config_path, err))?;
+
+        let config = serde_json::from_str::<StrixConfig>(&text).map_err(ConfigError::from)?;
+        info!("Adding imported packs to the existing {STRIX_CONFIG:?}");
+        config
+    } else {
+        let manifest = &packs[0].manifest;
+
+        StrixConfig {
+            name: header_text(manifest, "name").unwrap_or(fallback_name.to_string()),
+            description: header_text(manifest, "description").unwrap_or_default(),
+            ..Default::default()
+        }
+    };
+
+    for pack in packs {
+        config
+            .projects
+            .insert(pack.folder.clone(), pack.pack_type.clone());
+    }
+
+    fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+        .map_err(|err| IoError::new("create", &config_path, err))?;
+
+    info!("Wrote {:?}", config_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn manifest(module_type: &str) -> String {
+        format!(
+            r#"{{ "header": {{ "name": "Foo" }}, "modules": [{{ "type": "{module_type}" }}] }}"#
+        )
+    }
+
+    fn archive(files: &[(&str, String)]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for (name, text) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(text.as_bytes()).unwrap();
+        }
+        ZipArchive::new(zip.finish().unwrap()).unwrap()
+    }
+
+    fn folders(packs: &[ImportedPack]) -> Vec<(&str, StrixConfigPackType)> {
+        packs
+            .iter()
+            .map(|v| (v.folder.as_str(), v.pack_type.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_outer_packs_only() {
+        let mut archive = archive(&[
+            ("FooBP/manifest.json", manifest("data")),
+            ("FooBP/sub/manifest.json", manifest("resources")),
+            ("manifest.json.bak", String::new()),
+        ]);
+
+        let mut packs = vec![];
+        import_archive(&mut archive, "Foo", Path::new(""), false, &mut packs).unwrap();
+
+        assert_eq!(folders(&packs), [("FooBP", StrixConfigPackType::Behaviour)]);
+    }
+
+    #[test]
+    fn renames_packs_with_the_same_folder() {
+        let mut archive = archive(&[
+            ("behaviour/Foo/manifest.json", manifest("data")),
+            ("behaviour/Foo/a.txt", String::from("behaviour")),
+            ("resource/Foo/manifest.json", manifest("resources")),
+            ("resource/Foo/a.txt", String::from("resource")),
+            ("other/Foo/manifest.json", manifest("resources")),
+        ]);
+
+        let dest = std::env::temp_dir().join(format!("strix-import-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dest);
+
+        let mut packs = vec![];
+        import_archive(&mut archive, "Foo", &dest, true, &mut packs).unwrap();
+
+        assert_eq!(
+            folders(&packs),
+            [
+                ("Foo", StrixConfigPackType::Behaviour),
+                ("Foo_Resource", StrixConfigPackType::Resource),
+                ("Foo_Resource_2", StrixConfigPackType::Resource),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(dest.join("Foo/a.txt")).unwrap(),
+            "behaviour"
+        );
+        assert_eq!(
+            fs::read_to_string(dest.join("Foo_Resource/a.txt")).unwrap(),
+            "resource"
+        );
+
+        fs::remove_dir_all(&dest).unwrap();
+    }
+}
@@ -0,0 +1,134 @@
+use crate::config::StrixConfigPackType;
+use crate::lint::TEXTURE_EXTENSIONS;
+use json_comments::StripComments;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// A file listing other files of a pack, which Bedrock or other tools read instead of the folders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexFile {
+    /// `textures/textures_list.json` of a resource pack, every texture without its extension
+    TexturesList,
+    /// `contents.json` of any pack, every file of the pack
+    Contents,
+}
+
+impl IndexFile {
+    /// Where the index lives, relative to the pack
+    pub fn path(&self) -> &'static str {
+        match self {
+            IndexFile::TexturesList => "textures/textures_list.json",
+            IndexFile::Contents => "contents.json",
+        }
+    }
+
+    pub fn applies_to(&self, pack_type: &StrixConfigPackType) -> bool {
+        match self {
+            IndexFile::TexturesList => *pack_type == StrixConfigPackType::Resource,
+            IndexFile::Contents => true,
+        }
+    }
+
+    /// The entries for the files inside `pack`, sorted
+    pub fn entries(&self, pack: &Path) -> Vec<String> {
+        let files = WalkDir::new(pack)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|v| v.ok())
+            .filter(|v| v.file_type().is_file())
+            .filter_map(|v| {
+                let relative = v
+                    .path()
+                    .strip_prefix(pack)
+                    .ok()?
+                    .components()
+                    .map(|v| v.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                Some((v, relative))
+            });
+
+        match self {
+            IndexFile::TexturesList => files
+                .filter(|(v, relative)| {
+                    relative.starts_with("textures/")
+                        && v.path()
+                            .extension()
+                            .and_then(OsStr::to_str)
+                            .is_some_and(|v| TEXTURE_EXTENSIONS.contains(&v))
+                })
+                .map(|(_, relative)| match relative.rsplit_once('.') {
+                    Some((stem, _)) => stem.to_string(),
+                    None => relative,
+                })
+                .collect(),
+            IndexFile::Contents => files
+                .map(|(_, relative)| relative)
+                .filter(|v| v != self.path())
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self, entries: &[String]) -> Value {
+        match self {
+            IndexFile::TexturesList => json!(entries),
+            IndexFile::Contents => json!({
+                "content": entries.iter().map(|v| json!({ "path": v })).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    /// The entries of an existing index, `None` if it can't be read
+    pub fn read(&self, path: &Path) -> Option<Vec<String>> {
+        let text = fs::read_to_string(path).ok()?;
+        let value: Value = serde_json::from_reader(StripComments::new(text.as_bytes())).ok()?;
+
+        let entries = match self {
+            IndexFile::TexturesList => value.as_array()?,
+            IndexFile::Contents => value.get("content")?.as_array()?,
+        };
+
+        entries
+            .iter()
+            .map(|v| match self {
+                IndexFile::TexturesList => v.as_str().map(str::to_string),
+                IndexFile::Contents => v.get("path")?.as_str().map(str::to_string),
+            })
+            .collect()
+    }
+}
+
+/// How a checked-in index differs from the generated one
+#[derive(Debug, Clone, Default)]
+pub struct IndexDiff {
+    /// Entries for files which are missing in the checked-in index
+    pub missing: Vec<String>,
+    /// Entries of the checked-in index without a file
+    pub stale: Vec<String>,
+}
+
+impl IndexDiff {
+    pub fn new(checked_in: &[String], generated: &[String]) -> Self {
+        let checked_in: BTreeSet<&String> = checked_in.iter().collect();
+        let generated: BTreeSet<&String> = generated.iter().collect();
+
+        Self {
+            missing: generated
+                .difference(&checked_in)
+                .map(|v| v.to_string())
+                .collect(),
+            stale: checked_in
+                .difference(&generated)
+                .map(|v| v.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.stale.is_empty()
+    }
+}
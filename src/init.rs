@@ -0,0 +1,161 @@
+use crate::args::CliInitSubCommand;
+use crate::config::{StrixBuildConfig, StrixConfig, StrixConfigPackType, STRIX_CONFIG};
+use crate::error::{ExitCode, IoError};
+use crate::manifest::{header_text, pack_type_of, read_manifest, MANIFEST};
+use log::{info, warn};
+use serde_json::Value;
+use std::env::current_dir;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use walkdir::WalkDir;
+
+/// How deep `init` looks for pack manifests, `MyAddon/packs/MyBP/manifest.json` is still found
+const MAX_SEARCH_DEPTH: usize = 4;
+
+#[derive(Error, Debug)]
+pub enum InitError {
+    #[error("{0:?} already exists, use --force to overwrite it")]
+    ConfigExists(PathBuf),
+    #[error("Found {MANIFEST:?} directly in {0:?}, packs have to live in their own folders")]
+    ManifestInRoot(PathBuf),
+    #[error("Couldn't find any {MANIFEST:?} in {0:?}")]
+    NoPacks(PathBuf),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl InitError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            InitError::ConfigExists(_) | InitError::ManifestInRoot(_) | InitError::NoPacks(_) => {
+                ExitCode::Config
+            }
+            InitError::Io(_) => ExitCode::Io,
+        }
+    }
+}
+
+pub async fn init(init: CliInitSubCommand) -> Result<(), InitError> {
+    let path = init.path.unwrap_or_default();
+    let config_path = path.join(STRIX_CONFIG);
+
+    if config_path.exists() && !init.force {
+        return Err(InitError::ConfigExists(config_path));
+    }
+
+    let root = if path.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        path.as_path()
+    };
+
+    let build_path = StrixBuildConfig::default().build_path;
+
+    let mut manifests: Vec<_> = WalkDir::new(root)
+        .max_depth(MAX_SEARCH_DEPTH)
+        .into_iter()
+        .filter_entry(|v| {
+            let name = v.file_name().to_string_lossy();
+            v.depth() == 0 || !(name.starts_with('.') || name == build_path.as_str())
+        })
+        .filter_map(|v| v.ok())
+        .filter(|v| v.file_type().is_file() && v.file_name() == OsStr::new(MANIFEST))
+        .map(|v| v.into_path())
+        .collect();
+    // Parents have to come first, so the packs inside them are recognized as sub packs
+    manifests.sort_by_key(|v| (v.components().count(), v.clone()));
+
+    let mut config = StrixConfig::default();
+    let mut header: Option<(StrixConfigPackType, Value)> = None;
+
+    for manifest_path in manifests {
+        let Some(pack_path) = manifest_path.parent() else {
+            continue;
+        };
+
+        // Packs living inside other packs, like sub packs, aren't projects of their own
+        if config
+            .projects
+            .keys()
+            .any(|v| pack_path.starts_with(root.join(v)))
+        {
+            continue;
+        }
+
+        let manifest = match read_manifest(&manifest_path) {
+            Ok(v) => v,
+            Err(err) => {
+                warn!(
+                    "Couldn't read {:?}, skipping it, Err: {err}",
+                    manifest_path.display()
+                );
+                continue;
+            }
+        };
+
+        let Some(pack_type) = pack_type_of(&manifest) else {
+            warn!(
+                "Couldn't infer the pack type of {:?}, skipping it",
+                manifest_path.display()
+            );
+            continue;
+        };
+
+        let project = pack_path
+            .strip_prefix(root)
+            .unwrap_or(pack_path)
+            .components()
+            .map(|v| v.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if project.is_empty() {
+            return Err(InitError::ManifestInRoot(root.to_path_buf()));
+        }
+
+        info!("Found {:?} pack {:?}", pack_type, project);
+
+        // The behaviour pack header is the most descriptive for the whole addon
+        let replace = match &header {
+            None => true,
+            Some((StrixConfigPackType::Behaviour, _)) => false,
+            Some(_) => pack_type == StrixConfigPackType::Behaviour,
+        };
+
+        if replace {
+            header = Some((pack_type.clone(), manifest));
+        }
+
+        config.projects.insert(project, pack_type);
+    }
+
+    if config.projects.is_empty() {
+        return Err(InitError::NoPacks(root.to_path_buf()));
+    }
+
+    let fallback_name = fs::canonicalize(root)
+        .ok()
+        .or(current_dir().ok())
+        .and_then(|v| v.file_name().map(|v| v.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    if let Some((_, manifest)) = &header {
+        config.name = header_text(manifest, "name").unwrap_or(fallback_name);
+        config.description = header_text(manifest, "description").unwrap_or_default();
+    } else {
+        config.name = fallback_name;
+    }
+
+    fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+        .map_err(|err| IoError::new("create", &config_path, err))?;
+
+    info!(
+        "Wrote {:?} with {} projects",
+        config_path.display(),
+        config.projects.len()
+    );
+
+    Ok(())
+}
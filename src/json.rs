@@ -0,0 +1,295 @@
+use crate::diagnostic::TextRange;
+use json_comments::StripComments;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+
+/// One step into a JSON document, either an object key or an array index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonPath(pub Vec<PathSegment>);
+
+impl JsonPath {
+    pub fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.0.clone();
+        path.push(segment);
+        Self(path)
+    }
+
+    pub fn key(&self, key: impl Into<String>) -> Self {
+        self.join(PathSegment::Key(key.into()))
+    }
+
+    pub fn index(&self, index: usize) -> Self {
+        self.join(PathSegment::Index(index))
+    }
+
+    /// The path as a JSON pointer for [`Value::pointer_mut`]
+    pub fn pointer(&self) -> String {
+        self.0
+            .iter()
+            .map(|segment| match segment {
+                PathSegment::Key(key) => format!("/{}", key.replace('~', "~0").replace('/', "~1")),
+                PathSegment::Index(index) => format!("/{index}"),
+            })
+            .collect()
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "$");
+        }
+
+        write!(f, "$")?;
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Key(key) => write!(f, "[{key:?}]")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses JSON the way Minecraft does, comments are allowed
+pub fn parse(text: &str) -> Result<Value, (TextRange, String)> {
+    serde_json::from_reader(StripComments::new(text.as_bytes())).map_err(|err| {
+        (
+            TextRange::line(err.line().max(1), err.column().max(1)),
+            err.to_string(),
+        )
+    })
+}
+
+/// Finds the byte range of the value at `path` inside `text`
+pub fn locate(text: &str, path: &JsonPath) -> Option<(usize, usize)> {
+    let mut scanner = Scanner {
+        bytes: text.as_bytes(),
+        index: 0,
+    };
+    scanner.skip_whitespace();
+    scanner.find(&path.0)
+}
+
+/// Like [`locate`], but converted into a line and column range, falling back to the closest parent
+pub fn locate_range(text: &str, path: &JsonPath) -> Option<TextRange> {
+    let mut path = path.0.clone();
+
+    loop {
+        if let Some((start, end)) = locate(text, &JsonPath(path.clone())) {
+            return Some(TextRange::from_offsets(text, start, end));
+        }
+
+        path.pop()?;
+    }
+}
+
+/// Maps a byte offset inside the decoded string at `path` to an offset in `text`,
+/// accounting for the quote and escape sequences
+pub fn string_offset(text: &str, path: &JsonPath, offset: usize) -> Option<usize> {
+    let (start, _) = locate(text, path)?;
+    let bytes = text.as_bytes();
+
+    if bytes.get(start) != Some(&b'"') {
+        return None;
+    }
+
+    let mut index = start + 1;
+    let mut decoded = 0;
+
+    while decoded < offset {
+        match bytes.get(index)? {
+            b'"' => break,
+            b'\\' if bytes.get(index + 1) == Some(&b'u') => {
+                let code = u32::from_str_radix(text.get(index + 2..index + 6)?, 16).ok()?;
+
+                // Surrogate pairs take two escapes for one four byte character
+                if (0xD800..0xDC00).contains(&code) {
+                    index += 12;
+                    decoded += 4;
+                } else {
+                    index += 6;
+                    decoded += char::from_u32(code).map_or(3, char::len_utf8);
+                }
+            }
+            b'\\' => {
+                index += 2;
+                decoded += 1;
+            }
+            _ => {
+                index += 1;
+                decoded += 1;
+            }
+        }
+    }
+
+    Some(index)
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.index).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.peek() {
+                Some(b' ' | b'\t' | b'\n' | b'\r') => self.index += 1,
+                Some(b'/') => match self.bytes.get(self.index + 1) {
+                    Some(b'/') => {
+                        while !matches!(self.peek(), Some(b'\n') | None) {
+                            self.index += 1;
+                        }
+                    }
+                    Some(b'*') => {
+                        self.index += 2;
+                        while self.index < self.bytes.len()
+                            && !self.bytes[self.index..].starts_with(b"*/")
+                        {
+                            self.index += 1;
+                        }
+                        self.index = (self.index + 2).min(self.bytes.len());
+                    }
+                    _ => return,
+                },
+                _ => return,
+            }
+        }
+    }
+
+    /// Skips a string starting at the current `"`, returning its raw contents
+    fn string(&mut self) -> Option<&[u8]> {
+        if self.peek()? != b'"' {
+            return None;
+        }
+
+        self.index += 1;
+        let start = self.index;
+
+        loop {
+            match self.peek()? {
+                b'\\' => self.index += 2,
+                b'"' => {
+                    let raw = &self.bytes[start..self.index];
+                    self.index += 1;
+                    return Some(raw);
+                }
+                _ => self.index += 1,
+            }
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => {
+                self.string()?;
+            }
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                self.index += 1;
+
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        v if v == close => {
+                            self.index += 1;
+                            break;
+                        }
+                        b',' | b':' => self.index += 1,
+                        _ => self.skip_value()?,
+                    }
+                }
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    Some(b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r' | b'/') | None
+                ) {
+                    self.index += 1;
+                }
+            }
+        }
+
+        Some(())
+    }
+
+    fn find(&mut self, path: &[PathSegment]) -> Option<(usize, usize)> {
+        let start = self.index;
+
+        let Some((segment, rest)) = path.split_first() else {
+            self.skip_value()?;
+            return Some((start, self.index));
+        };
+
+        match (segment, self.peek()?) {
+            (PathSegment::Key(target), b'{') => {
+                self.index += 1;
+
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b'}' => return None,
+                        b',' => {
+                            self.index += 1;
+                            continue;
+                        }
+                        b'"' => {}
+                        _ => return None,
+                    }
+
+                    let raw = self.string()?;
+                    let key: String = serde_json::from_slice(&[b"\"", raw, b"\""].concat()).ok()?;
+
+                    self.skip_whitespace();
+                    if self.peek()? != b':' {
+                        return None;
+                    }
+                    self.index += 1;
+                    self.skip_whitespace();
+
+                    if &key == target {
+                        return self.find(rest);
+                    }
+
+                    self.skip_value()?;
+                }
+            }
+            (PathSegment::Index(target), b'[') => {
+                self.index += 1;
+                let mut current = 0;
+
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b']' => return None,
+                        b',' => {
+                            self.index += 1;
+                            current += 1;
+                            continue;
+                        }
+                        _ => {}
+                    }
+
+                    if current == *target {
+                        return self.find(rest);
+                    }
+
+                    self.skip_value()?;
+                }
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use crate::config::StrixConfigPackType;
+use crate::diagnostic::{Diagnostic, Severity, TextRange};
+use crate::json;
+use crate::lint::{LintContext, LintFile, LintRule};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub const DEFAULT_PRIMARY_LANGUAGE: &str = "en_US";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LangLine {
+    Entry {
+        key: String,
+        value: String,
+        /// A trailing `\t# comment` or `\t## comment`, without the leading `#`
+        comment: Option<String>,
+    },
+    /// A full line `## comment`, without the leading `##`
+    Comment(String),
+    Blank,
+    /// Anything that isn't a comment and has no `=`, kept as is
+    Invalid(String),
+}
+
+/// Parses a `.lang` file line by line
+pub fn parse(text: &str) -> Vec<LangLine> {
+    text.trim_start_matches('\u{feff}')
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() {
+                return LangLine::Blank;
+            }
+
+            if let Some(comment) = trimmed.strip_prefix("##") {
+                return LangLine::Comment(comment.to_string());
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return LangLine::Invalid(line.trim_end().to_string());
+            };
+
+            // Trailing comments start with a tab and `#`, vanilla files end values with a bare `\t#`,
+            // a `#` without the tab is part of the value
+            let (value, comment) = match value.split_once("\t#") {
+                Some((value, comment)) => (value, Some(comment.to_string())),
+                None => (value, None),
+            };
+
+            LangLine::Entry {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+                comment,
+            }
+        })
+        .collect()
+}
+
+fn write_line(out: &mut String, line: &LangLine) {
+    match line {
+        LangLine::Entry {
+            key,
+            value,
+            comment,
+        } => {
+            out.push_str(key);
+            out.push('=');
+            out.push_str(value);
+            if let Some(comment) = comment {
+                out.push_str("\t#");
+                out.push_str(comment);
+            }
+        }
+        LangLine::Comment(comment) => {
+            out.push_str("##");
+            out.push_str(comment);
+        }
+        LangLine::Blank => {}
+        LangLine::Invalid(text) => out.push_str(text),
+    }
+    out.push('\n');
+}
+
+/// Normalizes `key=value` lines and blank lines, keeping comments.
+/// When sorting, comments directly above an entry move together with it.
+pub fn format(text: &str, sort: bool) -> String {
+    let mut lines = parse(text);
+
+    if sort {
+        // The header is everything up to the first blank line, if it has no entries
+        let header_end = lines
+            .iter()
+            .position(|v| *v == LangLine::Blank)
+            .filter(|end| {
+                lines[..*end]
+                    .iter()
+                    .all(|v| matches!(v, LangLine::Comment(_)))
+            })
+            .unwrap_or(0);
+
+        let mut groups: Vec<(String, Vec<LangLine>)> = vec![];
+        let mut pending = vec![];
+        let mut trailing = vec![];
+
+        for line in lines.drain(header_end..) {
+            match &line {
+                LangLine::Entry { key, .. } => {
+                    let key = key.clone();
+                    pending.push(line);
+                    groups.push((key, std::mem::take(&mut pending)));
+                }
+                LangLine::Blank => {}
+                _ => pending.push(line),
+            }
+        }
+        trailing.append(&mut pending);
+
+        groups.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if header_end > 0 {
+            lines.push(LangLine::Blank);
+        }
+        lines.extend(groups.into_iter().flat_map(|(_, v)| v));
+        lines.extend(trailing);
+    }
+
+    render(&lines)
+}
+
+/// Writes lines back into a `.lang` file, collapsing repeated blank lines
+pub fn render(lines: &[LangLine]) -> String {
+    let mut out = String::new();
+    let mut previous_blank = true;
+
+    for line in lines {
+        let blank = *line == LangLine::Blank;
+        if blank && previous_blank {
+            continue;
+        }
+
+        write_line(&mut out, line);
+        previous_blank = blank;
+    }
+
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+
+    out
+}
+
+/// Strips all comments and blank lines
+pub fn minify(text: &str) -> String {
+    let mut out = String::new();
+
+    for line in parse(text) {
+        if let LangLine::Entry { key, value, .. } = line {
+            write_line(
+                &mut out,
+                &LangLine::Entry {
+                    key,
+                    value,
+                    comment: None,
+                },
+            );
+        }
+    }
+
+    out
+}
+
+/// All keys of a `.lang` file together with their 1-based line
+pub fn keys(text: &str) -> Vec<(usize, String)> {
+    parse(text)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, line)| match line {
+            LangLine::Entry { key, .. } => Some((i + 1, key)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The language of a `texts/<language>.lang` file
+fn language_of(file: &LintFile) -> Option<&str> {
+    let name = file.relative.strip_prefix("texts/")?;
+    if name.contains('/') {
+        return None;
+    }
+    name.strip_suffix(".lang")
+}
+
+fn project_of(file: &LintFile) -> String {
+    let path = file.path.to_string_lossy().replace('\\', "/");
+    path.strip_suffix(&file.relative)
+        .unwrap_or(&path)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Groups all `texts/*.lang` files by project
+fn lang_files(ctx: &LintContext) -> BTreeMap<String, Vec<(&str, &LintFile)>> {
+    let mut projects: BTreeMap<String, Vec<(&str, &LintFile)>> = BTreeMap::new();
+
+    for file in &ctx.files {
+        if let Some(language) = language_of(file) {
+            projects
+                .entry(project_of(file))
+                .or_default()
+                .push((language, file));
+        }
+    }
+
+    projects
+}
+
+pub struct LangKeys;
+
+impl LintRule for LangKeys {
+    fn code(&self) -> &'static str {
+        "lang-keys"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let primary_language = ctx.config.lint.primary_language();
+
+        let mut diagnostics = vec![];
+
+        for files in lang_files(ctx).values() {
+            let mut parsed: HashMap<&str, Vec<(usize, String)>> = HashMap::new();
+
+            for (language, file) in files {
+                let keys = keys(file.text.as_deref().unwrap_or_default());
+                let mut seen = HashSet::new();
+
+                for (line, key) in &keys {
+                    if !seen.insert(key) {
+                        diagnostics.push(Diagnostic::new(
+                            self.code(),
+                            &file.path,
+                            Some(TextRange::line(*line, 1)),
+                            format!("Key {key:?} is defined more than once"),
+                        ));
+                    }
+                }
+
+                parsed.insert(language, keys);
+            }
+
+            let Some(primary) = parsed.get(primary_language) else {
+                continue;
+            };
+            let primary_keys: HashSet<&String> = primary.iter().map(|(_, k)| k).collect();
+
+            for (language, file) in files {
+                if *language == primary_language {
+                    continue;
+                }
+
+                let keys = &parsed[language];
+                let own: HashSet<&String> = keys.iter().map(|(_, k)| k).collect();
+
+                for (_, key) in primary.iter().filter(|(_, k)| !own.contains(k)) {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        None,
+                        format!("Key {key:?} from {primary_language:?} is missing"),
+                    ));
+                }
+
+                for (line, key) in keys.iter().filter(|(_, k)| !primary_keys.contains(k)) {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        Some(TextRange::line(*line, 1)),
+                        format!("Key {key:?} doesn't exist in {primary_language:?}"),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+pub struct LanguagesJson;
+
+impl LintRule for LanguagesJson {
+    fn code(&self) -> &'static str {
+        "languages-json"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let lang_files = lang_files(ctx);
+        let mut diagnostics = vec![];
+
+        for file in ctx
+            .files
+            .iter()
+            .filter(|v| v.relative == "texts/languages.json")
+        {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            let Some(languages) = json.as_array() else {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&json::JsonPath::default()),
+                    "\"languages.json\" has to be an array of language codes",
+                ));
+                continue;
+            };
+
+            let present: Vec<&str> = lang_files
+                .get(&project_of(file))
+                .into_iter()
+                .flatten()
+                .map(|(language, _)| *language)
+                .collect();
+
+            for (i, language) in languages.iter().enumerate() {
+                let path = json::JsonPath::default().index(i);
+
+                match language {
+                    Value::String(language) if !present.contains(&language.as_str()) => diagnostics
+                        .push(Diagnostic::new(
+                            self.code(),
+                            &file.path,
+                            file.range(&path),
+                            format!("Language {language:?} has no \"texts/{language}.lang\""),
+                        )),
+                    Value::String(_) => {}
+                    _ => diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        file.range(&path),
+                        "Expected a language code like \"en_US\"",
+                    )),
+                }
+            }
+
+            for language in present {
+                if !languages.iter().any(|v| v.as_str() == Some(language)) {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        file.range(&json::JsonPath::default()),
+                        format!("\"texts/{language}.lang\" isn't listed"),
+                    ));
+                }
+            }
+        }
+
+        // Resource packs with translations need a `languages.json` for the game to pick them up
+        for (project, files) in &lang_files {
+            let is_resource = files
+                .first()
+                .is_some_and(|(_, v)| v.pack_type == StrixConfigPackType::Resource);
+            let has_languages_json = ctx
+                .files
+                .iter()
+                .any(|v| v.relative == "texts/languages.json" && project_of(v) == *project);
+
+            if is_resource && !has_languages_json {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    format!("{project}/texts/languages.json"),
+                    None,
+                    "Missing \"texts/languages.json\" next to the \".lang\" files",
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "\u{feff}## Header\n## of the file\n\n\n## Zombie\nentity.zombie.name = Zombie\t## hostile\n\nitem.apple.name=Apple ## not a comment\nbroken line\n## trailing\n\n";
+
+    #[test]
+    fn parses_lines() {
+        assert_eq!(
+            parse(TEXT),
+            [
+                LangLine::Comment(" Header".to_string()),
+                LangLine::Comment(" of the file".to_string()),
+                LangLine::Blank,
+                LangLine::Blank,
+                LangLine::Comment(" Zombie".to_string()),
+                LangLine::Entry {
+                    key: "entity.zombie.name".to_string(),
+                    value: "Zombie".to_string(),
+                    comment: Some("# hostile".to_string()),
+                },
+                LangLine::Blank,
+                LangLine::Entry {
+                    key: "item.apple.name".to_string(),
+                    value: "Apple ## not a comment".to_string(),
+                    comment: None,
+                },
+                LangLine::Invalid("broken line".to_string()),
+                LangLine::Comment(" trailing".to_string()),
+                LangLine::Blank,
+            ]
+        );
+    }
+
+    #[test]
+    fn formats_without_sorting() {
+        assert_eq!(
+            format(TEXT, false),
+            "## Header\n## of the file\n\n## Zombie\nentity.zombie.name=Zombie\t## hostile\n\nitem.apple.name=Apple ## not a comment\nbroken line\n## trailing\n"
+        );
+    }
+
+    #[test]
+    fn sorts_below_the_header() {
+        let text = "## Header\n\n## Zombie\nentity.zombie.name=Zombie\nitem.apple.name=Apple\n## Cow\nentity.cow.name=Cow\n## trailing\n";
+
+        assert_eq!(
+            format(text, true),
+            "## Header\n\n## Cow\nentity.cow.name=Cow\n## Zombie\nentity.zombie.name=Zombie\nitem.apple.name=Apple\n## trailing\n"
+        );
+    }
+
+    #[test]
+    fn sorts_everything_without_a_header() {
+        // A comment block followed by an entry isn't a header, it belongs to the entry
+        let text = "## Zombie\nentity.zombie.name=Zombie\n\nentity.cow.name=Cow\n";
+
+        assert_eq!(
+            format(text, true),
+            "entity.cow.name=Cow\n## Zombie\nentity.zombie.name=Zombie\n"
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for sort in [false, true] {
+            let formatted = format(TEXT, sort);
+            assert_eq!(format(&formatted, sort), formatted, "sort: {sort}");
+        }
+    }
+
+    #[test]
+    fn minifies_and_lists_keys() {
+        assert_eq!(
+            minify(TEXT),
+            "entity.zombie.name=Zombie\nitem.apple.name=Apple ## not a comment\n"
+        );
+        assert_eq!(
+            keys(TEXT),
+            [
+                (6, "entity.zombie.name".to_string()),
+                (8, "item.apple.name".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn minifies_vanilla_trailing_comments() {
+        let text =
+            "entity.zombie.name=Zombie\t#\nitem.apple.name=Apple\t#  \nitem.bow.name=Bow #1\n";
+        assert_eq!(
+            minify(text),
+            "entity.zombie.name=Zombie\nitem.apple.name=Apple\nitem.bow.name=Bow #1\n"
+        );
+        assert_eq!(format(text, false), text);
+    }
+}
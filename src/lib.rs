@@ -0,0 +1,52 @@
+//! Strix builds, formats and lints Minecraft Bedrock addons
+//!
+//! The `strix` binary is a thin layer over this library, so tools can reuse it without shelling
+//! out. The main entry points are:
+//!
+//! - [`Project::load`] reads the `strix.json` of a folder, [`Project::profile`] picks a build
+//!   profile from it
+//! - [`build::run`] runs the build pipeline of a project with a profile and
+//!   [`build::BuildOptions`], reporting every step to a [`build::BuildProgress`], like a closure
+//!   taking a [`build::BuildEvent`]
+//! - [`fmt::format_buffer`] formats a single buffer with a [`fmt::FmtConfig`]
+//! - [`build::package`] zips built projects into a `.mcaddon`
+//!
+//! The binary only calls [`run_cli`], the commands behind it aren't part of the library.
+//! Diagnostics are logged with the [`log`] crate, install a logger to see them.
+
+pub(crate) mod add;
+pub(crate) mod args;
+pub mod build;
+pub(crate) mod build_info;
+mod cli;
+pub mod config;
+pub mod diagnostic;
+pub mod error;
+pub mod files;
+pub mod filter;
+pub mod flipbook;
+pub mod fmt;
+pub mod hook;
+pub(crate) mod import;
+pub mod index;
+pub(crate) mod init;
+mod json;
+mod lang;
+pub mod lint;
+mod manifest;
+mod mcfunction;
+pub(crate) mod message;
+mod molang;
+pub(crate) mod new;
+pub mod plugin;
+pub(crate) mod progress;
+pub mod project;
+pub mod report;
+mod schema;
+pub(crate) mod translation;
+mod xref;
+
+pub use cli::run_cli;
+pub use config::StrixConfig;
+pub use error::ExitCode;
+pub use project::Project;
@@ -0,0 +1,978 @@
+use crate::args::CliLintSubCommand;
+use crate::config::{ConfigError, StrixConfig, StrixConfigPackType, StrixLintConfig};
+use crate::diagnostic::{Diagnostic, Diagnostics, Severity, TextRange};
+use crate::error::{ExitCode, IoError};
+use crate::files::FileFilter;
+use crate::json::{locate_range, parse, JsonPath};
+use crate::lang::{self, LangKeys, LanguagesJson};
+use crate::manifest::{validate_manifest, MANIFEST};
+use crate::mcfunction::{CommandSyntax, InvalidSelector, UnknownCommand, UnresolvedFunction};
+use crate::molang::{MolangSyntax, MolangUndefinedVariable, MolangUnknownName};
+use crate::report::Report;
+use crate::schema::SchemaRule;
+use crate::xref::{OrphanedAsset, UnresolvedReference};
+use log::{info, warn};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Extensions of files which are loaded as text for the rules
+const TEXT_EXTENSIONS: &[&str] = &["json", "lang", "mcfunction", "material", "js", "ts"];
+
+pub const TEXTURE_EXTENSIONS: &[&str] = &["png", "tga", "jpg", "jpeg"];
+pub const SOUND_EXTENSIONS: &[&str] = &["ogg", "wav", "fsb", "mp3"];
+
+pub struct LintFile {
+    pub pack_type: StrixConfigPackType,
+    /// Path of the file, relative to the working directory
+    pub path: PathBuf,
+    /// Path of the file inside its pack, always using `/` as separator
+    pub relative: String,
+    pub text: Option<String>,
+    pub json: Option<Value>,
+}
+
+impl LintFile {
+    pub fn range(&self, path: &JsonPath) -> Option<TextRange> {
+        locate_range(self.text.as_deref()?, path)
+    }
+
+    pub fn is_in(&self, folder: &str) -> bool {
+        self.relative.starts_with(&format!("{folder}/"))
+    }
+
+    pub fn extension(&self) -> Option<&str> {
+        self.path.extension().and_then(OsStr::to_str)
+    }
+}
+
+pub struct LintContext {
+    /// The folder the projects of the config are relative to
+    pub root: PathBuf,
+    pub config: StrixConfig,
+    pub files: Vec<LintFile>,
+}
+
+impl LintContext {
+    /// Walks every project like the build does and loads all text files `filter` lets through
+    pub fn load(root: &Path, config: &StrixConfig, filter: &FileFilter) -> Self {
+        let mut files = vec![];
+
+        let mut projects: Vec<_> = config.projects.iter().collect();
+        projects.sort_by_key(|(k, _)| k.as_str());
+
+        for (project, pack_type) in projects {
+            let project = root.join(project);
+
+            for path in filter.walk(&project) {
+                let relative = path
+                    .strip_prefix(&project)
+                    .unwrap_or(&path)
+                    .components()
+                    .map(|v| v.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+
+                let extension = path.extension().and_then(OsStr::to_str);
+
+                let text = match extension {
+                    Some(ext) if TEXT_EXTENSIONS.contains(&ext) => {
+                        match fs::read_to_string(&path) {
+                            Ok(v) => Some(v),
+                            Err(err) => {
+                                warn!("Couldn't read {:?}, Err: {err}", path.display());
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
+                let json = match (extension, &text) {
+                    (Some("json"), Some(text)) => parse(text).ok(),
+                    _ => None,
+                };
+
+                files.push(LintFile {
+                    pack_type: pack_type.clone(),
+                    path,
+                    relative,
+                    text,
+                    json,
+                });
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            config: config.clone(),
+            files,
+        }
+    }
+
+    pub fn files_of(&self, pack_type: StrixConfigPackType) -> impl Iterator<Item = &LintFile> {
+        self.files.iter().filter(move |v| v.pack_type == pack_type)
+    }
+
+    /// All parsed JSON files of a pack type inside `folder`
+    pub fn json_in(
+        &self,
+        pack_type: StrixConfigPackType,
+        folder: &'static str,
+    ) -> impl Iterator<Item = (&LintFile, &Value)> {
+        self.files_of(pack_type)
+            .filter(move |v| v.is_in(folder))
+            .filter_map(|v| Some((v, v.json.as_ref()?)))
+    }
+
+    /// The frame folder of a configured flipbook `path` is in, like `textures/blocks/fire`
+    ///
+    /// Flipbooks of every profile count, as lint doesn't pick one.
+    pub fn flipbook_of(&self, path: &str) -> Option<&str> {
+        self.config
+            .build
+            .profiles
+            .values()
+            .flat_map(|v| &v.flipbooks)
+            .map(|v| v.frames.trim_end_matches('/'))
+            .find(|frames| {
+                path.strip_prefix(frames)
+                    .is_some_and(|v| v.starts_with('/'))
+            })
+    }
+
+    /// If `path` is the strip of a configured flipbook, with or without `.png`, and it has frames
+    fn is_flipbook_strip(&self, path: &str) -> bool {
+        let path = path.strip_suffix(".png").unwrap_or(path);
+
+        self.files_of(StrixConfigPackType::Resource)
+            .any(|v| self.flipbook_of(&v.relative) == Some(path))
+    }
+
+    /// Checks if `path` without extension exists in any project of `pack_type`
+    pub fn asset_exists(
+        &self,
+        pack_type: StrixConfigPackType,
+        path: &str,
+        extensions: &[&str],
+    ) -> bool {
+        // Strips are only written by the build, from the frames inside the folder
+        if pack_type == StrixConfigPackType::Resource && self.is_flipbook_strip(path) {
+            return true;
+        }
+
+        self.files_of(pack_type).any(|file| {
+            let Some(stem) = file.relative.rsplit_once('.').map(|(v, _)| v) else {
+                return false;
+            };
+
+            (stem == path
+                && file
+                    .extension()
+                    .is_some_and(|ext| extensions.contains(&ext)))
+                || file.relative == path
+        })
+    }
+}
+
+#[cfg(test)]
+impl LintContext {
+    /// A context over in-memory files, given as pack type, path inside the pack and text
+    pub(crate) fn from_files(
+        config: StrixConfig,
+        files: &[(StrixConfigPackType, &str, &str)],
+    ) -> Self {
+        let files = files
+            .iter()
+            .map(|(pack_type, relative, text)| {
+                let extension = relative.rsplit_once('.').map(|(_, v)| v);
+                let text = extension
+                    .filter(|v| TEXT_EXTENSIONS.contains(v))
+                    .map(|_| text.to_string());
+
+                LintFile {
+                    pack_type: pack_type.clone(),
+                    path: PathBuf::from(format!("{pack_type:?}/{relative}")),
+                    relative: relative.to_string(),
+                    json: match extension {
+                        Some("json") => text.as_deref().and_then(|v| parse(v).ok()),
+                        _ => None,
+                    },
+                    text,
+                }
+            })
+            .collect();
+
+        Self {
+            root: PathBuf::new(),
+            config,
+            files,
+        }
+    }
+}
+
+pub trait LintRule: Send + Sync {
+    /// The code used in diagnostics and in the `lint.rules` section of `strix.json`
+    fn code(&self) -> &'static str;
+    fn default_severity(&self) -> Severity;
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic>;
+}
+
+pub fn rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(BrokenJson),
+        Box::new(InvalidManifest),
+        Box::new(DuplicateUuid),
+        Box::new(MissingTexture),
+        Box::new(MissingSound),
+        Box::new(UndefinedLangKey),
+        Box::new(MismatchedIdentifier),
+        Box::new(SchemaRule),
+        Box::new(UnresolvedReference),
+        Box::new(OrphanedAsset),
+        Box::new(LangKeys),
+        Box::new(LanguagesJson),
+        Box::new(MolangSyntax),
+        Box::new(MolangUnknownName),
+        Box::new(MolangUndefinedVariable),
+        Box::new(UnknownCommand),
+        Box::new(CommandSyntax),
+        Box::new(InvalidSelector),
+        Box::new(UnresolvedFunction),
+    ]
+}
+
+/// Runs all rules, or only the ones listed in `only`, applying the severities configured in `strix.json`
+pub fn run_rules(
+    ctx: &LintContext,
+    config: &StrixLintConfig,
+    only: Option<&[&str]>,
+) -> Vec<Diagnostic> {
+    check_rules(&rules(), ctx, config, only)
+}
+
+fn check_rules(
+    rules: &[Box<dyn LintRule>],
+    ctx: &LintContext,
+    config: &StrixLintConfig,
+    only: Option<&[&str]>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for rule in rules {
+        if only.is_some_and(|v| !v.contains(&rule.code())) {
+            continue;
+        }
+
+        let severity = config
+            .rules
+            .get(rule.code())
+            .copied()
+            .unwrap_or(rule.default_severity());
+
+        if severity == Severity::Off {
+            continue;
+        }
+
+        for mut diagnostic in rule.check(ctx) {
+            diagnostic.severity = severity;
+            diagnostics.push(diagnostic);
+        }
+    }
+
+    diagnostics.sort_by(|a, b| {
+        (&a.file, a.range.map(|v| v.start)).cmp(&(&b.file, b.range.map(|v| v.start)))
+    });
+
+    diagnostics
+}
+
+#[derive(Error, Debug)]
+pub enum LintError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("Found {errors} errors and {warnings} warnings")]
+    Errors { errors: usize, warnings: usize },
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl LintError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            LintError::Config(_) => ExitCode::Config,
+            LintError::Errors { .. } => ExitCode::Validation,
+            LintError::Io(_) => ExitCode::Io,
+        }
+    }
+}
+
+pub(crate) async fn lint(
+    lint: CliLintSubCommand,
+    config: Option<StrixConfig>,
+) -> Result<(), LintError> {
+    let config = config.ok_or(ConfigError::Missing("linting"))?;
+
+    let filter = FileFilter::new(Path::new(""), &config)?;
+    let ctx = LintContext::load(Path::new(""), &config, &filter);
+
+    if !lint.quiet {
+        info!(
+            "Linting {} files in {} projects",
+            ctx.files.len(),
+            config.projects.len()
+        );
+    }
+
+    let diagnostics = Diagnostics::default();
+    diagnostics.extend(run_rules(&ctx, &config.lint, None));
+
+    let errors = diagnostics.count(Severity::Error);
+    let warnings = diagnostics.count(Severity::Warning);
+
+    if errors == 0 && warnings > 0 {
+        warn!("Found {warnings} warnings");
+    } else if errors == 0 && !lint.quiet {
+        info!("No problems found");
+    }
+
+    let report = Report {
+        files: ctx.files.iter().map(|v| v.path.clone()).collect(),
+        diagnostics: diagnostics.into_vec(),
+    };
+    report.write(
+        lint.report.sarif.as_deref(),
+        lint.report.junit.as_deref(),
+        "lint",
+    )?;
+
+    check_errors(&report.diagnostics)
+}
+
+/// Fails if any diagnostic is an error, warnings alone pass
+fn check_errors(diagnostics: &[Diagnostic]) -> Result<(), LintError> {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|v| v.severity == severity)
+            .count()
+    };
+
+    match count(Severity::Error) {
+        0 => Ok(()),
+        errors => Err(LintError::Errors {
+            errors,
+            warnings: count(Severity::Warning),
+        }),
+    }
+}
+
+/// Returns the `description.identifier` of a definition like `minecraft:entity`
+pub fn identifier_of(json: &Value) -> Option<(JsonPath, &str)> {
+    let (key, definition) = json
+        .as_object()?
+        .iter()
+        .find(|(k, v)| (k.starts_with("minecraft:") || *k == "particle_effect") && v.is_object())?;
+
+    let identifier = definition.get("description")?.get("identifier")?.as_str()?;
+
+    Some((
+        JsonPath::default()
+            .key(key)
+            .key("description")
+            .key("identifier"),
+        identifier,
+    ))
+}
+
+struct BrokenJson;
+
+impl LintRule for BrokenJson {
+    fn code(&self) -> &'static str {
+        "broken-json"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        ctx.files
+            .iter()
+            .filter(|v| v.extension() == Some("json"))
+            .filter_map(|file| {
+                let (range, message) = parse(file.text.as_deref()?).err()?;
+                Some(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    Some(range),
+                    message,
+                ))
+            })
+            .collect()
+    }
+}
+
+struct InvalidManifest;
+
+impl LintRule for InvalidManifest {
+    fn code(&self) -> &'static str {
+        "invalid-manifest"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for project in ctx.config.projects.keys() {
+            let path = ctx.root.join(project).join(MANIFEST);
+
+            if !path.is_file() {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    path,
+                    None,
+                    format!("Project {project:?} has no {MANIFEST:?}"),
+                ));
+            }
+        }
+
+        for file in ctx.files.iter().filter(|v| v.relative == MANIFEST) {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            for problem in validate_manifest(json, &file.pack_type) {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&problem.path),
+                    problem.message,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+struct DuplicateUuid;
+
+impl LintRule for DuplicateUuid {
+    fn code(&self) -> &'static str {
+        "duplicate-uuid"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut seen: HashMap<String, PathBuf> = HashMap::new();
+        let mut diagnostics = vec![];
+
+        for file in ctx.files.iter().filter(|v| v.relative == MANIFEST) {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            let mut uuids = vec![(
+                JsonPath::default().key("header").key("uuid"),
+                json.get("header"),
+            )];
+
+            if let Some(modules) = json.get("modules").and_then(Value::as_array) {
+                for (i, module) in modules.iter().enumerate() {
+                    uuids.push((
+                        JsonPath::default().key("modules").index(i).key("uuid"),
+                        Some(module),
+                    ));
+                }
+            }
+
+            for (path, owner) in uuids {
+                let Some(uuid) = owner.and_then(|v| v.get("uuid")).and_then(Value::as_str) else {
+                    continue;
+                };
+
+                match seen.get(&uuid.to_ascii_lowercase()) {
+                    Some(first) => diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        file.range(&path),
+                        format!("UUID {uuid:?} is already used in {:?}", first.display()),
+                    )),
+                    None => {
+                        seen.insert(uuid.to_ascii_lowercase(), file.path.clone());
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Collects the texture paths referenced by texture registries and client entities
+pub fn texture_references(ctx: &LintContext) -> Vec<(&LintFile, JsonPath, &str)> {
+    let mut references = vec![];
+
+    for file in ctx.files_of(StrixConfigPackType::Resource) {
+        let Some(json) = &file.json else {
+            continue;
+        };
+
+        match file.relative.as_str() {
+            "textures/item_texture.json" | "textures/terrain_texture.json" => {
+                let Some(data) = json.get("texture_data").and_then(Value::as_object) else {
+                    continue;
+                };
+
+                for (name, entry) in data {
+                    let path = JsonPath::default()
+                        .key("texture_data")
+                        .key(name)
+                        .key("textures");
+
+                    let textures = match entry.get("textures") {
+                        Some(Value::String(v)) => vec![(path, v.as_str())],
+                        Some(Value::Array(v)) => v
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(i, v)| {
+                                let v = match v {
+                                    Value::String(v) => v.as_str(),
+                                    Value::Object(v) => v.get("path")?.as_str()?,
+                                    _ => return None,
+                                };
+                                Some((path.index(i), v))
+                            })
+                            .collect(),
+                        Some(Value::Object(v)) => match v.get("path").and_then(Value::as_str) {
+                            Some(texture) => vec![(path.key("path"), texture)],
+                            None => vec![],
+                        },
+                        _ => vec![],
+                    };
+
+                    for (path, texture) in textures {
+                        references.push((file, path, texture));
+                    }
+                }
+            }
+            _ if file.is_in("entity") || file.is_in("attachables") => {
+                let Some((key, definition)) = json
+                    .as_object()
+                    .and_then(|v| v.iter().find(|(k, _)| k.starts_with("minecraft:")))
+                else {
+                    continue;
+                };
+
+                let Some(textures) = definition
+                    .get("description")
+                    .and_then(|v| v.get("textures"))
+                    .and_then(Value::as_object)
+                else {
+                    continue;
+                };
+
+                for (name, texture) in textures {
+                    if let Some(texture) = texture.as_str() {
+                        references.push((
+                            file,
+                            JsonPath::default()
+                                .key(key)
+                                .key("description")
+                                .key("textures")
+                                .key(name),
+                            texture,
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    references
+}
+
+struct MissingTexture;
+
+impl LintRule for MissingTexture {
+    fn code(&self) -> &'static str {
+        "missing-texture"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        texture_references(ctx)
+            .into_iter()
+            .filter(|(_, _, texture)| {
+                !ctx.asset_exists(StrixConfigPackType::Resource, texture, TEXTURE_EXTENSIONS)
+            })
+            .map(|(file, path, texture)| {
+                Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&path),
+                    format!("Texture {texture:?} doesn't exist in any Resource project"),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Collects the sound files referenced in `sound_definitions.json`
+pub fn sound_references(ctx: &LintContext) -> Vec<(&LintFile, JsonPath, &str)> {
+    let mut references = vec![];
+
+    for file in ctx.files_of(StrixConfigPackType::Resource) {
+        if file.relative != "sounds/sound_definitions.json" {
+            continue;
+        }
+
+        let Some(json) = &file.json else {
+            continue;
+        };
+
+        // Newer files wrap everything in `sound_definitions`, older ones don't
+        let (root, definitions) = match json.get("sound_definitions").and_then(Value::as_object) {
+            Some(v) => (JsonPath::default().key("sound_definitions"), v),
+            None => match json.as_object() {
+                Some(v) => (JsonPath::default(), v),
+                None => continue,
+            },
+        };
+
+        for (name, definition) in definitions {
+            let Some(sounds) = definition.get("sounds").and_then(Value::as_array) else {
+                continue;
+            };
+
+            for (i, sound) in sounds.iter().enumerate() {
+                let path = root.key(name).key("sounds").index(i);
+                match sound {
+                    Value::String(v) => references.push((file, path, v.as_str())),
+                    Value::Object(v) => {
+                        if let Some(sound) = v.get("name").and_then(Value::as_str) {
+                            references.push((file, path.key("name"), sound));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    references
+}
+
+struct MissingSound;
+
+impl LintRule for MissingSound {
+    fn code(&self) -> &'static str {
+        "missing-sound"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        sound_references(ctx)
+            .into_iter()
+            .filter(|(_, _, sound)| {
+                !ctx.asset_exists(StrixConfigPackType::Resource, sound, SOUND_EXTENSIONS)
+            })
+            .map(|(file, path, sound)| {
+                Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&path),
+                    format!("Sound {sound:?} doesn't exist in any Resource project"),
+                )
+            })
+            .collect()
+    }
+}
+
+struct UndefinedLangKey;
+
+impl LintRule for UndefinedLangKey {
+    fn code(&self) -> &'static str {
+        "undefined-lang-key"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let primary_lang = format!("texts/{}.lang", ctx.config.lint.primary_language());
+
+        let defined: HashSet<String> = ctx
+            .files_of(StrixConfigPackType::Resource)
+            .filter(|v| v.relative == primary_lang)
+            .filter_map(|v| v.text.as_deref())
+            .flat_map(|v| lang::keys(v).into_iter().map(|(_, key)| key))
+            .collect();
+
+        let mut referenced: Vec<(&LintFile, JsonPath, String)> = vec![];
+
+        for (file, json) in ctx.json_in(StrixConfigPackType::Behaviour, "items") {
+            let path = JsonPath::default()
+                .key("minecraft:item")
+                .key("components")
+                .key("minecraft:display_name")
+                .key("value");
+
+            if let Some(key) = json
+                .pointer("/minecraft:item/components/minecraft:display_name/value")
+                .and_then(Value::as_str)
+            {
+                referenced.push((file, path, key.to_string()));
+            }
+        }
+
+        for (file, json) in ctx.json_in(StrixConfigPackType::Behaviour, "blocks") {
+            let path = JsonPath::default()
+                .key("minecraft:block")
+                .key("components")
+                .key("minecraft:display_name");
+
+            if let Some(key) = json
+                .pointer("/minecraft:block/components/minecraft:display_name")
+                .and_then(Value::as_str)
+            {
+                referenced.push((file, path, key.to_string()));
+            }
+        }
+
+        if ctx.files_of(StrixConfigPackType::Resource).next().is_some() {
+            for (file, json) in ctx.json_in(StrixConfigPackType::Behaviour, "entities") {
+                if let Some((path, identifier)) = identifier_of(json) {
+                    if !identifier.starts_with("minecraft:") {
+                        referenced.push((file, path, format!("entity.{identifier}.name")));
+                    }
+                }
+            }
+        }
+
+        referenced
+            .into_iter()
+            // Display names may also be literal text instead of a lang key
+            .filter(|(_, _, key)| key.contains('.') && !key.contains(' '))
+            .filter(|(_, _, key)| !defined.contains(key))
+            .map(|(file, path, key)| {
+                Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&path),
+                    format!("Lang key {key:?} isn't defined in any {primary_lang:?}"),
+                )
+            })
+            .collect()
+    }
+}
+
+struct MismatchedIdentifier;
+
+impl LintRule for MismatchedIdentifier {
+    fn code(&self) -> &'static str {
+        "mismatched-identifier"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        let definitions: Vec<(&LintFile, JsonPath, &str)> = ctx
+            .files
+            .iter()
+            .filter(|v| v.relative != MANIFEST)
+            .filter_map(|file| {
+                let (path, identifier) = identifier_of(file.json.as_ref()?)?;
+                Some((file, path, identifier))
+            })
+            .collect();
+
+        for (file, path, identifier) in &definitions {
+            let Some((namespace, _)) = identifier.split_once(':') else {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(path),
+                    format!("Identifier {identifier:?} has no namespace"),
+                ));
+                continue;
+            };
+
+            if !ctx.config.namespace.is_empty()
+                && namespace != "minecraft"
+                && namespace != ctx.config.namespace
+            {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(path),
+                    format!(
+                        "Identifier {identifier:?} doesn't use the project namespace {:?}",
+                        ctx.config.namespace
+                    ),
+                ));
+            }
+        }
+
+        let has_resource = ctx.files_of(StrixConfigPackType::Resource).next().is_some();
+        let has_behaviour = ctx
+            .files_of(StrixConfigPackType::Behaviour)
+            .next()
+            .is_some();
+
+        let server: Vec<_> = definitions
+            .iter()
+            .filter(|(file, _, _)| {
+                file.pack_type == StrixConfigPackType::Behaviour && file.is_in("entities")
+            })
+            .collect();
+        let client: Vec<_> = definitions
+            .iter()
+            .filter(|(file, _, _)| {
+                file.pack_type == StrixConfigPackType::Resource && file.is_in("entity")
+            })
+            .collect();
+
+        if has_resource {
+            for (file, path, identifier) in &server {
+                if !identifier.starts_with("minecraft:")
+                    && !client.iter().any(|(_, _, v)| v == identifier)
+                {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        file.range(path),
+                        format!(
+                            "Entity {identifier:?} has no client entity in any Resource project"
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if has_behaviour {
+            for (file, path, identifier) in &client {
+                if !identifier.starts_with("minecraft:")
+                    && !server.iter().any(|(_, _, v)| v == identifier)
+                {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        file.range(path),
+                        format!(
+                            "Client entity {identifier:?} has no entity in any Behaviour project"
+                        ),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reports every file it sees
+    struct Stub;
+
+    impl LintRule for Stub {
+        fn code(&self) -> &'static str {
+            "stub"
+        }
+
+        fn default_severity(&self) -> Severity {
+            Severity::Error
+        }
+
+        fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+            ctx.files
+                .iter()
+                .map(|v| Diagnostic::new(self.code(), &v.path, None, "found a file"))
+                .collect()
+        }
+    }
+
+    fn check(severity: Option<Severity>, only: Option<&[&str]>) -> Vec<Diagnostic> {
+        let ctx = LintContext::from_files(
+            StrixConfig::default(),
+            &[
+                (StrixConfigPackType::Behaviour, "b.json", "{}"),
+                (StrixConfigPackType::Resource, "a.json", "{}"),
+            ],
+        );
+        let mut config = StrixLintConfig::default();
+        if let Some(severity) = severity {
+            config.rules.insert(String::from("stub"), severity);
+        }
+
+        check_rules(&[Box::new(Stub)], &ctx, &config, only)
+    }
+
+    #[test]
+    fn uses_the_default_severity() {
+        let diagnostics = check(None, None);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|v| v.severity == Severity::Error));
+        // Sorted by file
+        assert_eq!(diagnostics[0].file, Path::new("Behaviour/b.json"));
+
+        let err = check_errors(&diagnostics).unwrap_err();
+        assert!(matches!(
+            err,
+            LintError::Errors {
+                errors: 2,
+                warnings: 0
+            }
+        ));
+        assert_eq!(err.exit_code(), ExitCode::Validation);
+        assert_ne!(err.exit_code() as i32, 0);
+    }
+
+    #[test]
+    fn applies_configured_severities() {
+        let diagnostics = check(Some(Severity::Warning), None);
+
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|v| v.severity == Severity::Warning));
+        assert!(check_errors(&diagnostics).is_ok());
+
+        assert!(check(Some(Severity::Off), None).is_empty());
+
+        let config: StrixLintConfig =
+            serde_json::from_str(r#"{ "rules": { "stub": "Off", "schema": "Info" } }"#).unwrap();
+        assert_eq!(config.rules["stub"], Severity::Off);
+        assert_eq!(config.rules["schema"], Severity::Info);
+    }
+
+    #[test]
+    fn runs_only_the_listed_rules() {
+        assert!(check(None, Some(&["schema"])).is_empty());
+        assert_eq!(check(None, Some(&["stub"])).len(), 2);
+    }
+}
@@ -0,0 +1,598 @@
+use crate::config::StrixConfigPackType;
+use crate::json::JsonPath;
+use json_comments::StripComments;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+pub const MANIFEST: &str = "manifest.json";
+
+pub fn read_manifest(path: &Path) -> Result<Value, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    parse_manifest(&text)
+}
+
+pub fn parse_manifest(text: &str) -> Result<Value, String> {
+    serde_json::from_reader(StripComments::new(text.as_bytes())).map_err(|err| err.to_string())
+}
+
+/// Infers the pack type from the module types declared in a manifest
+pub fn pack_type_of(manifest: &Value) -> Option<StrixConfigPackType> {
+    let modules = manifest.get("modules")?.as_array()?;
+
+    let types: Vec<&str> = modules
+        .iter()
+        .filter_map(|module| module.get("type").and_then(Value::as_str))
+        .collect();
+
+    if types.contains(&"resources") {
+        Some(StrixConfigPackType::Resource)
+    } else if types
+        .iter()
+        .any(|v| matches!(*v, "data" | "script" | "javascript"))
+    {
+        Some(StrixConfigPackType::Behaviour)
+    } else if types.contains(&"skin_pack") {
+        Some(StrixConfigPackType::Skin)
+    } else if types.contains(&"world_template") {
+        Some(StrixConfigPackType::WorldTemplate)
+    } else {
+        None
+    }
+}
+
+/// Reads a text field like `name` or `description` from the manifest header,
+/// ignoring localization keys like `pack.name`
+pub fn header_text(manifest: &Value, field: &str) -> Option<String> {
+    let text = manifest.get("header")?.get(field)?.as_str()?.trim();
+
+    if text.is_empty() || text.starts_with("pack.") {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+/// A version either written as `[major, minor, patch]` or, since format version 2, as a semver string
+// The typed manifest mirrors the Bedrock format, not every field is read by strix yet
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ManifestVersion {
+    Array([u32; 3]),
+    String(String),
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestHeader {
+    pub name: String,
+    pub description: Option<String>,
+    pub uuid: Uuid,
+    pub version: ManifestVersion,
+    pub min_engine_version: Option<ManifestVersion>,
+    pub base_game_version: Option<ManifestVersion>,
+    pub lock_template_options: Option<bool>,
+    pub allow_random_seed: Option<bool>,
+    pub platform_locked: Option<bool>,
+    pub pack_scope: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestModuleType {
+    Resources,
+    Data,
+    ClientData,
+    Interface,
+    WorldTemplate,
+    Script,
+    Javascript,
+    SkinPack,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestModule {
+    #[serde(rename = "type")]
+    pub module_type: ManifestModuleType,
+    pub uuid: Uuid,
+    pub version: ManifestVersion,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub entry: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestDependency {
+    pub uuid: Option<Uuid>,
+    pub module_name: Option<String>,
+    pub version: ManifestVersion,
+}
+
+/// A problem found in a manifest, together with where it was found
+#[derive(Debug, Clone)]
+pub struct ManifestProblem {
+    pub path: JsonPath,
+    pub message: String,
+}
+
+impl ManifestProblem {
+    fn new(path: JsonPath, message: impl Into<String>) -> Self {
+        Self {
+            path,
+            message: message.into(),
+        }
+    }
+}
+
+fn allowed_modules(pack_type: &StrixConfigPackType) -> &'static [ManifestModuleType] {
+    match pack_type {
+        StrixConfigPackType::Behaviour => &[
+            ManifestModuleType::Data,
+            ManifestModuleType::Script,
+            ManifestModuleType::Javascript,
+            ManifestModuleType::ClientData,
+        ],
+        StrixConfigPackType::Resource => {
+            &[ManifestModuleType::Resources, ManifestModuleType::Interface]
+        }
+        StrixConfigPackType::Skin => &[ManifestModuleType::SkinPack],
+        StrixConfigPackType::WorldTemplate => &[ManifestModuleType::WorldTemplate],
+    }
+}
+
+/// Deserializes `value` into `T`, reporting failures at `path`
+fn typed<T: DeserializeOwned>(
+    value: &Value,
+    path: JsonPath,
+    problems: &mut Vec<ManifestProblem>,
+) -> Option<T> {
+    match serde_json::from_value(value.clone()) {
+        Ok(v) => Some(v),
+        Err(err) => {
+            problems.push(ManifestProblem::new(path, err.to_string()));
+            None
+        }
+    }
+}
+
+fn check_version(
+    version: &ManifestVersion,
+    format_version: u64,
+    path: JsonPath,
+    problems: &mut Vec<ManifestProblem>,
+) {
+    if let ManifestVersion::String(text) = version {
+        if format_version < 2 {
+            problems.push(ManifestProblem::new(
+                path,
+                format!("Version {text:?} has to be written as [major, minor, patch] in format version 1"),
+            ));
+        } else if !is_semver(text) {
+            problems.push(ManifestProblem::new(
+                path,
+                format!("Version {text:?} isn't a valid semver version like \"1.0.0\""),
+            ));
+        }
+    }
+}
+
+fn is_semver(text: &str) -> bool {
+    let core = text.split(['-', '+']).next().unwrap_or_default();
+    let parts: Vec<&str> = core.split('.').collect();
+
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|v| !v.is_empty() && v.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Validates a manifest against the Bedrock manifest format and the pack type declared in `strix.json`
+pub fn validate_manifest(
+    manifest: &Value,
+    pack_type: &StrixConfigPackType,
+) -> Vec<ManifestProblem> {
+    let mut problems = vec![];
+    let root = JsonPath::default();
+
+    let Some(object) = manifest.as_object() else {
+        problems.push(ManifestProblem::new(
+            root,
+            "A manifest has to be a JSON object",
+        ));
+        return problems;
+    };
+
+    let format_version = match object.get("format_version") {
+        Some(Value::Number(v)) if matches!(v.as_u64(), Some(1..=2)) => v.as_u64().unwrap_or(2),
+        Some(other) => {
+            problems.push(ManifestProblem::new(
+                root.key("format_version"),
+                format!("Unsupported format_version {other}, expected 1 or 2"),
+            ));
+            return problems;
+        }
+        None => {
+            problems.push(ManifestProblem::new(
+                root,
+                "Missing required field \"format_version\"",
+            ));
+            return problems;
+        }
+    };
+
+    for key in object.keys() {
+        if !matches!(
+            key.as_str(),
+            "format_version"
+                | "header"
+                | "modules"
+                | "dependencies"
+                | "metadata"
+                | "capabilities"
+                | "subpacks"
+                | "settings"
+        ) {
+            problems.push(ManifestProblem::new(
+                root.key(key),
+                format!("Unknown field {key:?}"),
+            ));
+        }
+    }
+
+    let header = match object.get("header") {
+        Some(v) => typed::<ManifestHeader>(v, root.key("header"), &mut problems),
+        None => {
+            problems.push(ManifestProblem::new(
+                root.clone(),
+                "Missing required field \"header\"",
+            ));
+            None
+        }
+    };
+
+    let mut modules = vec![];
+    match object.get("modules") {
+        Some(Value::Array(v)) if !v.is_empty() => {
+            for (i, module) in v.iter().enumerate() {
+                let path = root.key("modules").index(i);
+                if let Some(module) = typed::<ManifestModule>(module, path.clone(), &mut problems) {
+                    modules.push((path, module));
+                }
+            }
+        }
+        Some(_) => problems.push(ManifestProblem::new(
+            root.key("modules"),
+            "\"modules\" has to be a non empty array",
+        )),
+        None => problems.push(ManifestProblem::new(
+            root.clone(),
+            "Missing required field \"modules\"",
+        )),
+    }
+
+    let mut dependencies = vec![];
+    match object.get("dependencies") {
+        Some(Value::Array(v)) => {
+            for (i, dependency) in v.iter().enumerate() {
+                let path = root.key("dependencies").index(i);
+                if let Some(dependency) =
+                    typed::<ManifestDependency>(dependency, path.clone(), &mut problems)
+                {
+                    dependencies.push((path, dependency));
+                }
+            }
+        }
+        Some(_) => problems.push(ManifestProblem::new(
+            root.key("dependencies"),
+            "\"dependencies\" has to be an array",
+        )),
+        None => {}
+    }
+
+    if let Some(header) = &header {
+        let path = root.key("header");
+        check_version(
+            &header.version,
+            format_version,
+            path.key("version"),
+            &mut problems,
+        );
+
+        if header.name.trim().is_empty() {
+            problems.push(ManifestProblem::new(
+                path.key("name"),
+                "\"name\" must not be empty",
+            ));
+        }
+
+        match &header.min_engine_version {
+            Some(ManifestVersion::Array(v)) if format_version >= 2 && *v < [1, 13, 0] => {
+                problems.push(ManifestProblem::new(
+                    path.key("min_engine_version"),
+                    format!("Format version 2 requires a \"min_engine_version\" of at least [1, 13, 0], found {v:?}"),
+                ))
+            }
+            Some(ManifestVersion::Array(_)) => {}
+            Some(ManifestVersion::String(v)) => problems.push(ManifestProblem::new(
+                path.key("min_engine_version"),
+                format!("\"min_engine_version\" has to be written as [major, minor, patch], found {v:?}"),
+            )),
+            None if format_version >= 2
+                && matches!(
+                    pack_type,
+                    StrixConfigPackType::Behaviour | StrixConfigPackType::Resource
+                ) =>
+            {
+                problems.push(ManifestProblem::new(
+                    path.clone(),
+                    "Missing required field \"min_engine_version\" for format version 2",
+                ))
+            }
+            None => {}
+        }
+
+        if *pack_type == StrixConfigPackType::WorldTemplate {
+            if header.base_game_version.is_none() {
+                problems.push(ManifestProblem::new(
+                    path.clone(),
+                    "Missing required field \"base_game_version\" for World Templates",
+                ));
+            }
+            if header.lock_template_options.is_none() {
+                problems.push(ManifestProblem::new(
+                    path.clone(),
+                    "Missing required field \"lock_template_options\" for World Templates",
+                ));
+            }
+        }
+
+        for (module_path, module) in &modules {
+            if module.uuid == header.uuid {
+                problems.push(ManifestProblem::new(
+                    module_path.key("uuid"),
+                    "Module uuid has to differ from the header uuid",
+                ));
+            }
+        }
+    }
+
+    let allowed = allowed_modules(pack_type);
+    let mut seen = vec![];
+
+    for (path, module) in &modules {
+        check_version(
+            &module.version,
+            format_version,
+            path.key("version"),
+            &mut problems,
+        );
+
+        if !allowed.contains(&module.module_type) {
+            problems.push(ManifestProblem::new(
+                path.key("type"),
+                format!(
+                    "Module type {:?} isn't allowed in a {:?} pack, expected one of {:?}",
+                    module.module_type, pack_type, allowed
+                ),
+            ));
+        }
+
+        if matches!(
+            module.module_type,
+            ManifestModuleType::Script | ManifestModuleType::Javascript
+        ) {
+            if module.entry.is_none() {
+                problems.push(ManifestProblem::new(
+                    path.clone(),
+                    "Script modules require an \"entry\" file",
+                ));
+            }
+            if module.module_type == ManifestModuleType::Script
+                && module.language.as_deref() != Some("javascript")
+            {
+                problems.push(ManifestProblem::new(
+                    path.key("language"),
+                    "Script modules require \"language\": \"javascript\"",
+                ));
+            }
+        }
+
+        if seen.contains(&module.uuid) {
+            problems.push(ManifestProblem::new(
+                path.key("uuid"),
+                format!("Module uuid {:?} is used more than once", module.uuid),
+            ));
+        }
+        seen.push(module.uuid);
+    }
+
+    for (path, dependency) in &dependencies {
+        match (&dependency.uuid, &dependency.module_name) {
+            (Some(_), None) => check_version(
+                &dependency.version,
+                format_version,
+                path.key("version"),
+                &mut problems,
+            ),
+            (None, Some(_)) => {}
+            (Some(_), Some(_)) => problems.push(ManifestProblem::new(
+                path.clone(),
+                "A dependency has either a \"uuid\" or a \"module_name\", not both",
+            )),
+            (None, None) => problems.push(ManifestProblem::new(
+                path.clone(),
+                "A dependency requires a \"uuid\" or a \"module_name\"",
+            )),
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn behaviour_manifest() -> Value {
+        json!({
+            "format_version": 2,
+            "header": {
+                "name": "Foo",
+                "description": "pack.description",
+                "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11",
+                "version": [1, 0, 0],
+                "min_engine_version": [1, 20, 80]
+            },
+            "modules": [
+                {
+                    "type": "data",
+                    "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a12",
+                    "version": "1.0.0"
+                }
+            ],
+            "dependencies": [
+                { "module_name": "@minecraft/server", "version": "1.11.0" }
+            ]
+        })
+    }
+
+    fn messages(manifest: &Value, pack_type: StrixConfigPackType) -> Vec<String> {
+        validate_manifest(manifest, &pack_type)
+            .into_iter()
+            .map(|v| v.message)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_manifest() {
+        assert!(messages(&behaviour_manifest(), StrixConfigPackType::Behaviour).is_empty());
+    }
+
+    #[test]
+    fn reads_header_and_pack_type() {
+        let manifest =
+            parse_manifest("// comment\n{ \"header\": { \"name\": \" Foo \" } }").unwrap();
+        assert_eq!(header_text(&manifest, "name").as_deref(), Some("Foo"));
+
+        let manifest = behaviour_manifest();
+        assert_eq!(header_text(&manifest, "description"), None);
+        assert_eq!(
+            pack_type_of(&manifest),
+            Some(StrixConfigPackType::Behaviour)
+        );
+        assert_eq!(pack_type_of(&json!({ "modules": [] })), None);
+        assert!(parse_manifest("{").is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_format_version() {
+        let mut manifest = behaviour_manifest();
+        manifest["format_version"] = json!(3);
+
+        let messages = messages(&manifest, StrixConfigPackType::Behaviour);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("Unsupported format_version 3"));
+
+        assert_eq!(
+            validate_manifest(&json!([]), &StrixConfigPackType::Behaviour)[0].message,
+            "A manifest has to be a JSON object"
+        );
+    }
+
+    #[test]
+    fn reports_invalid_modules() {
+        let mut manifest = behaviour_manifest();
+        manifest["modules"] = json!([
+            {
+                "type": "resources",
+                "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11",
+                "version": [1, 0, 0]
+            },
+            {
+                "type": "script",
+                "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11",
+                "version": [1, 0, 0]
+            }
+        ]);
+
+        let messages = messages(&manifest, StrixConfigPackType::Behaviour);
+        for expected in [
+            "Module uuid has to differ from the header uuid",
+            "Module type Resources isn't allowed in a Behaviour pack",
+            "Script modules require an \"entry\" file",
+            "Script modules require \"language\": \"javascript\"",
+            "is used more than once",
+        ] {
+            assert!(
+                messages.iter().any(|v| v.contains(expected)),
+                "{expected:?} not in {messages:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn reports_invalid_header_and_dependencies() {
+        let mut manifest = behaviour_manifest();
+        manifest["header"]["name"] = json!(" ");
+        manifest["header"]["min_engine_version"] = json!("1.20.80");
+        manifest["dependencies"] = json!([
+            {
+                "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a13",
+                "module_name": "@minecraft/server",
+                "version": [1, 0, 0]
+            },
+            { "version": [1, 0, 0] }
+        ]);
+        manifest["unknown"] = json!(true);
+
+        let messages = messages(&manifest, StrixConfigPackType::Behaviour);
+        for expected in [
+            "Unknown field \"unknown\"",
+            "\"name\" must not be empty",
+            "\"min_engine_version\" has to be written as [major, minor, patch]",
+            "A dependency has either a \"uuid\" or a \"module_name\", not both",
+            "A dependency requires a \"uuid\" or a \"module_name\"",
+        ] {
+            assert!(
+                messages.iter().any(|v| v.contains(expected)),
+                "{expected:?} not in {messages:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn requires_world_template_fields() {
+        let manifest = json!({
+            "format_version": 2,
+            "header": {
+                "name": "World",
+                "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a11",
+                "version": [1, 0, 0]
+            },
+            "modules": [
+                {
+                    "type": "world_template",
+                    "uuid": "7a0e9c3b-1f0e-4bd5-9fd1-2d8f5b2f1a12",
+                    "version": [1, 0, 0]
+                }
+            ]
+        });
+
+        assert_eq!(
+            messages(&manifest, StrixConfigPackType::WorldTemplate),
+            [
+                "Missing required field \"base_game_version\" for World Templates",
+                "Missing required field \"lock_template_options\" for World Templates",
+            ]
+        );
+    }
+}
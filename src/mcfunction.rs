@@ -0,0 +1,1050 @@
+use crate::config::StrixConfigPackType;
+use crate::diagnostic::{Diagnostic, Severity, TextRange};
+use crate::json::JsonPath;
+use crate::lint::{LintContext, LintFile, LintRule};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// The bundled grammar, every overload is written like `<player: target> [amount: int]`
+#[derive(Deserialize)]
+struct GrammarSource {
+    aliases: HashMap<String, String>,
+    commands: HashMap<String, Vec<String>>,
+    execute: HashMap<String, Vec<String>>,
+}
+
+struct Grammar {
+    aliases: HashMap<String, String>,
+    commands: HashMap<String, Vec<Vec<Param>>>,
+    execute: HashMap<String, Vec<Vec<Param>>>,
+}
+
+#[derive(Debug, Clone)]
+struct Param {
+    name: String,
+    kind: Kind,
+    optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Literal(Vec<String>),
+    Target,
+    Int,
+    Float,
+    Bool,
+    Position,
+    Coordinate,
+    Id,
+    Word,
+    String,
+    Message,
+    Json,
+    Function,
+    BlockStates,
+    Time,
+    Range,
+    Command,
+}
+
+impl Kind {
+    fn parse(text: &str) -> Self {
+        match text {
+            "target" => Kind::Target,
+            "int" => Kind::Int,
+            "float" => Kind::Float,
+            "bool" => Kind::Bool,
+            "pos" => Kind::Position,
+            "coord" => Kind::Coordinate,
+            "id" => Kind::Id,
+            "word" => Kind::Word,
+            "string" => Kind::String,
+            "message" => Kind::Message,
+            "json" => Kind::Json,
+            "function" => Kind::Function,
+            "states" => Kind::BlockStates,
+            "time" => Kind::Time,
+            "range" => Kind::Range,
+            "command" => Kind::Command,
+            literal => Kind::Literal(literal.split('|').map(str::to_string).collect()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Kind::Literal(v) => v
+                .iter()
+                .map(|v| format!("{v:?}"))
+                .collect::<Vec<_>>()
+                .join(" or "),
+            Kind::Target => "a selector or player name".to_string(),
+            Kind::Int => "an integer".to_string(),
+            Kind::Float => "a number".to_string(),
+            Kind::Bool => "true or false".to_string(),
+            Kind::Position => "a position".to_string(),
+            Kind::Coordinate => "a coordinate".to_string(),
+            Kind::Id => "an identifier".to_string(),
+            Kind::Word | Kind::String => "a name".to_string(),
+            Kind::Message => "a message".to_string(),
+            Kind::Json => "JSON".to_string(),
+            Kind::Function => "a function path".to_string(),
+            Kind::BlockStates => "block states".to_string(),
+            Kind::Time => "a duration".to_string(),
+            Kind::Range => "a range".to_string(),
+            Kind::Command => "a command".to_string(),
+        }
+    }
+}
+
+fn parse_overload(text: &str) -> Vec<Param> {
+    let mut params = vec![];
+    let mut rest = text.trim();
+
+    while !rest.is_empty() {
+        let (param, tail) = match rest.chars().next() {
+            Some(open @ ('<' | '[')) => {
+                let close = if open == '<' { '>' } else { ']' };
+                let end = rest.find(close).unwrap_or(rest.len() - 1);
+
+                let (name, kind) = rest[1..end].split_once(": ").unwrap_or((&rest[1..end], ""));
+                let param = Param {
+                    name: name.to_string(),
+                    kind: Kind::parse(kind),
+                    optional: open == '[',
+                };
+                (param, &rest[end + 1..])
+            }
+            _ => {
+                let end = rest.find(' ').unwrap_or(rest.len());
+                let param = Param {
+                    name: rest[..end].to_string(),
+                    kind: Kind::parse(&rest[..end]),
+                    optional: false,
+                };
+                (param, &rest[end..])
+            }
+        };
+
+        params.push(param);
+        rest = tail.trim_start();
+    }
+
+    params
+}
+
+fn grammar() -> &'static Grammar {
+    static GRAMMAR: OnceLock<Grammar> = OnceLock::new();
+
+    GRAMMAR.get_or_init(|| {
+        let source: GrammarSource = serde_json::from_str(include_str!("commands.json"))
+            .expect("the bundled command grammar is valid");
+
+        let parse = |overloads: HashMap<String, Vec<String>>| {
+            overloads
+                .into_iter()
+                .map(|(name, v)| (name, v.iter().map(|v| parse_overload(v)).collect()))
+                .collect()
+        };
+
+        Grammar {
+            aliases: source.aliases,
+            commands: parse(source.commands),
+            execute: parse(source.execute),
+        }
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandErrorKind {
+    UnknownCommand,
+    Syntax,
+    Selector,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandError {
+    /// Byte range inside the line
+    pub span: Range<usize>,
+    pub kind: CommandErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+}
+
+impl Token<'_> {
+    fn span(&self) -> Range<usize> {
+        self.start..self.start + self.text.len()
+    }
+}
+
+/// Splits a command at whitespace, keeping quoted strings, selectors and JSON together
+fn tokenize(line: &str) -> Result<Vec<Token<'_>>, CommandError> {
+    let bytes = line.as_bytes();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    loop {
+        while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+            index += 1;
+        }
+        if index >= bytes.len() {
+            break;
+        }
+
+        let start = index;
+        let mut depth = 0usize;
+        let mut quoted = false;
+
+        while index < bytes.len() {
+            match (quoted, bytes[index]) {
+                (true, b'\\') => index += 1,
+                (true, b'"') => quoted = false,
+                (false, b'"') => quoted = true,
+                (false, b'[' | b'{') => depth += 1,
+                (false, b']' | b'}') => depth = depth.saturating_sub(1),
+                (false, char) if char.is_ascii_whitespace() && depth == 0 => break,
+                _ => {}
+            }
+            index += 1;
+        }
+
+        if quoted {
+            return Err(CommandError {
+                span: start..line.len(),
+                kind: CommandErrorKind::Syntax,
+                message: "Unterminated string".to_string(),
+            });
+        }
+
+        index = index.min(bytes.len());
+        tokens.push(Token {
+            text: &line[start..index],
+            start,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// The result of checking a single command line
+#[derive(Debug, Default)]
+pub struct CheckedCommand {
+    pub errors: Vec<CommandError>,
+    /// Functions called with `function` or `schedule`, with their span
+    pub calls: Vec<(String, Range<usize>)>,
+    /// Where free text like a `say` message starts, formatting keeps it as is
+    raw_from: Option<usize>,
+}
+
+/// Counts the coordinates in a token like `~`, `^1.5` or `~~-1~`
+fn coordinates(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    let mut count = 0;
+
+    while index < bytes.len() {
+        let relative = matches!(bytes[index], b'~' | b'^');
+        if relative {
+            index += 1;
+        }
+
+        let start = index;
+        if index < bytes.len() && matches!(bytes[index], b'-' | b'+') {
+            index += 1;
+        }
+        while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'.') {
+            index += 1;
+        }
+
+        let number = &text[start..index];
+        if !relative && !number.bytes().any(|v| v.is_ascii_digit()) {
+            return None;
+        }
+        if !number.is_empty()
+            && number
+                .trim_start_matches(['-', '+'])
+                .parse::<f64>()
+                .is_err()
+        {
+            return None;
+        }
+
+        count += 1;
+    }
+
+    Some(count)
+}
+
+fn is_int(text: &str) -> bool {
+    text.parse::<i64>().is_ok()
+}
+
+fn is_float(text: &str) -> bool {
+    text.parse::<f64>().is_ok() && !text.contains(['e', 'E', 'n', 'N', 'i', 'I'])
+}
+
+fn is_id(text: &str) -> bool {
+    !text.is_empty()
+        && !is_float(text)
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '.' | '-'))
+}
+
+fn is_range(text: &str) -> bool {
+    let text = text.strip_prefix('!').unwrap_or(text);
+
+    match text.split_once("..") {
+        Some((min, max)) => {
+            (!min.is_empty() || !max.is_empty())
+                && (min.is_empty() || is_int(min))
+                && (max.is_empty() || is_int(max))
+        }
+        None => is_int(text),
+    }
+}
+
+/// Splits selector arguments at top level commas, returning each part with its offset
+fn split_arguments(text: &str) -> Vec<(usize, &str)> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut quoted = false;
+    let mut start = 0;
+
+    for (i, char) in text.char_indices() {
+        match (quoted, char) {
+            (_, '"') => quoted = !quoted,
+            (false, '[' | '{') => depth += 1,
+            (false, ']' | '}') => depth = depth.saturating_sub(1),
+            (false, ',') if depth == 0 => {
+                parts.push((start, &text[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    parts.push((start, &text[start..]));
+    parts
+}
+
+const SELECTORS: &[&str] = &["@p", "@r", "@a", "@e", "@s", "@initiator"];
+
+const GAME_MODES: &[&str] = &[
+    "survival",
+    "creative",
+    "adventure",
+    "spectator",
+    "default",
+    "s",
+    "c",
+    "a",
+    "d",
+    "0",
+    "1",
+    "2",
+    "5",
+];
+
+fn check_selector(token: &Token) -> Vec<CommandError> {
+    let mut errors = vec![];
+    let error = |span: Range<usize>, message: String| CommandError {
+        span,
+        kind: CommandErrorKind::Selector,
+        message,
+    };
+
+    let (head, arguments) = match token.text.find('[') {
+        Some(i) => (&token.text[..i], Some(&token.text[i..])),
+        None => (token.text, None),
+    };
+
+    if !SELECTORS.contains(&head) {
+        errors.push(error(
+            token.start..token.start + head.len(),
+            format!("Unknown selector {head:?}"),
+        ));
+    }
+
+    let Some(arguments) = arguments else {
+        return errors;
+    };
+
+    let Some(inner) = arguments
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+    else {
+        errors.push(error(
+            token.span(),
+            "Unterminated selector arguments".to_string(),
+        ));
+        return errors;
+    };
+
+    let inner_start = token.start + head.len() + 1;
+
+    for (offset, part) in split_arguments(inner) {
+        if part.trim().is_empty() {
+            continue;
+        }
+
+        let span = inner_start + offset..inner_start + offset + part.len();
+
+        let Some((key, value)) = part.split_once('=') else {
+            errors.push(error(
+                span,
+                format!("Expected \"key=value\", found {part:?}"),
+            ));
+            continue;
+        };
+
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        let negated = value.strip_prefix('!').unwrap_or(value);
+
+        let valid = match key.as_str() {
+            "x" | "y" | "z" => coordinates(value) == Some(1),
+            "r" | "rm" | "dx" | "dy" | "dz" | "rx" | "rxm" | "ry" | "rym" => is_float(value),
+            "c" | "l" | "lm" => is_int(value),
+            "type" | "family" | "tag" | "name" => true,
+            "m" => GAME_MODES.contains(&negated.to_ascii_lowercase().as_str()),
+            "scores" | "has_property" | "haspermission" => {
+                value.starts_with('{') && value.ends_with('}')
+            }
+            "hasitem" => {
+                (value.starts_with('{') && value.ends_with('}'))
+                    || (value.starts_with('[') && value.ends_with(']'))
+            }
+            _ => {
+                errors.push(error(span, format!("Unknown selector argument {key:?}")));
+                continue;
+            }
+        };
+
+        if !valid {
+            errors.push(error(
+                span,
+                format!("Invalid value {value:?} for selector argument {key:?}"),
+            ));
+        }
+    }
+
+    errors
+}
+
+/// Matches the start of `tokens` against a kind, returning how many tokens it takes
+fn match_kind(
+    kind: &Kind,
+    line: &str,
+    tokens: &[Token],
+    out: &mut CheckedCommand,
+) -> Option<usize> {
+    let token = tokens.first()?;
+    let text = token.text;
+
+    let matched = match kind {
+        Kind::Literal(values) => values.iter().any(|v| v.eq_ignore_ascii_case(text)),
+        Kind::Target => {
+            if text.starts_with('@') {
+                out.errors.extend(check_selector(token));
+                true
+            } else {
+                !is_float(text) && coordinates(text).is_none() && !text.starts_with(['{', '['])
+            }
+        }
+        Kind::Int => is_int(text),
+        Kind::Float => is_float(text),
+        Kind::Bool => matches!(text, "true" | "false"),
+        Kind::Coordinate => coordinates(text) == Some(1),
+        Kind::Position => {
+            let mut count = 0;
+
+            for (i, token) in tokens.iter().enumerate() {
+                count += coordinates(token.text)?;
+
+                if count == 3 {
+                    return Some(i + 1);
+                }
+                if count > 3 {
+                    return None;
+                }
+            }
+
+            return None;
+        }
+        Kind::Id => is_id(text),
+        Kind::Word | Kind::String => !text.starts_with('{'),
+        Kind::Function => {
+            let name = text.trim_matches('"');
+            let valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '/' | '.' | '-' | ' '));
+
+            if valid {
+                out.calls.push((name.to_string(), token.span()));
+            }
+            valid
+        }
+        Kind::BlockStates => (text.starts_with('[') && text.ends_with(']')) || is_int(text),
+        Kind::Time => {
+            let number = text
+                .strip_suffix(['d', 's', 't', 'D', 'S', 'T'])
+                .unwrap_or(text);
+            is_float(number)
+        }
+        Kind::Range => is_range(text),
+        Kind::Message => {
+            out.raw_from = Some(token.start);
+            return Some(tokens.len());
+        }
+        Kind::Json => {
+            out.raw_from = Some(token.start);
+
+            let json = line[token.start..].trim_end();
+            if let Err(err) = serde_json::from_str::<Value>(json) {
+                out.errors.push(CommandError {
+                    span: token.start..line.trim_end().len(),
+                    kind: CommandErrorKind::Syntax,
+                    message: format!("Invalid JSON: {err}"),
+                });
+            }
+            return Some(tokens.len());
+        }
+        Kind::Command => {
+            check_tokens(line, tokens, out);
+            return Some(tokens.len());
+        }
+    };
+
+    matched.then_some(1)
+}
+
+/// Where an overload stopped matching, and why
+struct Mismatch {
+    token: usize,
+    message: String,
+}
+
+fn match_params(
+    params: &[Param],
+    line: &str,
+    tokens: &[Token],
+    prefix: bool,
+) -> Result<(usize, CheckedCommand), Mismatch> {
+    let mut out = CheckedCommand::default();
+    let mut index = 0;
+
+    for param in params {
+        let remaining = &tokens[index..];
+
+        match match_kind(&param.kind, line, remaining, &mut out) {
+            Some(count) => index += count,
+            None if param.optional => {}
+            None if remaining.is_empty() => {
+                return Err(Mismatch {
+                    token: index,
+                    message: format!("Missing {} ({})", param.name, param.kind.describe()),
+                })
+            }
+            None => {
+                return Err(Mismatch {
+                    token: index,
+                    message: format!(
+                        "Expected {} for {:?}, found {:?}",
+                        param.kind.describe(),
+                        param.name,
+                        remaining[0].text
+                    ),
+                })
+            }
+        }
+    }
+
+    if !prefix && index < tokens.len() {
+        return Err(Mismatch {
+            token: index,
+            message: format!("Unexpected argument {:?}", tokens[index].text),
+        });
+    }
+
+    Ok((index, out))
+}
+
+/// Tries every overload, reporting the one that got the furthest if none match
+fn match_overloads(
+    overloads: &[Vec<Param>],
+    line: &str,
+    tokens: &[Token],
+    prefix: bool,
+    out: &mut CheckedCommand,
+) -> Option<usize> {
+    let mut best: Option<Mismatch> = None;
+
+    for overload in overloads {
+        match match_params(overload, line, tokens, prefix) {
+            Ok((count, checked)) => {
+                out.errors.extend(checked.errors);
+                out.calls.extend(checked.calls);
+                out.raw_from = checked.raw_from.or(out.raw_from);
+                return Some(count);
+            }
+            Err(mismatch) => {
+                if best.as_ref().is_none_or(|v| mismatch.token > v.token) {
+                    best = Some(mismatch);
+                }
+            }
+        }
+    }
+
+    if let Some(mismatch) = best {
+        let span = match tokens.get(mismatch.token) {
+            Some(token) => token.span(),
+            None => line.trim_end().len()..line.trim_end().len(),
+        };
+
+        out.errors.push(CommandError {
+            span,
+            kind: CommandErrorKind::Syntax,
+            message: mismatch.message,
+        });
+    }
+
+    None
+}
+
+fn check_execute(line: &str, tokens: &[Token], out: &mut CheckedCommand) {
+    let grammar = grammar();
+
+    let is_subcommand = |token: &Token| {
+        grammar
+            .execute
+            .contains_key(&token.text.to_ascii_lowercase())
+    };
+
+    // The old `execute <origin> <position> <command>` syntax
+    if tokens.first().is_some_and(|v| !is_subcommand(v)) {
+        match_overloads(&grammar.commands["execute"], line, tokens, false, out);
+        return;
+    }
+
+    let mut rest = tokens;
+    let mut last = "";
+
+    loop {
+        let Some(token) = rest.first() else {
+            if !matches!(last, "if" | "unless") {
+                let end = line.trim_end().len();
+                out.errors.push(CommandError {
+                    span: end..end,
+                    kind: CommandErrorKind::Syntax,
+                    message: "Expected \"run\" or a condition at the end of \"execute\""
+                        .to_string(),
+                });
+            }
+            return;
+        };
+
+        let name = token.text.to_ascii_lowercase();
+        let Some(overloads) = grammar.execute.get(&name) else {
+            out.errors.push(CommandError {
+                span: token.span(),
+                kind: CommandErrorKind::Syntax,
+                message: format!("Unknown execute subcommand {:?}", token.text),
+            });
+            return;
+        };
+
+        let Some(count) = match_overloads(overloads, line, &rest[1..], true, out) else {
+            return;
+        };
+
+        if name == "run" {
+            return;
+        }
+
+        rest = &rest[1 + count..];
+        last = grammar
+            .execute
+            .get_key_value(&name)
+            .map_or("", |(k, _)| k.as_str());
+    }
+}
+
+fn check_tokens(line: &str, tokens: &[Token], out: &mut CheckedCommand) {
+    let Some((first, rest)) = tokens.split_first() else {
+        return;
+    };
+
+    let grammar = grammar();
+    let name = first.text.trim_start_matches('/').to_ascii_lowercase();
+    let name = grammar.aliases.get(&name).cloned().unwrap_or(name);
+
+    if name == "execute" {
+        check_execute(line, rest, out);
+        return;
+    }
+
+    let Some(overloads) = grammar.commands.get(&name) else {
+        out.errors.push(CommandError {
+            span: first.span(),
+            kind: CommandErrorKind::UnknownCommand,
+            message: format!("Unknown command {:?}", first.text),
+        });
+        return;
+    };
+
+    match_overloads(overloads, line, rest, false, out);
+}
+
+/// Checks a single command against the bundled grammar
+pub fn check_command(line: &str) -> CheckedCommand {
+    let mut out = CheckedCommand::default();
+
+    match tokenize(line) {
+        Ok(tokens) => check_tokens(line, &tokens, &mut out),
+        Err(err) => out.errors.push(err),
+    }
+
+    out
+}
+
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+/// Every command line of a file with the byte offset it starts at
+pub fn commands(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\n', '\r'])))
+        })
+        .filter(|(_, line)| !line.trim().is_empty() && !is_comment(line))
+}
+
+fn format_command(line: &str) -> String {
+    let line = line.trim();
+
+    let Ok(tokens) = tokenize(line) else {
+        return line.to_string();
+    };
+
+    let raw_from = check_command(line).raw_from.unwrap_or(line.len());
+
+    let mut out = tokens
+        .iter()
+        .take_while(|v| v.start < raw_from)
+        .map(|v| v.text)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if raw_from < line.len() {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(line[raw_from..].trim_end());
+    }
+
+    out
+}
+
+/// Normalizes whitespace between arguments and blank lines, keeping comments and messages as is
+pub fn format(text: &str) -> String {
+    let mut out = String::new();
+    let mut previous_blank = true;
+
+    for line in text.trim_start_matches('\u{feff}').lines() {
+        let blank = line.trim().is_empty();
+        if blank && previous_blank {
+            continue;
+        }
+
+        if is_comment(line) {
+            out.push_str(line.trim());
+        } else if !blank {
+            out.push_str(&format_command(line));
+        }
+
+        out.push('\n');
+        previous_blank = blank;
+    }
+
+    while out.ends_with("\n\n") {
+        out.pop();
+    }
+
+    out
+}
+
+/// Strips comments and blank lines
+pub fn minify(text: &str) -> String {
+    let mut out = String::new();
+
+    for (_, line) in commands(text.trim_start_matches('\u{feff}')) {
+        out.push_str(&format_command(line));
+        out.push('\n');
+    }
+
+    out
+}
+
+fn function_files(ctx: &LintContext) -> impl Iterator<Item = &LintFile> {
+    ctx.files_of(StrixConfigPackType::Behaviour)
+        .filter(|v| v.is_in("functions") && v.extension() == Some("mcfunction"))
+}
+
+/// Every command of every function file, checked
+fn checked(ctx: &LintContext) -> Vec<(&LintFile, usize, CheckedCommand)> {
+    function_files(ctx)
+        .filter_map(|file| Some((file, file.text.as_deref()?)))
+        .flat_map(|(file, text)| {
+            commands(text).map(move |(offset, line)| (file, offset, check_command(line)))
+        })
+        .collect()
+}
+
+fn range(file: &LintFile, offset: usize, span: &Range<usize>) -> Option<TextRange> {
+    let text = file.text.as_deref()?;
+    Some(TextRange::from_offsets(
+        text,
+        offset + span.start,
+        offset + span.end,
+    ))
+}
+
+fn command_errors(
+    rule: &dyn LintRule,
+    ctx: &LintContext,
+    kinds: &[CommandErrorKind],
+) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for (file, offset, command) in checked(ctx) {
+        for error in command.errors.iter().filter(|v| kinds.contains(&v.kind)) {
+            diagnostics.push(Diagnostic::new(
+                rule.code(),
+                &file.path,
+                range(file, offset, &error.span),
+                error.message.clone(),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+pub struct UnknownCommand;
+
+impl LintRule for UnknownCommand {
+    fn code(&self) -> &'static str {
+        "unknown-command"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        command_errors(self, ctx, &[CommandErrorKind::UnknownCommand])
+    }
+}
+
+pub struct CommandSyntax;
+
+impl LintRule for CommandSyntax {
+    fn code(&self) -> &'static str {
+        "command-syntax"
+    }
+
+    // The bundled grammar can lag behind the game, so this doesn't fail the lint by default
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        command_errors(self, ctx, &[CommandErrorKind::Syntax])
+    }
+}
+
+pub struct InvalidSelector;
+
+impl LintRule for InvalidSelector {
+    fn code(&self) -> &'static str {
+        "invalid-selector"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        command_errors(self, ctx, &[CommandErrorKind::Selector])
+    }
+}
+
+pub struct UnresolvedFunction;
+
+impl LintRule for UnresolvedFunction {
+    fn code(&self) -> &'static str {
+        "unresolved-function"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let functions: HashSet<&str> = function_files(ctx)
+            .filter_map(|v| {
+                v.relative
+                    .strip_prefix("functions/")?
+                    .strip_suffix(".mcfunction")
+            })
+            .collect();
+
+        let mut diagnostics = vec![];
+
+        for (file, offset, command) in checked(ctx) {
+            for (name, span) in &command.calls {
+                if !functions.contains(name.trim_start_matches('/')) {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        range(file, offset, span),
+                        format!("Function {name:?} doesn't exist in any Behaviour project"),
+                    ));
+                }
+            }
+        }
+
+        for file in ctx
+            .files_of(StrixConfigPackType::Behaviour)
+            .filter(|v| v.relative == "functions/tick.json")
+        {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            let values = json.get("values").and_then(Value::as_array);
+
+            for (i, value) in values.into_iter().flatten().enumerate() {
+                let path = JsonPath::default().key("values").index(i);
+
+                let message = match value.as_str() {
+                    Some(name) if functions.contains(name.trim_start_matches('/')) => continue,
+                    Some(name) => {
+                        format!("Function {name:?} doesn't exist in any Behaviour project")
+                    }
+                    None => "Expected a function path".to_string(),
+                };
+
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&path),
+                    message,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(line: &str) -> Vec<CommandErrorKind> {
+        check_command(line)
+            .errors
+            .into_iter()
+            .map(|v| v.kind)
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_commands() {
+        for line in [
+            "tp @s ~ ~1 ~",
+            "/give @p minecraft:diamond 5",
+            "execute as @a[tag=x,r=5] at @s run say hi  there",
+            "execute @a ~ ~ ~ detect ~ ~-1 ~ stone 0 say yo",
+            "summon minecraft:pig ~~1~",
+            "tellraw @a {\"rawtext\":[{\"text\":\"hi there\"}]}",
+            "scoreboard players add @s points 1",
+        ] {
+            assert_eq!(check_command(line).errors.len(), 0, "{line:?}");
+        }
+    }
+
+    #[test]
+    fn collects_function_calls() {
+        let checked = check_command("execute if entity @s[type=pig] run function foo/bar");
+
+        assert!(checked.errors.is_empty());
+        assert_eq!(checked.calls, [("foo/bar".to_string(), 44..51)]);
+    }
+
+    #[test]
+    fn reports_invalid_commands() {
+        assert_eq!(kinds("foo bar"), [CommandErrorKind::UnknownCommand]);
+        assert_eq!(kinds("give @p"), [CommandErrorKind::Syntax]);
+        assert_eq!(kinds("execute as @s"), [CommandErrorKind::Syntax]);
+        assert_eq!(
+            kinds("execute sideways run say hi"),
+            [CommandErrorKind::Syntax]
+        );
+        assert_eq!(
+            kinds("effect @e[bogus=1] speed 10 1"),
+            [CommandErrorKind::Selector]
+        );
+        assert_eq!(kinds("kill @x"), [CommandErrorKind::Selector]);
+
+        let err = &check_command("tellraw @a {\"rawtext\":\"open").errors[0];
+        assert_eq!(err.message, "Unterminated string");
+        assert_eq!(err.span, 11..27);
+    }
+
+    #[test]
+    fn formats_commands() {
+        let text = "\u{feff}  # header  \n\n\n tp  @s   ~ ~1 ~ \nsay  hi   there\ngive @p\t minecraft:apple\n\n";
+
+        assert_eq!(
+            format(text),
+            "# header\n\ntp @s ~ ~1 ~\nsay hi   there\ngive @p minecraft:apple\n"
+        );
+        assert_eq!(
+            minify(text),
+            "tp @s ~ ~1 ~\nsay hi   there\ngive @p minecraft:apple\n"
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let text = "# a\nexecute  as @a  run  say  hello   world\n\n\nfoo   bar\ntellraw @a {\"rawtext\": [ {\"text\": \"x\"} ]}\n";
+        let formatted = format(text);
+
+        assert_eq!(format(&formatted), formatted);
+        assert_eq!(minify(&formatted), minify(text));
+    }
+
+    #[test]
+    fn lists_command_lines() {
+        let text = "# comment\r\nsay a\r\n\r\n  say b\n";
+
+        assert_eq!(
+            commands(text).collect::<Vec<_>>(),
+            [(11, "say a"), (20, "  say b")]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::args::CliMessageFormat;
+use crate::diagnostic::{Diagnostic, Severity};
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static MESSAGE_FORMAT: OnceLock<CliMessageFormat> = OnceLock::new();
+
+static ERRORS: AtomicUsize = AtomicUsize::new(0);
+static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+
+/// A single line of `--message-format json` output, tagged by its `reason`
+#[derive(Serialize, Debug)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub enum Message<'a> {
+    Diagnostic(&'a Diagnostic),
+    Artifact {
+        kind: ArtifactKind,
+        project: Option<&'a str>,
+        path: &'a Path,
+    },
+    Summary {
+        command: &'a str,
+        success: bool,
+        errors: usize,
+        warnings: usize,
+        duration_ms: u128,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArtifactKind {
+    /// A processed project inside the build folder
+    Project,
+    /// A project copied into the `development_*_packs` folder
+    DevFolder,
+    /// The packaged `.mcaddon`
+    Package,
+    /// The `build-info.json` describing the other artifacts
+    BuildInfo,
+}
+
+pub fn set_message_format(format: CliMessageFormat) {
+    let _ = MESSAGE_FORMAT.set(format);
+}
+
+pub fn is_json() -> bool {
+    MESSAGE_FORMAT.get() == Some(&CliMessageFormat::Json)
+}
+
+/// Prints a message to stdout when using `--message-format json`, does nothing otherwise
+pub fn emit(message: &Message) {
+    if !is_json() {
+        return;
+    }
+
+    match serde_json::to_string(message) {
+        Ok(line) => {
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{line}");
+        }
+        Err(err) => eprintln!(
+            "An unexpected Error occurred while trying to serialize {message:?}, Err: {err}"
+        ),
+    }
+}
+
+/// Counts a reported diagnostic towards the summary
+pub fn count(severity: Severity) {
+    match severity {
+        Severity::Error => ERRORS.fetch_add(1, Ordering::Relaxed),
+        Severity::Warning => WARNINGS.fetch_add(1, Ordering::Relaxed),
+        Severity::Info | Severity::Off => 0,
+    };
+}
+
+pub fn emit_artifact(kind: ArtifactKind, project: Option<&str>, path: &Path) {
+    emit(&Message::Artifact {
+        kind,
+        project,
+        path,
+    });
+}
+
+pub fn emit_summary(command: &str, success: bool, duration: Duration) {
+    emit(&Message::Summary {
+        command,
+        success,
+        errors: ERRORS.load(Ordering::Relaxed),
+        warnings: WARNINGS.load(Ordering::Relaxed),
+        duration_ms: duration.as_millis(),
+    });
+}
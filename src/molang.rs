@@ -0,0 +1,1293 @@
+use crate::diagnostic::{Diagnostic, Severity, TextRange};
+use crate::json::{self, JsonPath};
+use crate::lint::{LintContext, LintFile, LintRule};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Known names per namespace, `math` functions come with their minimum and maximum argument count
+#[derive(Deserialize)]
+struct NameTable {
+    math: HashMap<String, [usize; 2]>,
+    query: HashSet<String>,
+    variable: HashSet<String>,
+    context: HashSet<String>,
+}
+
+fn name_table() -> &'static NameTable {
+    static TABLE: OnceLock<NameTable> = OnceLock::new();
+
+    TABLE.get_or_init(|| {
+        serde_json::from_str(include_str!("molang.json"))
+            .expect("the bundled Molang table is valid")
+    })
+}
+
+const OPERATORS: &[&str] = &[
+    "&&", "||", "==", "!=", "<=", ">=", "??", "->", "<", ">", "+", "-", "*", "/", "!", "?", ":",
+    "=", ";", ",", "(", ")", "[", "]", "{", "}",
+];
+
+/// Long namespaces and the alias minify replaces them with
+const ALIASES: &[(&str, &str)] = &[
+    ("query", "q"),
+    ("variable", "v"),
+    ("temp", "t"),
+    ("context", "c"),
+];
+
+#[derive(Debug, Clone)]
+pub struct MolangError {
+    /// Byte range inside the expression
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    String(String),
+    Name(String),
+    Op(&'static str),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Lexed {
+    token: Token,
+    span: Range<usize>,
+}
+
+fn is_name_start(char: u8) -> bool {
+    char.is_ascii_alphabetic() || char == b'_'
+}
+
+fn is_name_part(char: u8) -> bool {
+    char.is_ascii_alphanumeric() || char == b'_'
+}
+
+fn tokenize(text: &str) -> Result<Vec<Lexed>, MolangError> {
+    let bytes = text.as_bytes();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let start = index;
+        let char = bytes[index];
+
+        let token = if char.is_ascii_whitespace() {
+            index += 1;
+            continue;
+        } else if char.is_ascii_digit()
+            || (char == b'.' && bytes.get(index + 1).is_some_and(u8::is_ascii_digit))
+        {
+            while index < bytes.len() && (bytes[index].is_ascii_digit() || bytes[index] == b'.') {
+                index += 1;
+            }
+            Token::Number(text[start..index].to_string())
+        } else if is_name_start(char) {
+            // Dotted names like `query.is_baby` or `v.struct.field` are a single token
+            loop {
+                while index < bytes.len() && is_name_part(bytes[index]) {
+                    index += 1;
+                }
+
+                if bytes.get(index) == Some(&b'.')
+                    && bytes.get(index + 1).is_some_and(|v| is_name_start(*v))
+                {
+                    index += 1;
+                } else {
+                    break;
+                }
+            }
+            Token::Name(text[start..index].to_string())
+        } else if char == b'\'' {
+            let Some(end) = text[index + 1..].find('\'') else {
+                return Err(MolangError {
+                    span: start..bytes.len(),
+                    message: "Unterminated string".to_string(),
+                });
+            };
+            index += end + 2;
+            Token::String(text[start + 1..index - 1].to_string())
+        } else if let Some(op) = OPERATORS.iter().find(|v| text[index..].starts_with(**v)) {
+            index += op.len();
+            Token::Op(op)
+        } else {
+            let char = text[index..].chars().next().unwrap_or_default();
+            return Err(MolangError {
+                span: start..start + char.len_utf8(),
+                message: format!("Unexpected character {char:?}"),
+            });
+        };
+
+        tokens.push(Lexed {
+            token,
+            span: start..index,
+        });
+    }
+
+    tokens.push(Lexed {
+        token: Token::Eof,
+        span: text.len()..text.len(),
+    });
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number(String),
+    String(String),
+    Name {
+        name: String,
+        span: Range<usize>,
+    },
+    Call {
+        name: String,
+        span: Range<usize>,
+        args: Vec<Expr>,
+    },
+    Index {
+        target: Box<Expr>,
+        index: Box<Expr>,
+    },
+    Arrow {
+        target: Box<Expr>,
+        expr: Box<Expr>,
+    },
+    Unary {
+        op: &'static str,
+        expr: Box<Expr>,
+    },
+    Binary {
+        op: &'static str,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    Conditional {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Option<Box<Expr>>,
+    },
+    Paren(Box<Expr>),
+    Block(Vec<Expr>),
+    Return(Box<Expr>),
+    Break,
+    Continue,
+}
+
+/// A parsed expression, complex expressions are the ones using `;`
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub statements: Vec<Expr>,
+    pub complex: bool,
+}
+
+const PREFIX_POWER: u8 = 19;
+const POSTFIX_POWER: u8 = 21;
+const CONDITIONAL_POWER: (u8, u8) = (6, 5);
+
+fn binding_power(op: &str) -> Option<(u8, u8)> {
+    Some(match op {
+        "=" => (2, 1),
+        "??" => (4, 3),
+        "||" => (7, 8),
+        "&&" => (9, 10),
+        "==" | "!=" => (11, 12),
+        "<" | "<=" | ">" | ">=" => (13, 14),
+        "+" | "-" => (15, 16),
+        "*" | "/" => (17, 18),
+        _ => return None,
+    })
+}
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Lexed {
+        &self.tokens[self.index.min(self.tokens.len() - 1)]
+    }
+
+    fn next(&mut self) -> Lexed {
+        let token = self.peek().clone();
+        self.index += 1;
+        token
+    }
+
+    fn is_op(&self, op: &str) -> bool {
+        matches!(self.peek().token, Token::Op(v) if v == op)
+    }
+
+    fn error(&self, message: String) -> MolangError {
+        MolangError {
+            span: self.peek().span.clone(),
+            message,
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), MolangError> {
+        if !self.is_op(op) {
+            return Err(self.error(format!("Expected {op:?}")));
+        }
+
+        self.index += 1;
+        Ok(())
+    }
+
+    fn statements(&mut self) -> Result<Program, MolangError> {
+        let mut statements = vec![];
+        let mut complex = false;
+
+        while self.peek().token != Token::Eof && !self.is_op("}") {
+            statements.push(self.expression(0)?);
+
+            if !self.is_op(";") {
+                break;
+            }
+
+            self.index += 1;
+            complex = true;
+        }
+
+        Ok(Program {
+            statements,
+            complex,
+        })
+    }
+
+    fn expression(&mut self, min_power: u8) -> Result<Expr, MolangError> {
+        let mut lhs = self.prefix()?;
+
+        while let Token::Op(op) = self.peek().token {
+            match op {
+                "[" | "->" if POSTFIX_POWER >= min_power => {
+                    self.index += 1;
+
+                    lhs = if op == "[" {
+                        let index = self.expression(0)?;
+                        self.expect("]")?;
+                        Expr::Index {
+                            target: Box::new(lhs),
+                            index: Box::new(index),
+                        }
+                    } else {
+                        Expr::Arrow {
+                            target: Box::new(lhs),
+                            expr: Box::new(self.expression(POSTFIX_POWER)?),
+                        }
+                    };
+                }
+                "?" if CONDITIONAL_POWER.0 >= min_power => {
+                    self.index += 1;
+                    let then = self.expression(0)?;
+
+                    let otherwise = if self.is_op(":") {
+                        self.index += 1;
+                        Some(Box::new(self.expression(CONDITIONAL_POWER.1)?))
+                    } else {
+                        None
+                    };
+
+                    lhs = Expr::Conditional {
+                        condition: Box::new(lhs),
+                        then: Box::new(then),
+                        otherwise,
+                    };
+                }
+                op => {
+                    let Some((left, right)) = binding_power(op) else {
+                        break;
+                    };
+                    if left < min_power {
+                        break;
+                    }
+
+                    self.index += 1;
+                    lhs = Expr::Binary {
+                        op,
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(self.expression(right)?),
+                    };
+                }
+            }
+        }
+
+        Ok(lhs)
+    }
+
+    fn prefix(&mut self) -> Result<Expr, MolangError> {
+        let Lexed { token, span } = self.next();
+
+        Ok(match token {
+            Token::Number(v) => Expr::Number(v),
+            Token::String(v) => Expr::String(v),
+            Token::Name(name) => match name.to_ascii_lowercase().as_str() {
+                "return" => Expr::Return(Box::new(self.expression(0)?)),
+                "break" => Expr::Break,
+                "continue" => Expr::Continue,
+                _ if self.is_op("(") => {
+                    self.index += 1;
+                    let mut args = vec![];
+
+                    while !self.is_op(")") {
+                        args.push(self.expression(0)?);
+
+                        if !self.is_op(",") {
+                            break;
+                        }
+                        self.index += 1;
+                    }
+
+                    self.expect(")")?;
+                    Expr::Call { name, span, args }
+                }
+                _ => Expr::Name { name, span },
+            },
+            Token::Op("(") => {
+                let expr = self.expression(0)?;
+                self.expect(")")?;
+                Expr::Paren(Box::new(expr))
+            }
+            Token::Op("{") => {
+                let program = self.statements()?;
+                self.expect("}")?;
+                Expr::Block(program.statements)
+            }
+            Token::Op(op @ ("-" | "!")) => Expr::Unary {
+                op,
+                expr: Box::new(self.expression(PREFIX_POWER)?),
+            },
+            Token::Op(op) => {
+                return Err(MolangError {
+                    span,
+                    message: format!("Unexpected {op:?}"),
+                })
+            }
+            Token::Eof => {
+                return Err(MolangError {
+                    span,
+                    message: "Unexpected end of expression".to_string(),
+                })
+            }
+        })
+    }
+}
+
+pub fn parse(text: &str) -> Result<Program, MolangError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        index: 0,
+    };
+
+    let program = parser.statements()?;
+
+    if parser.peek().token != Token::Eof {
+        return Err(parser.error("Expected \";\" or the end of the expression".to_string()));
+    }
+
+    Ok(program)
+}
+
+/// Prints an expression with normalized spacing, or as short as possible when compacting
+pub fn format(program: &Program, compact: bool) -> String {
+    let mut out = String::new();
+
+    for (i, statement) in program.statements.iter().enumerate() {
+        if i > 0 && !compact {
+            out.push(' ');
+        }
+
+        write_expr(&mut out, statement, compact);
+
+        if program.complex {
+            out.push(';');
+        }
+    }
+
+    out
+}
+
+fn write_name(out: &mut String, name: &str, compact: bool) {
+    if compact {
+        if let Some((namespace, rest)) = name.split_once('.') {
+            if let Some((_, alias)) = ALIASES
+                .iter()
+                .find(|(v, _)| v.eq_ignore_ascii_case(namespace))
+            {
+                out.push_str(alias);
+                out.push('.');
+                out.push_str(rest);
+                return;
+            }
+        }
+    }
+
+    out.push_str(name);
+}
+
+fn write_expr(out: &mut String, expr: &Expr, compact: bool) {
+    let space = if compact { "" } else { " " };
+
+    match expr {
+        Expr::Number(v) => out.push_str(v),
+        Expr::String(v) => {
+            out.push('\'');
+            out.push_str(v);
+            out.push('\'');
+        }
+        Expr::Name { name, .. } => write_name(out, name, compact),
+        Expr::Call { name, args, .. } => {
+            write_name(out, name, compact);
+            out.push('(');
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                    out.push_str(space);
+                }
+                write_expr(out, arg, compact);
+            }
+            out.push(')');
+        }
+        Expr::Index { target, index } => {
+            write_expr(out, target, compact);
+            out.push('[');
+            write_expr(out, index, compact);
+            out.push(']');
+        }
+        Expr::Arrow { target, expr } => {
+            write_expr(out, target, compact);
+            out.push_str("->");
+            write_expr(out, expr, compact);
+        }
+        Expr::Unary { op, expr } => {
+            out.push_str(op);
+            write_expr(out, expr, compact);
+        }
+        Expr::Binary { op, lhs, rhs } => {
+            write_expr(out, lhs, compact);
+            out.push_str(space);
+            out.push_str(op);
+            out.push_str(space);
+            write_expr(out, rhs, compact);
+        }
+        Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => {
+            write_expr(out, condition, compact);
+            out.push_str(space);
+            out.push('?');
+            out.push_str(space);
+            write_expr(out, then, compact);
+            if let Some(otherwise) = otherwise {
+                out.push_str(space);
+                out.push(':');
+                out.push_str(space);
+                write_expr(out, otherwise, compact);
+            }
+        }
+        Expr::Paren(expr) => {
+            out.push('(');
+            write_expr(out, expr, compact);
+            out.push(')');
+        }
+        Expr::Block(statements) => {
+            out.push('{');
+            for statement in statements {
+                out.push_str(space);
+                write_expr(out, statement, compact);
+                out.push(';');
+            }
+            out.push_str(space);
+            out.push('}');
+        }
+        Expr::Return(expr) => {
+            out.push_str("return ");
+            write_expr(out, expr, compact);
+        }
+        Expr::Break => out.push_str("break"),
+        Expr::Continue => out.push_str("continue"),
+    }
+}
+
+fn children(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::Call { args, .. } => args.iter().collect(),
+        Expr::Index { target, index } => vec![target, index],
+        Expr::Arrow { target, expr } => vec![target, expr],
+        Expr::Unary { expr, .. } | Expr::Paren(expr) | Expr::Return(expr) => vec![expr],
+        Expr::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+        Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => {
+            let mut children = vec![condition.as_ref(), then];
+            children.extend(otherwise.as_deref());
+            children
+        }
+        Expr::Block(statements) => statements.iter().collect(),
+        _ => vec![],
+    }
+}
+
+fn walk<'a>(expr: &'a Expr, f: &mut impl FnMut(&'a Expr)) {
+    f(expr);
+
+    for child in children(expr) {
+        walk(child, f);
+    }
+}
+
+fn check_name(name: &str, span: &Range<usize>, args: Option<usize>) -> Option<MolangError> {
+    let table = name_table();
+    let lower = name.to_ascii_lowercase();
+    let mut segments = lower.split('.');
+    let namespace = segments.next().unwrap_or_default();
+    let member = segments.next();
+
+    let error = |message: String| {
+        Some(MolangError {
+            span: span.clone(),
+            message,
+        })
+    };
+
+    match (namespace, member) {
+        ("this" | "true" | "false", None) if args.is_none() => None,
+        ("loop", None) if args == Some(2) => None,
+        ("for_each", None) if args == Some(3) => None,
+        ("loop" | "for_each", None) => error(format!(
+            "\"{namespace}\" takes {} arguments",
+            if namespace == "loop" { 2 } else { 3 }
+        )),
+        (
+            "query" | "q" | "math" | "variable" | "v" | "temp" | "t" | "context" | "c" | "geometry"
+            | "texture" | "material" | "array",
+            None,
+        ) => error(format!("Expected a name after \"{namespace}.\"")),
+        ("query" | "q", Some(member)) if !table.query.contains(member) => {
+            error(format!("Unknown query {name:?}"))
+        }
+        ("context" | "c", Some(member)) if !table.context.contains(member) => {
+            error(format!("Unknown context {name:?}"))
+        }
+        ("math", Some(member)) => match (table.math.get(member), args) {
+            (None, _) => error(format!("Unknown math function {name:?}")),
+            (Some([min, _]), None) if *min > 0 => error(format!("{name:?} has to be called")),
+            (Some([min, max]), Some(args)) if args < *min || args > *max => {
+                let expected = if min == max {
+                    min.to_string()
+                } else {
+                    format!("{min} to {max}")
+                };
+                error(format!("{name:?} takes {expected} arguments, found {args}"))
+            }
+            _ => None,
+        },
+        (
+            "query" | "q" | "variable" | "v" | "temp" | "t" | "context" | "c" | "geometry"
+            | "texture" | "material" | "array",
+            Some(_),
+        ) => None,
+        _ => error(format!("Unknown name {name:?}")),
+    }
+}
+
+/// Reports names that aren't in the bundled table, or called with the wrong number of arguments
+pub fn unknown_names(program: &Program) -> Vec<MolangError> {
+    let mut errors = vec![];
+
+    for statement in &program.statements {
+        walk(statement, &mut |expr| {
+            let error = match expr {
+                Expr::Name { name, span } => check_name(name, span, None),
+                Expr::Call { name, span, args } => check_name(name, span, Some(args.len())),
+                _ => None,
+            };
+            errors.extend(error);
+        });
+    }
+
+    errors
+}
+
+/// The lowercase variable name of `variable.name` or `v.name.field`
+pub fn variable_name(name: &str) -> Option<String> {
+    let (namespace, rest) = name.split_once('.')?;
+
+    if !matches!(namespace.to_ascii_lowercase().as_str(), "variable" | "v") {
+        return None;
+    }
+
+    Some(rest.split('.').next()?.to_ascii_lowercase())
+}
+
+/// Variables assigned in an expression, and the ones read together with where
+pub fn variables(program: &Program) -> (Vec<String>, Vec<(String, Range<usize>)>) {
+    let mut assigned = vec![];
+    let mut read = vec![];
+    let mut targets = HashSet::new();
+
+    for statement in &program.statements {
+        walk(statement, &mut |expr| match expr {
+            Expr::Binary { op: "=", lhs, .. } => {
+                if let Expr::Name { name, span } = lhs.as_ref() {
+                    targets.insert(span.start);
+                    assigned.extend(variable_name(name));
+                }
+            }
+            Expr::Name { name, span } if !targets.contains(&span.start) => {
+                if let Some(variable) = variable_name(name) {
+                    read.push((variable, span.clone()));
+                }
+            }
+            _ => {}
+        });
+    }
+
+    (assigned, read)
+}
+
+fn is_command(text: &str) -> bool {
+    let text = text.trim_start();
+    text.starts_with('/') || text.starts_with('@')
+}
+
+fn entries(value: Option<&Value>) -> impl Iterator<Item = (&String, &Value)> {
+    value.and_then(Value::as_object).into_iter().flatten()
+}
+
+fn items(value: Option<&Value>) -> impl Iterator<Item = (usize, &Value)> {
+    value
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+}
+
+struct Expressions<'a> {
+    found: Vec<(JsonPath, &'a str)>,
+}
+
+impl<'a> Expressions<'a> {
+    fn push(&mut self, path: JsonPath, value: Option<&'a Value>) {
+        if let Some(text) = value.and_then(Value::as_str) {
+            if !text.trim().is_empty() {
+                self.found.push((path, text));
+            }
+        }
+    }
+
+    /// A string, or an array of strings
+    fn push_all(&mut self, path: JsonPath, value: Option<&'a Value>) {
+        match value {
+            Some(Value::Array(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.push(path.index(i), Some(value));
+                }
+            }
+            value => self.push(path, value),
+        }
+    }
+
+    /// Arrays like `[ "name", { "name": "condition" } ]`
+    fn conditions(&mut self, path: JsonPath, value: Option<&'a Value>) {
+        for (i, item) in items(value) {
+            for (name, condition) in entries(Some(item)) {
+                self.push(path.index(i).key(name), Some(condition));
+            }
+        }
+    }
+
+    /// Molang or commands in `on_entry`, `on_exit` and timelines
+    fn scripts(&mut self, path: JsonPath, value: Option<&'a Value>) {
+        match value {
+            Some(Value::Array(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.scripts(path.index(i), Some(value));
+                }
+            }
+            Some(Value::String(text)) if !is_command(text) => self.push(path, value),
+            _ => {}
+        }
+    }
+
+    fn effects(&mut self, path: JsonPath, value: Option<&'a Value>) {
+        match value {
+            Some(Value::Array(values)) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.effects(path.index(i), Some(value));
+                }
+            }
+            Some(Value::Object(effect)) => self.push(
+                path.key("pre_effect_script"),
+                effect.get("pre_effect_script"),
+            ),
+            _ => {}
+        }
+    }
+
+    fn entity(&mut self, path: JsonPath, value: &'a Value) {
+        let description = value.get("description");
+        let path = path.key("description");
+
+        let scripts = description.and_then(|v| v.get("scripts"));
+        let scripts_path = path.key("scripts");
+
+        for field in ["initialize", "pre_animation"] {
+            self.push_all(scripts_path.key(field), scripts.and_then(|v| v.get(field)));
+        }
+        for field in ["scale", "scaleX", "scaleY", "scaleZ"] {
+            self.push(scripts_path.key(field), scripts.and_then(|v| v.get(field)));
+        }
+        self.conditions(
+            scripts_path.key("animate"),
+            scripts.and_then(|v| v.get("animate")),
+        );
+
+        self.conditions(
+            path.key("render_controllers"),
+            description.and_then(|v| v.get("render_controllers")),
+        );
+    }
+
+    fn animation(&mut self, path: JsonPath, value: &'a Value) {
+        for field in [
+            "anim_time_update",
+            "blend_weight",
+            "start_delay",
+            "loop_delay",
+        ] {
+            self.push(path.key(field), value.get(field));
+        }
+
+        for (bone, channels) in entries(value.get("bones")) {
+            for channel in ["rotation", "position", "scale"] {
+                let path = path.key("bones").key(bone).key(channel);
+
+                match channels.get(channel) {
+                    Some(Value::Object(keyframes)) => {
+                        for (time, keyframe) in keyframes {
+                            let path = path.key(time);
+
+                            if keyframe.is_object() {
+                                for field in ["pre", "post"] {
+                                    self.push_all(path.key(field), keyframe.get(field));
+                                }
+                            } else {
+                                self.push_all(path, Some(keyframe));
+                            }
+                        }
+                    }
+                    value => self.push_all(path, value),
+                }
+            }
+        }
+
+        for (time, script) in entries(value.get("timeline")) {
+            self.scripts(path.key("timeline").key(time), Some(script));
+        }
+
+        for field in ["particle_effects", "sound_effects"] {
+            for (time, effect) in entries(value.get(field)) {
+                self.effects(path.key(field).key(time), Some(effect));
+            }
+        }
+    }
+
+    fn controller(&mut self, path: JsonPath, value: &'a Value) {
+        for (state, value) in entries(value.get("states")) {
+            let path = path.key("states").key(state);
+
+            self.conditions(path.key("transitions"), value.get("transitions"));
+            self.conditions(path.key("animations"), value.get("animations"));
+
+            for field in ["on_entry", "on_exit"] {
+                self.scripts(path.key(field), value.get(field));
+            }
+
+            for (name, variable) in entries(value.get("variables")) {
+                self.push(
+                    path.key("variables").key(name).key("input"),
+                    variable.get("input"),
+                );
+            }
+
+            self.push_all(path.key("parameters"), value.get("parameters"));
+            self.effects(path.key("particle_effects"), value.get("particle_effects"));
+        }
+    }
+
+    fn render_controller(&mut self, path: JsonPath, value: &'a Value) {
+        for field in ["geometry", "light_color_multiplier"] {
+            self.push(path.key(field), value.get(field));
+        }
+
+        self.push_all(path.key("textures"), value.get("textures"));
+
+        for field in ["materials", "part_visibility"] {
+            self.conditions(path.key(field), value.get(field));
+        }
+
+        for field in ["color", "overlay_color", "on_fire_color", "is_hurt_color"] {
+            for (channel, value) in entries(value.get(field)) {
+                self.push(path.key(field).key(channel), Some(value));
+            }
+        }
+
+        for field in ["offset", "scale"] {
+            self.push_all(
+                path.key("uv_anim").key(field),
+                value.get("uv_anim").and_then(|v| v.get(field)),
+            );
+        }
+    }
+
+    /// Most strings in particle components are Molang, except for a few enums, events and colors
+    fn particle_component(&mut self, path: JsonPath, key: &str, value: &'a Value) {
+        match value {
+            Value::Object(values) => {
+                for (key, value) in values {
+                    if !matches!(
+                        key.as_str(),
+                        "mode" | "facing_camera_mode" | "texture" | "events" | "gradient"
+                    ) {
+                        self.particle_component(path.key(key), key, value);
+                    }
+                }
+            }
+            Value::Array(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    self.particle_component(path.index(i), key, value);
+                }
+            }
+            Value::String(text)
+                if text.starts_with('#')
+                    || (key == "direction" && matches!(text.as_str(), "inwards" | "outwards")) => {}
+            Value::String(_) => self.push(path, Some(value)),
+            _ => {}
+        }
+    }
+
+    fn particle(&mut self, path: JsonPath, value: &'a Value) {
+        for (name, component) in entries(value.get("components")) {
+            if name.ends_with("_events") {
+                continue;
+            }
+            self.particle_component(path.key("components").key(name), name, component);
+        }
+
+        for (name, curve) in entries(value.get("curves")) {
+            let path = path.key("curves").key(name);
+
+            for field in ["input", "horizontal_range"] {
+                self.push(path.key(field), curve.get(field));
+            }
+            if curve.get("nodes").is_some_and(Value::is_array) {
+                self.push_all(path.key("nodes"), curve.get("nodes"));
+            }
+        }
+
+        for (name, event) in entries(value.get("events")) {
+            self.push(
+                path.key("events").key(name).key("expression"),
+                event.get("expression"),
+            );
+        }
+    }
+}
+
+/// Finds the Molang strings in a file, picking the fields to look at by its top level keys
+pub fn expressions(json: &Value) -> Vec<(JsonPath, &str)> {
+    let mut expressions = Expressions { found: vec![] };
+
+    for (key, value) in entries(Some(json)) {
+        let path = JsonPath::default().key(key);
+
+        match key.as_str() {
+            "minecraft:client_entity" | "minecraft:attachable" | "minecraft:entity" => {
+                expressions.entity(path, value)
+            }
+            "animations" => {
+                for (name, animation) in entries(Some(value)) {
+                    expressions.animation(path.key(name), animation);
+                }
+            }
+            "animation_controllers" => {
+                for (name, controller) in entries(Some(value)) {
+                    expressions.controller(path.key(name), controller);
+                }
+            }
+            "render_controllers" => {
+                for (name, controller) in entries(Some(value)) {
+                    expressions.render_controller(path.key(name), controller);
+                }
+            }
+            "particle_effect" => expressions.particle(path, value),
+            _ => {}
+        }
+    }
+
+    expressions.found
+}
+
+/// Rewrites the Molang strings inside a JSON file, leaving everything else untouched
+pub fn format_json(text: &str, compact: bool) -> Option<String> {
+    let json = json::parse(text).ok()?;
+    let mut replacements = vec![];
+
+    for (path, expression) in expressions(&json) {
+        let Ok(program) = parse(expression) else {
+            continue;
+        };
+
+        let formatted = format(&program, compact);
+        if formatted == expression {
+            continue;
+        }
+
+        if let Some(range) = json::locate(text, &path) {
+            replacements.push((range, serde_json::to_string(&formatted).ok()?));
+        }
+    }
+
+    if replacements.is_empty() {
+        return None;
+    }
+
+    replacements.sort_by_key(|((start, _), _)| std::cmp::Reverse(*start));
+
+    let mut text = text.to_string();
+    for ((start, end), replacement) in replacements {
+        text.replace_range(start..end, &replacement);
+    }
+
+    Some(text)
+}
+
+/// Compacts all Molang strings of an already parsed file
+pub fn minify(json: &mut Value) {
+    let compacted: Vec<(JsonPath, String)> = expressions(json)
+        .into_iter()
+        .filter_map(|(path, expression)| Some((path, format(&parse(expression).ok()?, true))))
+        .collect();
+
+    for (path, expression) in compacted {
+        if let Some(value) = json.pointer_mut(&path.pointer()) {
+            *value = Value::String(expression);
+        }
+    }
+}
+
+fn range(file: &LintFile, path: &JsonPath, span: &Range<usize>) -> Option<TextRange> {
+    let text = file.text.as_deref()?;
+
+    match (
+        json::string_offset(text, path, span.start),
+        json::string_offset(text, path, span.end),
+    ) {
+        (Some(start), Some(end)) => Some(TextRange::from_offsets(text, start, end)),
+        _ => file.range(path),
+    }
+}
+
+/// Every Molang string in the projects, parsed
+fn parsed(ctx: &LintContext) -> Vec<(&LintFile, JsonPath, Result<Program, MolangError>)> {
+    ctx.files
+        .iter()
+        .filter_map(|file| Some((file, file.json.as_ref()?)))
+        .flat_map(|(file, json)| {
+            expressions(json)
+                .into_iter()
+                .map(move |(path, expression)| (file, path, parse(expression)))
+        })
+        .collect()
+}
+
+pub struct MolangSyntax;
+
+impl LintRule for MolangSyntax {
+    fn code(&self) -> &'static str {
+        "molang-syntax"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        parsed(ctx)
+            .into_iter()
+            .filter_map(|(file, path, program)| {
+                let error = program.err()?;
+
+                Some(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    range(file, &path, &error.span),
+                    format!("Invalid Molang: {}", error.message),
+                ))
+            })
+            .collect()
+    }
+}
+
+pub struct MolangUnknownName;
+
+impl LintRule for MolangUnknownName {
+    fn code(&self) -> &'static str {
+        "molang-unknown-name"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for (file, path, program) in parsed(ctx) {
+            let Ok(program) = program else {
+                continue;
+            };
+
+            for error in unknown_names(&program) {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    range(file, &path, &error.span),
+                    error.message,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+pub struct MolangUndefinedVariable;
+
+impl LintRule for MolangUndefinedVariable {
+    fn code(&self) -> &'static str {
+        "molang-undefined-variable"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let parsed = parsed(ctx);
+
+        let mut defined: HashSet<String> = name_table().variable.clone();
+
+        // Variables declared in `scripts.variables` are set by the engine or other entities
+        for file in &ctx.files {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            for (_, definition) in entries(Some(json)) {
+                let variables = definition
+                    .get("description")
+                    .and_then(|v| v.get("scripts"))
+                    .and_then(|v| v.get("variables"));
+
+                defined.extend(entries(variables).filter_map(|(name, _)| variable_name(name)));
+            }
+        }
+
+        for (_, _, program) in &parsed {
+            if let Ok(program) = program {
+                defined.extend(variables(program).0);
+            }
+        }
+
+        let mut diagnostics = vec![];
+
+        for (file, path, program) in &parsed {
+            let Ok(program) = program else {
+                continue;
+            };
+
+            for (variable, span) in variables(program).1 {
+                if !defined.contains(&variable) {
+                    diagnostics.push(Diagnostic::new(
+                        self.code(),
+                        &file.path,
+                        range(file, path, &span),
+                        format!("Variable {variable:?} is never assigned"),
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formatted(text: &str, compact: bool) -> String {
+        format(&parse(text).unwrap(), compact)
+    }
+
+    fn error(text: &str) -> String {
+        parse(text).unwrap_err().message
+    }
+
+    #[test]
+    fn tokenizes_names_numbers_and_strings() {
+        let tokens: Vec<Token> = tokenize("v.a.b>=.5?'x y':q.c(1)")
+            .unwrap()
+            .into_iter()
+            .map(|v| v.token)
+            .collect();
+
+        assert_eq!(
+            tokens,
+            [
+                Token::Name("v.a.b".to_string()),
+                Token::Op(">="),
+                Token::Number(".5".to_string()),
+                Token::Op("?"),
+                Token::String("x y".to_string()),
+                Token::Op(":"),
+                Token::Name("q.c".to_string()),
+                Token::Op("("),
+                Token::Number("1".to_string()),
+                Token::Op(")"),
+                Token::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        let err = tokenize("q.a + 'open").unwrap_err();
+        assert_eq!(err.message, "Unterminated string");
+        assert_eq!(err.span, 6..11);
+
+        let err = tokenize("1 # 2").unwrap_err();
+        assert_eq!(err.message, "Unexpected character '#'");
+        assert_eq!(err.span, 2..3);
+    }
+
+    #[test]
+    fn formats_expressions() {
+        for (text, expected) in [
+            ("1+2*3", "1 + 2 * 3"),
+            ("(1+2)*-q.a", "(1 + 2) * -q.a"),
+            ("q.is_baby?0.5:1", "q.is_baby ? 0.5 : 1"),
+            ("math.clamp(v.x,0,1)", "math.clamp(v.x, 0, 1)"),
+            (
+                "v.a=1;v.b=v.a??2;return v.b;",
+                "v.a = 1; v.b = v.a ?? 2; return v.b;",
+            ),
+            (
+                "loop(2,{v.i=v.i+1;break;});",
+                "loop(2, { v.i = v.i + 1; break; });",
+            ),
+            ("c.other->q.health", "c.other->q.health"),
+            ("array.colors[q.variant]", "array.colors[q.variant]"),
+        ] {
+            assert_eq!(formatted(text, false), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn compacts_expressions() {
+        assert_eq!(
+            formatted("query.is_baby ? variable.Size : temp.x", true),
+            "q.is_baby?v.Size:t.x"
+        );
+        assert_eq!(formatted("Query.a = 1; context.b;", true), "q.a=1;c.b;");
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for text in [
+            "1+2*3-(4/5)",
+            "q.is_baby?(v.a>1&&!v.b):math.sin(q.anim_time*90)",
+            "v.x=0;loop(3,{v.x=v.x+1;});return v.x;",
+        ] {
+            for compact in [false, true] {
+                let once = formatted(text, compact);
+                assert_eq!(formatted(&once, compact), once, "{text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert!(parse("1 +").is_err());
+        assert!(parse("(1 + 2").is_err());
+        assert!(parse("math.sin(1,").is_err());
+        assert_eq!(error("1 2"), "Expected \";\" or the end of the expression");
+    }
+
+    #[test]
+    fn reports_unknown_names() {
+        let messages = |text: &str| -> Vec<String> {
+            unknown_names(&parse(text).unwrap())
+                .into_iter()
+                .map(|v| v.message)
+                .collect()
+        };
+
+        assert!(messages("q.is_baby && math.sin(v.anything) > c.is_first_person").is_empty());
+        assert_eq!(
+            messages("q.bogus + math.sin(1, 2) + math.nope(1) + math.clamp + foo + query"),
+            [
+                "Unknown query \"q.bogus\"",
+                "\"math.sin\" takes 1 arguments, found 2",
+                "Unknown math function \"math.nope\"",
+                "\"math.clamp\" has to be called",
+                "Unknown name \"foo\"",
+                "Expected a name after \"query.\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_assigned_and_read_variables() {
+        let (assigned, read) = variables(&parse("v.a = 1; t.b = V.Size.x + v.a;").unwrap());
+
+        assert_eq!(assigned, ["a"]);
+        assert_eq!(
+            read,
+            [("size".to_string(), 15..23), ("a".to_string(), 26..29)]
+        );
+    }
+
+    #[test]
+    fn formats_molang_inside_json() {
+        let text = r#"{
+	"animation_controllers": {
+		"controller.animation.foo": {
+			"states": {
+				"default": {
+					"transitions": [{ "walk": "q.modified_move_speed>0.1" }],
+					"on_entry": ["/say hi", "v.walking=0;"]
+				}
+			}
+		}
+	}
+}"#;
+
+        let formatted = format_json(text, false).unwrap();
+        assert!(formatted.contains(r#"{ "walk": "q.modified_move_speed > 0.1" }"#));
+        assert!(formatted.contains(r#"["/say hi", "v.walking = 0;"]"#));
+        assert_eq!(format_json(&formatted, false), None);
+    }
+}
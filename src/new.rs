@@ -1,35 +1,43 @@
 use crate::args::CliNewSubCommand;
 use crate::config::{StrixConfig, StrixConfigPackType, StrixConfigProjectType, STRIX_CONFIG};
+use crate::error::{ExitCode, IoError};
 use dialoguer::{Input, MultiSelect, Select};
-use log::error;
 use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
+use thiserror::Error;
 use uuid::Uuid;
 
-fn get_text(name: &'static str) -> Result<String, String> {
+#[derive(Error, Debug)]
+pub enum NewError {
+    #[error("An unexpected Error occurred while trying to prompt for the {what}, Err: {source}")]
+    Prompt {
+        what: &'static str,
+        source: dialoguer::Error,
+    },
+    #[error("An unexpected Error occurred while trying to prompt for the Addon Generator, Err: Unexpected index {0}")]
+    UnknownGenerator(usize),
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl NewError {
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::Io
+    }
+}
+
+fn get_text(name: &'static str, what: &'static str) -> Result<String, NewError> {
     Input::new()
         .with_prompt(format!("Addon {name}"))
         .interact()
-        .map_err(|err| err.to_string())
+        .map_err(|source| NewError::Prompt { what, source })
 }
 
-pub async fn new(new: CliNewSubCommand) -> bool {
-    let name = match get_text("Name") {
-        Ok(v) => v,
-        Err(err) => {
-            error!("An unexpected Error occurred while trying to prompt for the Addon Name, Err: {err}");
-            return true;
-        }
-    };
-
-    let description = match get_text("Description") {
-        Ok(v) => v,
-        Err(err) => {
-            error!("An unexpected Error occurred while trying to prompt for the Addon Description, Err: {err}");
-            return true;
-        }
-    };
+pub async fn new(new: CliNewSubCommand) -> Result<(), NewError> {
+    let name = get_text("Name", "Addon Name")?;
+    let description = get_text("Description", "Addon Description")?;
+    let namespace = get_text("Namespace", "Addon Namespace")?;
 
     let select = Select::new()
         .with_prompt(format!("Select an Addon Generator for {name:?}"))
@@ -39,16 +47,7 @@ pub async fn new(new: CliNewSubCommand) -> bool {
 
     if let Some(path) = &new.path {
         if !path.exists() || !path.is_dir() {
-            match fs::create_dir(path) {
-                Ok(_) => {}
-                Err(err) => {
-                    error!(
-                        "An unexpected Error occurred while trying to create {:?}, Err: {err}",
-                        path
-                    );
-                    return true;
-                }
-            }
+            fs::create_dir(path).map_err(|err| IoError::new("create", path, err))?;
         }
     }
 
@@ -57,10 +56,11 @@ pub async fn new(new: CliNewSubCommand) -> bool {
     let mut config = StrixConfig {
         name,
         description,
+        namespace,
         ..Default::default()
     };
 
-    let error_out = match select.interact() {
+    let result = match select.interact() {
         Ok(0) => {
             config.project_type = StrixConfigProjectType::Vanilla;
             new_vanilla(&mut config, path.clone())
@@ -73,34 +73,23 @@ pub async fn new(new: CliNewSubCommand) -> bool {
             config.project_type = StrixConfigProjectType::Dash;
             unimplemented!()
         }
-        Ok(other) => {
-            error!("An unexpected Error occurred while trying to prompt for the Addon Generator, Err: Unexpected index {other}");
-            true
-        }
-        Err(err) => {
-            error!("An unexpected Error occurred while trying to prompt for the Addon Generator, Err: {err}");
-            true
-        }
+        Ok(other) => Err(NewError::UnknownGenerator(other)),
+        Err(source) => Err(NewError::Prompt {
+            what: "Addon Generator",
+            source,
+        }),
     };
 
-    match fs::write(
+    fs::write(
         path.join(STRIX_CONFIG),
         serde_json::to_string_pretty(&config).unwrap(),
-    ) {
-        Ok(_) => {}
-        Err(err) => {
-            error!(
-                "An unexpected Error occurred while trying to create {:?}, Err: {err}",
-                path.join(".strix")
-            );
-            return true;
-        }
-    };
+    )
+    .map_err(|err| IoError::new("create", path.join(STRIX_CONFIG), err))?;
 
-    error_out
+    result
 }
 
-fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
+fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> Result<(), NewError> {
     let select = MultiSelect::new()
         .with_prompt(format!("Select the Packs for {:?}", config.name))
         .items(&[
@@ -111,14 +100,20 @@ fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
         ])
         .report(true);
 
-    let selected = match select.interact() {
-        Ok(selected) => selected,
-        Err(err) => {
-            error!("An unexpected Error occurred while trying to prompt for the Addon Packs, Err: {err}");
-            return true;
-        }
-    };
+    let selected = select.interact().map_err(|source| NewError::Prompt {
+        what: "Addon Packs",
+        source,
+    })?;
 
+    write_vanilla(config, path, &selected)
+}
+
+/// Writes the manifests of the selected packs, indexed like the prompt items
+fn write_vanilla(
+    config: &mut StrixConfig,
+    path: PathBuf,
+    selected: &[usize],
+) -> Result<(), NewError> {
     // Behaviour Pack
     if selected.contains(&0) {
         let addon_name = format!("{}BP", config.name);
@@ -149,22 +144,11 @@ fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
         .unwrap_or_default();
 
         if !addon_path.exists() {
-            if let Err(err) = fs::create_dir(addon_path) {
-                error!(
-                    "An unexpected Error occurred while trying to write {:?}, Err: {err}",
-                    addon_path.display()
-                );
-                return true;
-            }
+            fs::create_dir(addon_path).map_err(|err| IoError::new("write", addon_path, err))?;
         }
 
-        if let Err(err) = fs::write(addon_path.join("manifest.json"), json) {
-            error!(
-                "An unexpected Error occurred while trying to write {:?}, Err: {err}",
-                addon_path.join("manifest.json").display()
-            );
-            return true;
-        };
+        fs::write(addon_path.join("manifest.json"), json)
+            .map_err(|err| IoError::new("write", addon_path.join("manifest.json"), err))?;
     }
 
     // Resource Pack
@@ -187,7 +171,7 @@ fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
             },
             "modules": [
                 {
-                    "type": "resource",
+                    "type": "resources",
                     "description": config.description,
                     "uuid": Uuid::new_v4(),
                     "version": [ 1, 0, 0 ],
@@ -197,22 +181,11 @@ fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
         .unwrap_or_default();
 
         if !addon_path.exists() {
-            if let Err(err) = fs::create_dir(addon_path) {
-                error!(
-                    "An unexpected Error occurred while trying to write {:?}, Err: {err}",
-                    addon_path.display()
-                );
-                return true;
-            }
+            fs::create_dir(addon_path).map_err(|err| IoError::new("write", addon_path, err))?;
         }
 
-        if let Err(err) = fs::write(addon_path.join("manifest.json"), json) {
-            error!(
-                "An unexpected Error occurred while trying to write {:?}, Err: {err}",
-                addon_path.join("manifest.json").display()
-            );
-            return true;
-        };
+        fs::write(addon_path.join("manifest.json"), json)
+            .map_err(|err| IoError::new("write", addon_path.join("manifest.json"), err))?;
     }
 
     // World Template
@@ -225,5 +198,34 @@ fn new_vanilla(config: &mut StrixConfig, path: PathBuf) -> bool {
         unimplemented!()
     }
 
-    false
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::{read_manifest, validate_manifest};
+
+    #[test]
+    fn writes_valid_vanilla_manifests() {
+        let root = std::env::temp_dir().join(format!("strix-new-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let mut config = StrixConfig {
+            name: String::from("Foo"),
+            description: String::from("pack.description"),
+            ..StrixConfig::default()
+        };
+        write_vanilla(&mut config, root.clone(), &[0, 1]).unwrap();
+
+        assert_eq!(config.projects.len(), 2);
+        for (name, pack_type) in &config.projects {
+            let manifest = read_manifest(&root.join(name).join("manifest.json")).unwrap();
+            let problems = validate_manifest(&manifest, pack_type);
+            assert!(problems.is_empty(), "{name}: {problems:?}");
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
 }
@@ -0,0 +1,472 @@
+use crate::project::Project;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use log::{error, info, warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+};
+
+/// Instructions a plugin may run per call, so a stuck plugin can't hang the build
+const PLUGIN_FUEL: u64 = 1_000_000_000;
+
+/// Linear memory a plugin instance may use, growing past it traps
+///
+/// Every build job runs its own instance, so a build may use this much memory per job.
+const PLUGIN_MEMORY: usize = 64 * 1024 * 1024;
+
+/// Imports of a plugin are resolved from this module, nothing else is available
+const HOST_MODULE: &str = "strix";
+
+#[derive(Error, Debug)]
+pub enum PluginError {
+    #[error(
+        "An unexpected Error occurred while trying to read the plugin {path:?}, Err: {source}"
+    )]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Couldn't load the plugin {path:?}, Err: {source}")]
+    Load { path: PathBuf, source: wasmi::Error },
+    #[error("The plugin {plugin:?} registered the invalid glob {glob:?}, Err: {source}")]
+    Glob {
+        plugin: String,
+        glob: String,
+        source: globset::Error,
+    },
+    #[error("The plugin {plugin:?} trapped while transforming {path:?}, Err: {source}")]
+    Trap {
+        plugin: String,
+        path: String,
+        source: wasmi::Error,
+    },
+    #[error("The plugin {plugin:?} failed to transform {path:?} with code {code}")]
+    Failed {
+        plugin: String,
+        path: String,
+        code: i32,
+    },
+}
+
+/// A compiled WebAssembly plugin, which transforms the files matching its globs
+///
+/// Plugins only see what the host API hands them, the file they transform and its path inside
+/// the pack. There is no WASI or any other import, so they can't touch the file system, the
+/// network or the clock. Every file gets a fresh instance, so no state leaks between files.
+///
+/// The host API is imported from the `strix` module:
+///
+/// | Import                        | Description                                         |
+/// |-------------------------------|-----------------------------------------------------|
+/// | `register_glob(ptr, len)`     | Only valid in `register`, adds a glob to transform  |
+/// | `path_len() -> i32`           | Length of the path of the file, like `items/a.json` |
+/// | `path_read(ptr)`              | Writes the path to `ptr`                            |
+/// | `input_len() -> i32`          | Length of the file contents                         |
+/// | `input_read(ptr)`             | Writes the file contents to `ptr`                   |
+/// | `output_write(ptr, len)`      | Replaces the file contents                          |
+/// | `log(level, ptr, len)`        | Logs a message, 0 is info, 1 warn and 2 error       |
+///
+/// A plugin exports its `memory`, a `register()` function and a `transform() -> i32` function,
+/// which returns 0 on success. Files are left as they are if `output_write` isn't called.
+/// Every call is limited to a billion instructions and 64 MiB of memory.
+pub struct Plugin {
+    pub name: String,
+    pub globs: Vec<String>,
+    glob_set: GlobSet,
+    module: Module,
+    linker: Linker<HostState>,
+}
+
+#[derive(Default)]
+struct HostState {
+    /// `register_glob` is only allowed while registering
+    registering: bool,
+    globs: Vec<String>,
+    plugin: String,
+    path: String,
+    input: Vec<u8>,
+    output: Option<Vec<u8>>,
+    limits: StoreLimits,
+}
+
+/// A store with the fuel and memory limits every plugin call runs with
+fn limited_store(engine: &Engine, state: HostState) -> Result<Store<HostState>, wasmi::Error> {
+    let mut store = Store::new(
+        engine,
+        HostState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(PLUGIN_MEMORY)
+                .trap_on_grow_failure(true)
+                .build(),
+            ..state
+        },
+    );
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(PLUGIN_FUEL)?;
+
+    Ok(store)
+}
+
+impl Plugin {
+    /// Compiles a `.wasm` file and lets it register its globs
+    pub fn load(engine: &Engine, path: &Path) -> Result<Self, PluginError> {
+        let wasm = fs::read(path).map_err(|source| PluginError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        let load_error = |source| PluginError::Load {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        let module = Module::new(engine, &wasm).map_err(load_error)?;
+        let linker = host_linker(engine).map_err(load_error)?;
+
+        let name = path
+            .file_stem()
+            .map(|v| v.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut store = limited_store(
+            engine,
+            HostState {
+                registering: true,
+                plugin: name.clone(),
+                ..Default::default()
+            },
+        )
+        .map_err(load_error)?;
+
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|v| v.start(&mut store))
+            .map_err(load_error)?;
+        instance
+            .get_typed_func::<(), ()>(&store, "register")
+            .and_then(|v| v.call(&mut store, ()))
+            .map_err(load_error)?;
+
+        let globs = std::mem::take(&mut store.data_mut().globs);
+        let mut builder = GlobSetBuilder::new();
+
+        for glob in &globs {
+            builder.add(Glob::new(glob).map_err(|source| PluginError::Glob {
+                plugin: name.clone(),
+                glob: glob.clone(),
+                source,
+            })?);
+        }
+
+        let glob_set = builder.build().map_err(|source| PluginError::Glob {
+            plugin: name.clone(),
+            glob: globs.join(", "),
+            source,
+        })?;
+
+        Ok(Self {
+            name,
+            globs,
+            glob_set,
+            module,
+            linker,
+        })
+    }
+
+    /// If the plugin wants to transform the file at `path`, relative to its pack
+    pub fn matches(&self, path: &str) -> bool {
+        self.glob_set.is_match(path)
+    }
+
+    /// Runs the plugin on a file in a fresh instance, `None` if the file wasn't changed
+    pub fn transform(&self, path: &str, input: Vec<u8>) -> Result<Option<Vec<u8>>, PluginError> {
+        let trap = |source| PluginError::Trap {
+            plugin: self.name.clone(),
+            path: path.to_string(),
+            source,
+        };
+
+        let mut store = limited_store(
+            self.module.engine(),
+            HostState {
+                plugin: self.name.clone(),
+                path: path.to_string(),
+                input,
+                ..Default::default()
+            },
+        )
+        .map_err(trap)?;
+
+        let instance = self
+            .linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|v| v.start(&mut store))
+            .map_err(trap)?;
+        let code = instance
+            .get_typed_func::<(), i32>(&store, "transform")
+            .and_then(|v| v.call(&mut store, ()))
+            .map_err(trap)?;
+
+        if code != 0 {
+            return Err(PluginError::Failed {
+                plugin: self.name.clone(),
+                path: path.to_string(),
+                code,
+            });
+        }
+
+        Ok(store.into_data().output)
+    }
+}
+
+/// Loads the plugins of a profile, paths are relative to the project root
+pub fn load_plugins(project: &Project, paths: &[String]) -> Result<Vec<Plugin>, PluginError> {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config);
+
+    paths
+        .iter()
+        .map(|v| Plugin::load(&engine, &project.path(v)))
+        .collect()
+}
+
+fn host_linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::new(engine);
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "register_glob",
+        |mut caller: Caller<HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            if !caller.data().registering {
+                return Err(wasmi::Error::new(
+                    "register_glob can only be called in register",
+                ));
+            }
+
+            let glob = String::from_utf8(read_memory(&caller, ptr, len)?)
+                .map_err(|_| wasmi::Error::new("globs have to be valid UTF-8"))?;
+            caller.data_mut().globs.push(glob);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(HOST_MODULE, "path_len", |caller: Caller<HostState>| {
+        caller.data().path.len() as i32
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "path_read",
+        |mut caller: Caller<HostState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let path = caller.data().path.clone();
+            write_memory(&mut caller, ptr, path.as_bytes())
+        },
+    )?;
+
+    linker.func_wrap(HOST_MODULE, "input_len", |caller: Caller<HostState>| {
+        caller.data().input.len() as i32
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "input_read",
+        |mut caller: Caller<HostState>, ptr: i32| -> Result<(), wasmi::Error> {
+            let input = std::mem::take(&mut caller.data_mut().input);
+            let result = write_memory(&mut caller, ptr, &input);
+            caller.data_mut().input = input;
+            result
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "output_write",
+        |mut caller: Caller<HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let output = read_memory(&caller, ptr, len)?;
+            caller.data_mut().output = Some(output);
+            Ok(())
+        },
+    )?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |caller: Caller<HostState>, level: i32, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+            let message = read_memory(&caller, ptr, len)?;
+            let message = String::from_utf8_lossy(&message);
+            let state = caller.data();
+
+            match level {
+                0 => info!("[{}] {}: {message}", state.plugin, state.path),
+                1 => warn!("[{}] {}: {message}", state.plugin, state.path),
+                _ => error!("[{}] {}: {message}", state.plugin, state.path),
+            }
+            Ok(())
+        },
+    )?;
+
+    Ok(linker)
+}
+
+fn read_memory(caller: &Caller<HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, wasmi::Error> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("plugins have to export their memory"))?;
+
+    memory_range(memory.data(caller), ptr, len)
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            wasmi::Error::new(format!(
+                "{len} bytes at {} are outside of the plugin memory",
+                ptr as u32
+            ))
+        })
+}
+
+/// The `len` bytes at `ptr`, `None` if the length is negative or they don't fit into `data`
+fn memory_range(data: &[u8], ptr: i32, len: i32) -> Option<&[u8]> {
+    let start = ptr as u32 as usize;
+    let len = usize::try_from(len).ok()?;
+    data.get(start..start.checked_add(len)?)
+}
+
+fn write_memory(caller: &mut Caller<HostState>, ptr: i32, data: &[u8]) -> Result<(), wasmi::Error> {
+    let memory = caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("plugins have to export their memory"))?;
+
+    memory
+        .write(caller, ptr as u32 as usize, data)
+        .map_err(wasmi::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plugin whose `transform` passes `ptr` and `len` to `output_write`
+    fn output_plugin(name: &str, ptr: i32, len: i32) -> Plugin {
+        load_wat(
+            name,
+            &format!(
+                r#"(module
+                    (import "strix" "output_write" (func $output_write (param i32 i32)))
+                    (memory (export "memory") 1)
+                    (data (i32.const 0) "hello")
+                    (func (export "register"))
+                    (func (export "transform") (result i32)
+                        (call $output_write (i32.const {ptr}) (i32.const {len}))
+                        (i32.const 0)))"#
+            ),
+        )
+    }
+
+    fn load_wat(name: &str, text: &str) -> Plugin {
+        let wasm = wat::parse_str(text).unwrap();
+
+        let path = std::env::temp_dir().join(format!("strix-{name}-{}.wasm", std::process::id()));
+        fs::write(&path, wasm).unwrap();
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let plugin = Plugin::load(&Engine::new(&config), &path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+        plugin
+    }
+
+    #[test]
+    fn reads_memory_in_range() {
+        let plugin = output_plugin("in-range", 0, 5);
+        let output = plugin.transform("a.json", vec![]).unwrap();
+
+        assert_eq!(output.as_deref(), Some(b"hello".as_slice()));
+    }
+
+    #[test]
+    fn traps_on_negative_length() {
+        let plugin = output_plugin("negative", 0, -1);
+
+        assert!(matches!(
+            plugin.transform("a.json", vec![]),
+            Err(PluginError::Trap { .. })
+        ));
+    }
+
+    #[test]
+    fn traps_on_oversized_length() {
+        let plugin = output_plugin("oversized", 65_000, 1_000);
+
+        assert!(matches!(
+            plugin.transform("a.json", vec![]),
+            Err(PluginError::Trap { .. })
+        ));
+    }
+
+    #[test]
+    fn traps_when_growing_past_the_memory_limit() {
+        let pages = PLUGIN_MEMORY / 65_536;
+        let plugin = load_wat(
+            "grow",
+            &format!(
+                r#"(module
+                    (memory (export "memory") 1)
+                    (func (export "register"))
+                    (func (export "transform") (result i32)
+                        (drop (memory.grow (i32.const {pages})))
+                        (i32.const 0)))"#
+            ),
+        );
+
+        assert!(matches!(
+            plugin.transform("a.json", vec![]),
+            Err(PluginError::Trap { .. })
+        ));
+
+        // Growing up to the limit is fine
+        let plugin = load_wat(
+            "grow-to-limit",
+            &format!(
+                r#"(module
+                    (memory (export "memory") 1)
+                    (func (export "register"))
+                    (func (export "transform") (result i32)
+                        (if (i32.eq (memory.grow (i32.const {})) (i32.const -1))
+                            (then (return (i32.const 1))))
+                        (i32.const 0)))"#,
+                pages - 1
+            ),
+        );
+        assert_eq!(plugin.transform("a.json", vec![]).unwrap(), None);
+    }
+
+    #[test]
+    fn fails_to_load_oversized_memory() {
+        let pages = PLUGIN_MEMORY / 65_536 + 1;
+        let wasm = wat::parse_str(format!(
+            r#"(module (memory (export "memory") {pages}) (func (export "register")))"#
+        ))
+        .unwrap();
+        let path = std::env::temp_dir().join(format!("strix-huge-{}.wasm", std::process::id()));
+        fs::write(&path, wasm).unwrap();
+
+        let result = Plugin::load(&Engine::default(), &path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(PluginError::Load { .. })));
+    }
+
+    #[test]
+    fn memory_range_checks_bounds() {
+        let data = [1, 2, 3, 4];
+
+        assert_eq!(memory_range(&data, 1, 2), Some([2, 3].as_slice()));
+        assert_eq!(memory_range(&data, 4, 0), Some([].as_slice()));
+        assert_eq!(memory_range(&data, 3, 2), None);
+        assert_eq!(memory_range(&data, 0, -1), None);
+        assert_eq!(memory_range(&data, -1, 1), None);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::build::{BuildEvent, BuildProgress, BuildStage};
+use crate::message::{self, emit_artifact, ArtifactKind};
+use crate::schema::SCHEMA_VERSION;
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The bars currently on screen, log lines are printed above them instead of through them
+static ACTIVE: Mutex<Option<MultiProgress>> = Mutex::new(None);
+
+/// Runs `f` with all progress bars hidden, used by the logger for every line
+pub fn suspend<R>(f: impl FnOnce() -> R) -> R {
+    let active = ACTIVE.lock().ok().and_then(|v| v.clone());
+
+    match active {
+        Some(multi) => multi.suspend(f),
+        None => f(),
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct StageStats {
+    files: usize,
+    before: u64,
+    after: u64,
+    duration: Duration,
+}
+
+/// Shows a progress bar per stage of the `build` command, followed by a summary table
+///
+/// Nothing is drawn with `--quiet`, hooks, filters and plugins are logged like any other step.
+pub struct CliProgress {
+    quiet: bool,
+    multi: MultiProgress,
+    bars: BTreeMap<BuildStage, ProgressBar>,
+    stats: BTreeMap<(String, BuildStage), StageStats>,
+}
+
+impl CliProgress {
+    pub fn new(quiet: bool) -> Self {
+        let multi = match quiet {
+            true => MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+            false => MultiProgress::new(),
+        };
+
+        if let Ok(mut active) = ACTIVE.lock() {
+            *active = Some(multi.clone());
+        }
+
+        Self {
+            quiet,
+            multi,
+            bars: BTreeMap::new(),
+            stats: BTreeMap::new(),
+        }
+    }
+
+    /// Clears the bars and prints the summary of a successful build
+    pub fn finish(self, success: bool) {
+        for bar in self.bars.values() {
+            bar.finish_and_clear();
+        }
+
+        if let Ok(mut active) = ACTIVE.lock() {
+            *active = None;
+        }
+
+        if self.quiet || !success || self.stats.is_empty() {
+            return;
+        }
+
+        let table = self.summary();
+
+        // stdout belongs to `--message-format json`
+        if message::is_json() {
+            eprint!("{table}");
+        } else {
+            print!("{table}");
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut rows = vec![[
+            String::from("Project"),
+            String::from("Stage"),
+            String::from("Files"),
+            String::from("Before"),
+            String::from("After"),
+            String::from("Time"),
+        ]];
+
+        let mut stats: Vec<_> = self.stats.iter().collect();
+        stats.sort_by_key(|((project, stage), _)| (*stage == BuildStage::Package, project, *stage));
+
+        for ((project, stage), stats) in stats {
+            rows.push([
+                project.clone(),
+                stage.to_string(),
+                stats.files.to_string(),
+                HumanBytes(stats.before).to_string(),
+                HumanBytes(stats.after).to_string(),
+                format!("{:.2?}", stats.duration),
+            ]);
+        }
+
+        let mut widths = [0; 6];
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut out = String::new();
+        for row in &rows {
+            let line: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .enumerate()
+                .map(|(i, (cell, width))| match i {
+                    // Numbers are right aligned
+                    2..=5 => format!("{cell:>width$}"),
+                    _ => format!("{cell:<width$}"),
+                })
+                .collect();
+            out += line.join("  ").trim_end();
+            out += "\n";
+        }
+
+        out
+    }
+
+    fn bar(&mut self, stage: BuildStage) -> &ProgressBar {
+        // Keep the bars in the order of the stages, no matter which one started first
+        let after = self.bars.range(..stage).next_back().map(|(_, v)| v.clone());
+        let multi = &self.multi;
+
+        self.bars.entry(stage).or_insert_with(|| {
+            let bar = ProgressBar::new(0)
+                .with_style(
+                    ProgressStyle::with_template(
+                        "{prefix:>9.cyan.bold} [{bar:30}] {pos}/{len} {wide_msg}",
+                    )
+                    .unwrap_or_else(|_| ProgressStyle::default_bar())
+                    .progress_chars("=> "),
+                )
+                .with_prefix(stage.to_string());
+
+            match after {
+                Some(after) => multi.insert_after(&after, bar),
+                None => multi.insert(0, bar),
+            }
+        })
+    }
+}
+
+impl BuildProgress for CliProgress {
+    fn event(&mut self, event: BuildEvent) {
+        match event {
+            BuildEvent::Validating if !self.quiet => {
+                info!("Validating against the bundled Bedrock {SCHEMA_VERSION} schemas");
+            }
+            BuildEvent::HookStarted { stage, command } if !self.quiet => {
+                info!("Running {stage} hook {command:?}");
+            }
+            BuildEvent::HookFinished {
+                stage,
+                command,
+                output,
+            } => {
+                for line in output.stderr.lines() {
+                    warn!("[{stage}] {line}");
+                }
+
+                if !self.quiet {
+                    for line in output.stdout.lines() {
+                        info!("[{stage}] {line}");
+                    }
+
+                    info!(
+                        "Hook {command:?} finished in {}",
+                        humantime::format_duration(output.duration)
+                    );
+                }
+            }
+            BuildEvent::FilterStarted { name } if !self.quiet => {
+                info!("Running filter {name:?}");
+            }
+            BuildEvent::FilterFinished { name, output } => {
+                for line in output.stderr.lines() {
+                    warn!("[{name}] {line}");
+                }
+
+                if !self.quiet {
+                    for line in output.stdout.lines() {
+                        info!("[{name}] {line}");
+                    }
+
+                    info!(
+                        "Filter {name:?} finished in {}",
+                        humantime::format_duration(output.duration)
+                    );
+                }
+            }
+            BuildEvent::PluginLoaded { name, globs } if !self.quiet => {
+                info!("Loaded plugin {name:?} for {globs:?}");
+            }
+            BuildEvent::StageStarted {
+                stage,
+                project,
+                files,
+            } => {
+                let bar = self.bar(stage);
+                bar.inc_length(files as u64);
+                bar.set_message(project.to_string());
+            }
+            BuildEvent::FileDone {
+                stage,
+                project,
+                before,
+                after,
+                ..
+            } => {
+                self.bar(stage).inc(1);
+
+                let stats = self.stats.entry((project.to_string(), stage)).or_default();
+                stats.files += 1;
+                stats.before += before;
+                stats.after += after;
+            }
+            BuildEvent::StageFinished {
+                stage,
+                project,
+                duration,
+            } => {
+                let stats = self.stats.entry((project.to_string(), stage)).or_default();
+                stats.duration += duration;
+            }
+            BuildEvent::IndexOutdated { path, diff, .. } => {
+                warn!(
+                    "{path:?} is out of date and was regenerated, missing {:?}, without a file {:?}",
+                    diff.missing, diff.stale
+                );
+            }
+            BuildEvent::ProjectBuilt { project, path } => {
+                emit_artifact(ArtifactKind::Project, Some(project), path);
+            }
+            BuildEvent::DevFolderCopied { project, path } => {
+                emit_artifact(ArtifactKind::DevFolder, Some(project), path);
+            }
+            BuildEvent::Packaged { path } => {
+                // The zipped files were counted with their own size, the package is what's left
+                let name = path
+                    .file_stem()
+                    .map(|v| v.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                if let (Some(stats), Ok(metadata)) = (
+                    self.stats.get_mut(&(name, BuildStage::Package)),
+                    fs::metadata(path),
+                ) {
+                    stats.after = metadata.len();
+                }
+
+                emit_artifact(ArtifactKind::Package, None, path);
+            }
+            BuildEvent::BuildInfoWritten { path } => {
+                emit_artifact(ArtifactKind::BuildInfo, None, path);
+            }
+            _ => {}
+        }
+    }
+}
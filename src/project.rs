@@ -0,0 +1,57 @@
+use crate::build::BuildError;
+use crate::config::{read_config, ConfigError, StrixBuildConfigProfile, StrixConfig};
+use crate::files::FileFilter;
+use std::path::{Path, PathBuf};
+
+/// A `strix.json` together with the folder it lives in
+///
+/// All paths inside the config, like the projects and the build path, are relative to `root`.
+/// An empty `root` is the working directory, which keeps paths in diagnostics short.
+#[derive(Debug, Clone)]
+pub struct Project {
+    pub root: PathBuf,
+    pub config: StrixConfig,
+}
+
+impl Project {
+    pub fn new(root: impl Into<PathBuf>, config: StrixConfig) -> Self {
+        Self {
+            root: root.into(),
+            config,
+        }
+    }
+
+    /// Loads the `strix.json` inside `root`
+    pub fn load(root: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        let root = root.into();
+        let config = read_config(&root)?.ok_or(ConfigError::Missing("loading a project"))?;
+
+        Ok(Self { root, config })
+    }
+
+    /// The ignore files and globs deciding which files of the projects are used
+    pub fn files(&self) -> Result<FileFilter, ConfigError> {
+        FileFilter::new(&self.root, &self.config)
+    }
+
+    /// Resolves a path from the config against the project root
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.root.join(path)
+    }
+
+    /// Looks up a build profile, falling back to the default profile of the config
+    pub fn profile(
+        &self,
+        name: Option<&str>,
+    ) -> Result<(&str, &StrixBuildConfigProfile), BuildError> {
+        let name = name.unwrap_or(&self.config.build.default_profile);
+
+        match self.config.build.profiles.get_key_value(name) {
+            Some((name, profile)) => Ok((name, profile)),
+            None => Err(BuildError::MissingProfile {
+                name: name.to_string(),
+                available: self.config.build.profiles.keys().cloned().collect(),
+            }),
+        }
+    }
+}
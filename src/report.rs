@@ -0,0 +1,200 @@
+use crate::diagnostic::{Diagnostic, Severity, TextRange};
+use crate::error::IoError;
+use log::info;
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Everything a validation run looked at, written out as SARIF or JUnit XML for CI
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Every checked file, files without diagnostics show up as passed tests in JUnit
+    pub files: Vec<PathBuf>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Report {
+    /// Writes a SARIF report and a JUnit report to the given paths, like `--sarif` and `--junit`
+    pub fn write(
+        &self,
+        sarif: Option<&Path>,
+        junit: Option<&Path>,
+        command: &str,
+    ) -> Result<(), IoError> {
+        if let Some(path) = sarif {
+            let sarif = serde_json::to_string_pretty(&self.sarif()).unwrap_or_default();
+            write_report(path, sarif, "SARIF")?;
+        }
+
+        if let Some(path) = junit {
+            write_report(path, self.junit(command), "JUnit")?;
+        }
+
+        Ok(())
+    }
+
+    /// A SARIF 2.1.0 log with a single run, one result per diagnostic
+    pub fn sarif(&self) -> Value {
+        let rules: BTreeSet<&str> = self.diagnostics.iter().map(|v| v.code.as_str()).collect();
+
+        let results: Vec<Value> = self
+            .diagnostics
+            .iter()
+            .filter(|v| v.severity != Severity::Off)
+            .map(|diagnostic| {
+                let location = |range: Option<&TextRange>| {
+                    let mut physical_location = json!({
+                        "artifactLocation": { "uri": uri(&diagnostic.file) },
+                    });
+
+                    if let Some(range) = range {
+                        physical_location["region"] = json!({
+                            "startLine": range.start.line,
+                            "startColumn": range.start.column,
+                            "endLine": range.end.line,
+                            "endColumn": range.end.column,
+                        });
+                    }
+
+                    json!({ "physicalLocation": physical_location })
+                };
+
+                let locations: Vec<Value> = std::iter::once(location(diagnostic.range.as_ref()))
+                    .chain(
+                        diagnostic
+                            .additional_ranges
+                            .iter()
+                            .map(|v| location(Some(v))),
+                    )
+                    .collect();
+
+                json!({
+                    "ruleId": diagnostic.code,
+                    "level": match diagnostic.severity {
+                        Severity::Error => "error",
+                        Severity::Warning => "warning",
+                        Severity::Info | Severity::Off => "note",
+                    },
+                    "message": { "text": diagnostic.message },
+                    "locations": locations,
+                })
+            })
+            .collect();
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules.iter().map(|v| json!({ "id": v })).collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+
+    /// A JUnit XML suite, each diagnostic is its own test case and clean files pass
+    pub fn junit(&self, command: &str) -> String {
+        let mut cases = vec![];
+        let mut failures = 0;
+
+        for diagnostic in &self.diagnostics {
+            if diagnostic.severity == Severity::Off {
+                continue;
+            }
+
+            let file = uri(&diagnostic.file);
+            let location = match diagnostic.range {
+                Some(range) => format!("{file}:{}:{}", range.start.line, range.start.column),
+                None => file.clone(),
+            };
+            let name = format!("{} {location}", diagnostic.code);
+
+            let body = if diagnostic.severity == Severity::Error {
+                failures += 1;
+                format!(
+                    "<failure type=\"{}\" message=\"{}\">{}</failure>",
+                    escape(&diagnostic.code),
+                    escape(&diagnostic.message),
+                    escape(&location)
+                )
+            } else {
+                format!(
+                    "<system-out>{}: {}</system-out>",
+                    diagnostic.severity,
+                    escape(&diagnostic.message)
+                )
+            };
+
+            cases.push(format!(
+                "    <testcase classname=\"{}\" name=\"{}\">\n      {body}\n    </testcase>\n",
+                escape(&file),
+                escape(&name)
+            ));
+        }
+
+        let problems: BTreeSet<&Path> = self.diagnostics.iter().map(|v| v.file.as_path()).collect();
+
+        for file in self
+            .files
+            .iter()
+            .filter(|v| !problems.contains(v.as_path()))
+        {
+            cases.push(format!(
+                "    <testcase classname=\"{}\" name=\"{command}\"/>\n",
+                escape(&uri(file))
+            ));
+        }
+
+        let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out += &format!(
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{failures}\">\n",
+            env!("CARGO_PKG_NAME"),
+            cases.len()
+        );
+        out += &format!(
+            "  <testsuite name=\"{} {command}\" tests=\"{}\" failures=\"{failures}\">\n",
+            env!("CARGO_PKG_NAME"),
+            cases.len()
+        );
+        out.extend(cases);
+        out += "  </testsuite>\n</testsuites>\n";
+
+        out
+    }
+}
+
+fn write_report(path: &Path, contents: String, kind: &str) -> Result<(), IoError> {
+    fs::write(path, contents).map_err(|err| IoError::new("write", path, err))?;
+
+    info!("Wrote {kind} report to {:?}", path.display());
+    Ok(())
+}
+
+/// Relative paths with forward slashes, like CI tools expect them
+fn uri(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            _ => out.push(char),
+        }
+    }
+
+    out
+}
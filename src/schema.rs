@@ -0,0 +1,691 @@
+use crate::config::StrixConfigPackType;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::json::JsonPath;
+use crate::lint::{LintContext, LintFile, LintRule};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// The Bedrock version the bundled schemas were written against
+///
+/// The schemas cover the common fields and components, not every one Bedrock knows, and most
+/// leave unknown components alone. Items and blocks have a schema for the formats before
+/// `1.16.100` and one for the newer component based format, models start at `1.12.0`. Entities
+/// and everything else use one schema for all formats, as their structure didn't change.
+pub const SCHEMA_VERSION: &str = "1.21.0";
+
+struct BundledSchema {
+    /// `None` if the folder has the same format in every pack type
+    pack_type: Option<StrixConfigPackType>,
+    folder: &'static str,
+    /// The lowest `format_version` this schema applies to
+    since: [u32; 3],
+    source: &'static str,
+}
+
+const SCHEMAS: &[BundledSchema] = &[
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "entities",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/entity.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "items",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/item_legacy.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "items",
+        since: [1, 16, 100],
+        source: include_str!("schemas/behavior/item.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "blocks",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/block_legacy.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "blocks",
+        since: [1, 16, 100],
+        source: include_str!("schemas/behavior/block.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "recipes",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/recipe.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "loot_tables",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/loot_table.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Behaviour),
+        folder: "spawn_rules",
+        since: [0, 0, 0],
+        source: include_str!("schemas/behavior/spawn_rules.json"),
+    },
+    BundledSchema {
+        pack_type: None,
+        folder: "animation_controllers",
+        since: [0, 0, 0],
+        source: include_str!("schemas/animation_controller.json"),
+    },
+    BundledSchema {
+        pack_type: None,
+        folder: "animations",
+        since: [0, 0, 0],
+        source: include_str!("schemas/animation.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Resource),
+        folder: "render_controllers",
+        since: [0, 0, 0],
+        source: include_str!("schemas/resource/render_controller.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Resource),
+        folder: "entity",
+        since: [0, 0, 0],
+        source: include_str!("schemas/resource/client_entity.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Resource),
+        folder: "attachables",
+        since: [0, 0, 0],
+        source: include_str!("schemas/resource/attachable.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Resource),
+        folder: "particles",
+        since: [0, 0, 0],
+        source: include_str!("schemas/resource/particle.json"),
+    },
+    BundledSchema {
+        pack_type: Some(StrixConfigPackType::Resource),
+        folder: "models",
+        since: [1, 12, 0],
+        source: include_str!("schemas/resource/geometry.json"),
+    },
+];
+
+fn parsed_schemas() -> &'static [Value] {
+    static PARSED: OnceLock<Vec<Value>> = OnceLock::new();
+
+    PARSED.get_or_init(|| {
+        SCHEMAS
+            .iter()
+            .map(|v| serde_json::from_str(v.source).expect("bundled schemas are valid JSON"))
+            .collect()
+    })
+}
+
+/// Parses a version like `"1.20.80"`, missing parts count as `0`
+pub fn parse_version(text: &str) -> Option<[u32; 3]> {
+    let mut version = [0; 3];
+
+    for (i, part) in text.split('.').enumerate() {
+        if i >= 3 {
+            return None;
+        }
+        version[i] = part.parse().ok()?;
+    }
+
+    Some(version)
+}
+
+/// Picks the schema for a file by its folder and `format_version`
+pub fn schema_for(file: &LintFile, json: &Value) -> Option<&'static Value> {
+    let version = json
+        .get("format_version")
+        .and_then(Value::as_str)
+        .and_then(parse_version)
+        .unwrap_or_default();
+
+    SCHEMAS
+        .iter()
+        .zip(parsed_schemas())
+        .filter(|(v, _)| v.pack_type.as_ref().is_none_or(|v| *v == file.pack_type))
+        .filter(|(v, _)| file.is_in(v.folder))
+        .filter(|(v, _)| v.since <= version)
+        .max_by_key(|(v, _)| v.since)
+        .map(|(_, schema)| schema)
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaError {
+    pub path: JsonPath,
+    pub message: String,
+}
+
+/// Validates `value` against a schema, supporting the JSON Schema subset used by the bundled schemas
+pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaError> {
+    let mut errors = vec![];
+    validate_node(schema, schema, value, &JsonPath::default(), &mut errors);
+    errors
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(v) if v.is_i64() || v.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn report(errors: &mut Vec<SchemaError>, path: &JsonPath, message: String) {
+    errors.push(SchemaError {
+        path: path.clone(),
+        message,
+    });
+}
+
+fn validate_node(
+    root: &Value,
+    schema: &Value,
+    value: &Value,
+    path: &JsonPath,
+    errors: &mut Vec<SchemaError>,
+) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(schema) => validate_node(root, schema, value, path, errors),
+            None => report(
+                errors,
+                path,
+                format!("Unresolved schema reference {reference:?}"),
+            ),
+        }
+        return;
+    }
+
+    match schema.get("type") {
+        Some(Value::String(expected)) if !matches_type(expected, value) => {
+            return report(
+                errors,
+                path,
+                format!("Expected {expected}, found {}", type_name(value)),
+            );
+        }
+        Some(Value::Array(expected))
+            if !expected
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|v| matches_type(v, value)) =>
+        {
+            return report(
+                errors,
+                path,
+                format!(
+                    "Expected one of {}, found {}",
+                    expected
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    type_name(value)
+                ),
+            );
+        }
+        _ => {}
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return report(
+                errors,
+                path,
+                format!(
+                    "Expected one of {}, found {value}",
+                    allowed
+                        .iter()
+                        .map(Value::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            return report(errors, path, format!("Expected {expected}, found {value}"));
+        }
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        let Some(branches) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+
+        let results: Vec<Vec<SchemaError>> = branches
+            .iter()
+            .map(|branch| {
+                let mut branch_errors = vec![];
+                validate_node(root, branch, value, path, &mut branch_errors);
+                branch_errors
+            })
+            .collect();
+
+        let matching = results.iter().filter(|v| v.is_empty()).count();
+
+        if matching == 0 {
+            // Report the branch that got closest, it's most likely the one that was intended
+            if let Some(best) = results.into_iter().min_by_key(Vec::len) {
+                errors.extend(best);
+            }
+            return;
+        } else if keyword == "oneOf" && matching > 1 {
+            report(
+                errors,
+                path,
+                format!("Matches {matching} alternatives, expected exactly one"),
+            );
+            return;
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        report(errors, path, format!("Missing required property {key:?}"));
+                    }
+                }
+            }
+
+            if let Some(min) = schema.get("minProperties").and_then(Value::as_u64) {
+                if (object.len() as u64) < min {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at least {min} properties, found {}", object.len()),
+                    );
+                }
+            }
+
+            if let Some(max) = schema.get("maxProperties").and_then(Value::as_u64) {
+                if object.len() as u64 > max {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at most {max} properties, found {}", object.len()),
+                    );
+                }
+            }
+
+            let properties = schema.get("properties").and_then(Value::as_object);
+
+            for (key, item) in object {
+                let item_path = path.key(key);
+
+                match properties.and_then(|v| v.get(key)) {
+                    Some(property) => validate_node(root, property, item, &item_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => errors.push(SchemaError {
+                            path: item_path,
+                            message: format!("Unknown property {key:?}"),
+                        }),
+                        Some(additional @ Value::Object(_)) => {
+                            validate_node(root, additional, item, &item_path, errors)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(array) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (array.len() as u64) < min {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at least {min} items, found {}", array.len()),
+                    );
+                }
+            }
+
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if array.len() as u64 > max {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at most {max} items, found {}", array.len()),
+                    );
+                }
+            }
+
+            if let Some(items) = schema.get("items") {
+                for (i, item) in array.iter().enumerate() {
+                    validate_node(root, items, item, &path.index(i), errors);
+                }
+            }
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected a value of at least {min}, found {number}"),
+                    );
+                }
+            }
+
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected a value of at most {max}, found {number}"),
+                    );
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at least {min} characters, found {length}"),
+                    );
+                }
+            }
+
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    report(
+                        errors,
+                        path,
+                        format!("Expected at most {max} characters, found {length}"),
+                    );
+                }
+            }
+
+            match schema.get("format").and_then(Value::as_str) {
+                Some("identifier") if !is_identifier(text) => report(
+                    errors,
+                    path,
+                    format!("{text:?} isn't a valid identifier like \"namespace:name\""),
+                ),
+                Some("version") if parse_version(text).is_none() => report(
+                    errors,
+                    path,
+                    format!("{text:?} isn't a valid version like \"1.20.0\""),
+                ),
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    let Some((namespace, name)) = text.split_once(':') else {
+        return false;
+    };
+
+    let valid = |v: &str| {
+        !v.is_empty()
+            && v.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-' | '/'))
+    };
+
+    valid(namespace) && valid(name)
+}
+
+pub struct SchemaRule;
+
+impl LintRule for SchemaRule {
+    fn code(&self) -> &'static str {
+        "schema"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for file in &ctx.files {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            let Some(schema) = schema_for(file, json) else {
+                continue;
+            };
+
+            for error in validate(schema, json) {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    file.range(&error.path),
+                    format!("{}: {}", error.path, error.message),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    /// The errors of a validation as `path: message`
+    fn errors(schema: &Value, value: &Value) -> Vec<String> {
+        validate(schema, value)
+            .into_iter()
+            .map(|v| format!("{}: {}", v.path, v.message))
+            .collect()
+    }
+
+    fn file(pack_type: StrixConfigPackType, relative: &str) -> LintFile {
+        LintFile {
+            pack_type,
+            path: PathBuf::from(relative),
+            relative: relative.to_string(),
+            text: None,
+            json: None,
+        }
+    }
+
+    #[test]
+    fn parses_bundled_schemas() {
+        assert_eq!(parsed_schemas().len(), SCHEMAS.len());
+        assert!(parsed_schemas().iter().all(Value::is_object));
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("1.20.80"), Some([1, 20, 80]));
+        assert_eq!(parse_version("1.16"), Some([1, 16, 0]));
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version("1.x.0"), None);
+    }
+
+    #[test]
+    fn accepts_valid_values() {
+        let schema = json!({
+            "type": "object",
+            "required": ["identifier"],
+            "additionalProperties": false,
+            "properties": {
+                "identifier": { "type": "string", "format": "identifier" },
+                "format_version": { "type": "string", "format": "version" },
+                "count": { "type": "integer", "minimum": 1, "maximum": 64 },
+                "tags": { "type": "array", "items": { "type": "string" }, "maxItems": 2 }
+            }
+        });
+        let value = json!({
+            "identifier": "foo:bar",
+            "format_version": "1.20.80",
+            "count": 64,
+            "tags": ["a", "b"]
+        });
+
+        assert!(errors(&schema, &value).is_empty());
+    }
+
+    #[test]
+    fn reports_invalid_values() {
+        let schema = json!({
+            "type": "object",
+            "required": ["identifier", "count"],
+            "additionalProperties": false,
+            "properties": {
+                "identifier": { "type": "string", "format": "identifier" },
+                "count": { "type": "integer", "minimum": 1 },
+                "mode": { "enum": ["a", "b"] },
+                "name": { "type": "string", "minLength": 2 },
+                "tags": { "type": "array", "minItems": 1 }
+            }
+        });
+        let value = json!({
+            "identifier": "bar",
+            "mode": "c",
+            "name": "x",
+            "tags": [],
+            "extra": 1.5
+        });
+
+        assert_eq!(
+            errors(&schema, &value),
+            [
+                "$: Missing required property \"count\"",
+                "$[\"identifier\"]: \"bar\" isn't a valid identifier like \"namespace:name\"",
+                "$[\"mode\"]: Expected one of \"a\", \"b\", found \"c\"",
+                "$[\"name\"]: Expected at least 2 characters, found 1",
+                "$[\"tags\"]: Expected at least 1 items, found 0",
+                "$[\"extra\"]: Unknown property \"extra\"",
+            ]
+        );
+        assert_eq!(
+            errors(&schema, &json!([])),
+            ["$: Expected object, found array"]
+        );
+    }
+
+    #[test]
+    fn resolves_references_and_alternatives() {
+        let schema = json!({
+            "definitions": {
+                "vec3": { "type": "array", "items": { "type": "number" }, "minItems": 3, "maxItems": 3 }
+            },
+            "type": "object",
+            "properties": {
+                "position": { "$ref": "#/definitions/vec3" },
+                "size": { "anyOf": [{ "type": "number" }, { "$ref": "#/definitions/vec3" }] },
+                "kind": { "oneOf": [{ "const": 1 }, { "type": "integer" }] },
+                "missing": { "$ref": "#/definitions/missing" }
+            }
+        });
+
+        assert!(errors(&schema, &json!({ "position": [0, 1.5, 2], "size": 2 })).is_empty());
+        assert_eq!(
+            errors(
+                &schema,
+                &json!({ "position": [0, "1"], "size": "big", "kind": 1, "missing": 0 })
+            ),
+            [
+                "$[\"position\"]: Expected at least 3 items, found 2",
+                "$[\"position\"][1]: Expected number, found string",
+                "$[\"size\"]: Expected number, found string",
+                "$[\"kind\"]: Matches 2 alternatives, expected exactly one",
+                "$[\"missing\"]: Unresolved schema reference \"#/definitions/missing\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn picks_schema_by_folder_and_version() {
+        let item = file(StrixConfigPackType::Behaviour, "items/foo.json");
+        let legacy = schema_for(&item, &json!({ "format_version": "1.10.0" })).unwrap();
+        let current = schema_for(&item, &json!({ "format_version": "1.20.80" })).unwrap();
+
+        assert!(!std::ptr::eq(legacy, current));
+        assert!(std::ptr::eq(
+            current,
+            schema_for(&item, &json!({ "format_version": "1.16.100" })).unwrap()
+        ));
+
+        assert!(schema_for(
+            &file(StrixConfigPackType::Resource, "items/foo.json"),
+            &json!({ "format_version": "1.20.80" })
+        )
+        .is_none());
+        assert!(schema_for(
+            &file(StrixConfigPackType::Behaviour, "scripts/a.json"),
+            &json!({})
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn validates_blocks_by_format() {
+        let block = file(StrixConfigPackType::Behaviour, "blocks/foo.json");
+
+        let legacy = json!({
+            "format_version": "1.12.0",
+            "minecraft:block": {
+                "description": { "identifier": "foo:ore", "register_to_creative_menu": true },
+                "components": {
+                    "minecraft:destroy_time": 3,
+                    "minecraft:block_light_emission": 0.5,
+                    "minecraft:map_color": "#ffffff"
+                }
+            }
+        });
+        let schema = schema_for(&block, &legacy).unwrap();
+        assert!(errors(schema, &legacy).is_empty());
+
+        let mut current = legacy.clone();
+        current["minecraft:block"]["permutations"] = json!([]);
+        current["minecraft:block"]["components"]["minecraft:block_light_emission"] = json!(2);
+        assert_eq!(
+            errors(schema, &current),
+            [
+                "$[\"minecraft:block\"][\"components\"][\"minecraft:block_light_emission\"]: Expected a value of at most 1, found 2",
+                "$[\"minecraft:block\"][\"permutations\"]: Unknown property \"permutations\"",
+            ]
+        );
+
+        current["format_version"] = json!("1.20.80");
+        current["minecraft:block"]["description"] = json!({ "identifier": "foo:ore" });
+        current["minecraft:block"]["components"] = json!({ "minecraft:light_emission": 15 });
+        let schema = schema_for(&block, &current).unwrap();
+        assert!(errors(schema, &current).is_empty());
+    }
+}
@@ -0,0 +1,951 @@
+use crate::args::{
+    CliLangCommand, CliLangExportSubCommand, CliLangImportSubCommand, CliLangSubCommand,
+    CliTranslationFormat,
+};
+use crate::config::{ConfigError, StrixConfig, STRIX_CONFIG};
+use crate::error::{ExitCode, IoError};
+use crate::json;
+use crate::lang::{self, LangLine, DEFAULT_PRIMARY_LANGUAGE};
+use log::{error, info, warn};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const DEFAULT_CSV_OUTPUT: &str = "translations.csv";
+const DEFAULT_PO_OUTPUT: &str = "translations";
+const PO_TEMPLATE: &str = "template.pot";
+
+/// Separates the project from the key in a PO `msgctxt`, it can't appear in folder names on Windows
+const PO_CONTEXT_SEPARATOR: char = '|';
+
+/// All keys of all projects, with the source text and every translation of it
+struct TranslationTable {
+    source_language: String,
+    languages: Vec<String>,
+    rows: Vec<TranslationRow>,
+}
+
+struct TranslationRow {
+    project: String,
+    key: String,
+    source: String,
+    translations: HashMap<String, String>,
+}
+
+#[derive(Error, Debug)]
+pub enum LangError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error("Unsupported translation file {0:?}, expected a \".csv\" or \".po\" file")]
+    UnsupportedFile(PathBuf),
+    #[error("Couldn't read {path:?}, {message}")]
+    InvalidTable { path: PathBuf, message: String },
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl LangError {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            LangError::Config(_) => ExitCode::Config,
+            LangError::UnsupportedFile(_) | LangError::InvalidTable { .. } => ExitCode::Validation,
+            LangError::Io(_) => ExitCode::Io,
+        }
+    }
+
+    /// Logs every error but the last one, which is returned to decide the exit code
+    fn from_errors(mut errors: Vec<LangError>) -> Result<(), LangError> {
+        let Some(last) = errors.pop() else {
+            return Ok(());
+        };
+
+        for err in errors {
+            error!("{err}");
+        }
+
+        Err(last)
+    }
+}
+
+pub async fn lang(lang: CliLangSubCommand, config: Option<StrixConfig>) -> Result<(), LangError> {
+    let config = config.ok_or(ConfigError::Missing("translations"))?;
+
+    match lang.command {
+        CliLangCommand::Export(v) => export(v, &config),
+        CliLangCommand::Import(v) => import(v, &config),
+    }
+}
+
+/// Reads all `texts/*.lang` files of a project, keyed by language
+fn read_languages(project: &str) -> BTreeMap<String, String> {
+    let mut languages = BTreeMap::new();
+
+    let Ok(dir) = fs::read_dir(Path::new(project).join("texts")) else {
+        return languages;
+    };
+
+    for entry in dir.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(OsStr::to_str) != Some("lang") {
+            continue;
+        }
+
+        let Some(language) = path.file_stem().and_then(OsStr::to_str) else {
+            continue;
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                languages.insert(language.to_string(), text);
+            }
+            Err(err) => warn!("Couldn't read {:?}, Err: {err}", path.display()),
+        }
+    }
+
+    languages
+}
+
+fn values(text: &str) -> Vec<(String, String)> {
+    lang::parse(text)
+        .into_iter()
+        .filter_map(|line| match line {
+            LangLine::Entry { key, value, .. } => Some((key, value)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn build_table(config: &StrixConfig) -> TranslationTable {
+    let source_language = config.lint.primary_language().to_string();
+
+    let mut projects: Vec<_> = config.projects.keys().collect();
+    projects.sort();
+
+    let mut languages = vec![];
+    let mut rows = vec![];
+
+    for project in projects {
+        let mut files = read_languages(project);
+
+        let Some(source) = files.remove(&source_language) else {
+            if !files.is_empty() {
+                warn!("{project:?} has translations but no \"texts/{source_language}.lang\", skipping it");
+            }
+            continue;
+        };
+
+        let translations: HashMap<&String, HashMap<String, String>> = files
+            .iter()
+            .map(|(language, text)| (language, values(text).into_iter().collect()))
+            .collect();
+
+        for language in files.keys() {
+            if !languages.contains(language) {
+                languages.push(language.clone());
+            }
+        }
+
+        for (key, value) in values(&source) {
+            rows.push(TranslationRow {
+                project: project.clone(),
+                translations: translations
+                    .iter()
+                    .filter_map(|(language, values)| {
+                        values.get(&key).map(|v| (language.to_string(), v.clone()))
+                    })
+                    .collect(),
+                key,
+                source: value,
+            });
+        }
+    }
+
+    languages.sort();
+
+    TranslationTable {
+        source_language,
+        languages,
+        rows,
+    }
+}
+
+fn export(export: CliLangExportSubCommand, config: &StrixConfig) -> Result<(), LangError> {
+    let table = build_table(config);
+
+    if table.rows.is_empty() {
+        warn!(
+            "Found no \"texts/{}.lang\" files to export",
+            table.source_language
+        );
+    }
+
+    match export.format {
+        CliTranslationFormat::Csv => {
+            let path = export
+                .output
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CSV_OUTPUT));
+
+            write_file(&path, write_csv(&table))
+        }
+        CliTranslationFormat::Po => {
+            let folder = export
+                .output
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_PO_OUTPUT));
+
+            fs::create_dir_all(&folder).map_err(|err| IoError::new("create", &folder, err))?;
+
+            // The template is what translators start from for a new language
+            write_file(&folder.join(PO_TEMPLATE), write_po(&table, None))?;
+
+            for language in &table.languages {
+                write_file(
+                    &folder.join(format!("{language}.po")),
+                    write_po(&table, Some(language)),
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn write_file(path: &Path, text: String) -> Result<(), LangError> {
+    fs::write(path, text).map_err(|err| IoError::new("write", path, err))?;
+
+    info!("Wrote {:?}", path.display());
+    Ok(())
+}
+
+fn import(import: CliLangImportSubCommand, config: &StrixConfig) -> Result<(), LangError> {
+    let mut errors = vec![];
+
+    for path in &import.files {
+        let text = match fs::read_to_string(path) {
+            Ok(v) => v,
+            Err(err) => {
+                errors.push(IoError::new("read", path, err).into());
+                continue;
+            }
+        };
+
+        let table = match path.extension().and_then(OsStr::to_str) {
+            Some("csv") => read_csv(&text),
+            Some("po") => read_po(&text),
+            Some("pot") => {
+                info!(
+                    "Skipping {:?}, templates don't contain translations",
+                    path.display()
+                );
+                continue;
+            }
+            _ => {
+                errors.push(LangError::UnsupportedFile(path.clone()));
+                continue;
+            }
+        };
+
+        match table {
+            Ok(table) => merge_table(&table, config, &mut errors),
+            Err(message) => errors.push(LangError::InvalidTable {
+                path: path.clone(),
+                message,
+            }),
+        }
+    }
+
+    LangError::from_errors(errors)
+}
+
+fn merge_table(table: &TranslationTable, config: &StrixConfig, errors: &mut Vec<LangError>) {
+    let mut projects: Vec<&str> = vec![];
+
+    for row in &table.rows {
+        if !projects.contains(&row.project.as_str()) {
+            projects.push(&row.project);
+        }
+    }
+
+    for project in projects {
+        if !config.projects.contains_key(project) {
+            warn!("{project:?} isn't a project in {STRIX_CONFIG:?}, skipping its translations");
+            continue;
+        }
+
+        let texts = Path::new(project).join("texts");
+        let source_path = texts.join(format!("{}.lang", table.source_language));
+
+        let source_text = match fs::read_to_string(&source_path) {
+            Ok(v) => v,
+            Err(err) => {
+                errors.push(IoError::new("read", &source_path, err).into());
+                continue;
+            }
+        };
+
+        let source = lang::parse(&source_text);
+        let current: HashMap<String, String> = values(&source_text).into_iter().collect();
+
+        let mut translations: BTreeMap<&str, HashMap<&str, &str>> = BTreeMap::new();
+
+        for row in table.rows.iter().filter(|v| v.project == project) {
+            let Some(value) = current.get(&row.key) else {
+                warn!(
+                    "{:?} no longer exists in {:?}, skipping its translations",
+                    row.key,
+                    source_path.display()
+                );
+                continue;
+            };
+
+            if *value != row.source {
+                warn!(
+                    "The source text of {:?} in {:?} changed since the export, its translations may be outdated",
+                    row.key,
+                    source_path.display()
+                );
+            }
+
+            for (language, text) in &row.translations {
+                if *language == table.source_language || text.is_empty() {
+                    continue;
+                }
+
+                if text.contains('\n') {
+                    warn!(
+                        "The {language:?} translation of {:?} spans multiple lines, which \".lang\" files don't support",
+                        row.key
+                    );
+                    continue;
+                }
+
+                translations
+                    .entry(language)
+                    .or_default()
+                    .insert(&row.key, text);
+            }
+        }
+
+        for (language, translations) in translations {
+            let path = texts.join(format!("{language}.lang"));
+            let existing = fs::read_to_string(&path).ok();
+
+            let text = merge(existing.as_deref(), &source, &translations);
+
+            if existing.as_deref() == Some(text.as_str()) {
+                continue;
+            }
+
+            if let Err(err) = write_file(&path, text) {
+                errors.push(err);
+                continue;
+            }
+
+            if existing.is_none() {
+                if let Err(err) = register_language(&texts.join("languages.json"), language) {
+                    errors.push(err);
+                }
+            }
+        }
+    }
+}
+
+/// Updates the values in an existing `.lang` file in place,
+/// new keys are inserted next to their neighbours in the source language
+fn merge(
+    existing: Option<&str>,
+    source: &[LangLine],
+    translations: &HashMap<&str, &str>,
+) -> String {
+    let Some(existing) = existing else {
+        // A new file follows the source language, comments included
+        let lines: Vec<LangLine> = source
+            .iter()
+            .filter_map(|line| match line {
+                LangLine::Entry { key, comment, .. } => {
+                    translations.get(key.as_str()).map(|value| LangLine::Entry {
+                        key: key.clone(),
+                        value: value.to_string(),
+                        comment: comment.clone(),
+                    })
+                }
+                line => Some(line.clone()),
+            })
+            .collect();
+
+        return lang::render(&lines);
+    };
+
+    let mut lines = lang::parse(existing);
+
+    for line in &mut lines {
+        if let LangLine::Entry { key, value, .. } = line {
+            if let Some(translation) = translations.get(key.as_str()) {
+                *value = translation.to_string();
+            }
+        }
+    }
+
+    let position = |lines: &[LangLine], target: &str| {
+        lines
+            .iter()
+            .position(|v| matches!(v, LangLine::Entry { key, .. } if key == target))
+    };
+
+    let source_keys: Vec<&str> = source
+        .iter()
+        .filter_map(|line| match line {
+            LangLine::Entry { key, .. } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    for (i, key) in source_keys.iter().enumerate() {
+        let Some(value) = translations.get(key) else {
+            continue;
+        };
+
+        if position(&lines, key).is_some() {
+            continue;
+        }
+
+        let index = source_keys[..i]
+            .iter()
+            .rev()
+            .find_map(|v| position(&lines, v).map(|v| v + 1))
+            .or_else(|| {
+                source_keys[i + 1..]
+                    .iter()
+                    .find_map(|v| position(&lines, v))
+            })
+            .unwrap_or(lines.len());
+
+        lines.insert(
+            index,
+            LangLine::Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                comment: None,
+            },
+        );
+    }
+
+    lang::render(&lines)
+}
+
+/// Adds a new language to `languages.json`, if the project has one
+fn register_language(path: &Path, language: &str) -> Result<(), LangError> {
+    let Ok(text) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+
+    let mut languages = match json::parse(&text) {
+        Ok(Value::Array(v)) => v,
+        _ => {
+            warn!(
+                "Couldn't add {language:?} to {:?}, it isn't an array",
+                path.display()
+            );
+            return Ok(());
+        }
+    };
+
+    if languages.iter().any(|v| v.as_str() == Some(language)) {
+        return Ok(());
+    }
+
+    languages.push(Value::String(language.to_string()));
+
+    write_file(
+        path,
+        serde_json::to_string_pretty(&languages).unwrap_or_default(),
+    )
+}
+
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// The header is `project,key,<source language>,<languages...>`
+fn write_csv(table: &TranslationTable) -> String {
+    let mut out = String::new();
+
+    let header: Vec<&str> = ["project", "key", &table.source_language]
+        .into_iter()
+        .chain(table.languages.iter().map(String::as_str))
+        .collect();
+
+    out += &header
+        .iter()
+        .map(|v| escape_csv(v))
+        .collect::<Vec<_>>()
+        .join(",");
+    out += "\r\n";
+
+    for row in &table.rows {
+        let fields: Vec<String> = [row.project.as_str(), &row.key, &row.source]
+            .into_iter()
+            .chain(table.languages.iter().map(|v| {
+                row.translations
+                    .get(v)
+                    .map(String::as_str)
+                    .unwrap_or_default()
+            }))
+            .map(escape_csv)
+            .collect();
+
+        out += &fields.join(",");
+        out += "\r\n";
+    }
+
+    out
+}
+
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    let mut line = 1;
+
+    while let Some(char) = chars.next() {
+        match (quoted, char) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, char) => {
+                if char == '\n' {
+                    line += 1;
+                }
+                field.push(char);
+            }
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                line += 1;
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, char) => field.push(char),
+        }
+    }
+
+    if quoted {
+        return Err(format!("unterminated quote starting before line {line}"));
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn read_csv(text: &str) -> Result<TranslationTable, String> {
+    let mut records = parse_csv(text)?.into_iter();
+
+    let header = records.next().ok_or("the file is empty")?;
+    if header.len() < 3 || header[0] != "project" || header[1] != "key" {
+        return Err("expected a header like \"project,key,en_US,...\"".to_string());
+    }
+
+    let source_language = header[2].clone();
+    let languages = header[3..].to_vec();
+
+    let rows = records
+        .enumerate()
+        .filter(|(_, v)| v.iter().any(|v| !v.is_empty()))
+        .map(|(i, record)| {
+            if record.len() < 3 {
+                return Err(format!("row {} has less than 3 columns", i + 2));
+            }
+
+            Ok(TranslationRow {
+                project: record[0].clone(),
+                key: record[1].clone(),
+                source: record[2].clone(),
+                translations: languages
+                    .iter()
+                    .cloned()
+                    .zip(record.into_iter().skip(3))
+                    .collect(),
+            })
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(TranslationTable {
+        source_language,
+        languages,
+        rows,
+    })
+}
+
+fn escape_po(text: &str) -> String {
+    let mut out = String::from('"');
+
+    for char in text.chars() {
+        match char {
+            '\\' => out += "\\\\",
+            '"' => out += "\\\"",
+            '\n' => out += "\\n",
+            '\t' => out += "\\t",
+            char => out.push(char),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+fn unescape_po(text: &str) -> Result<String, String> {
+    let inner = text
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a quoted string, found {text:?}"))?;
+
+    let mut out = String::new();
+    let mut chars = inner.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            out.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(char @ ('\\' | '"')) => out.push(char),
+            other => {
+                return Err(format!(
+                    "unknown escape sequence \\{}",
+                    other.unwrap_or(' ')
+                ))
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Writes a PO file for `language`, or the template without translations
+fn write_po(table: &TranslationTable, language: Option<&str>) -> String {
+    let mut out = String::new();
+
+    out += "msgid \"\"\nmsgstr \"\"\n";
+    out += "\"Content-Type: text/plain; charset=UTF-8\\n\"\n";
+    if let Some(language) = language {
+        out += &format!("\"Language: {language}\\n\"\n");
+    }
+    out += &format!("\"X-Source-Language: {}\\n\"\n", table.source_language);
+
+    for row in &table.rows {
+        let translation = language
+            .and_then(|v| row.translations.get(v))
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        out += &format!(
+            "\n#: {}/texts/{}.lang\n",
+            row.project, table.source_language
+        );
+        out += &format!(
+            "msgctxt {}\n",
+            escape_po(&format!("{}{PO_CONTEXT_SEPARATOR}{}", row.project, row.key))
+        );
+        out += &format!("msgid {}\n", escape_po(&row.source));
+        out += &format!("msgstr {}\n", escape_po(translation));
+    }
+
+    out
+}
+
+fn read_po(text: &str) -> Result<TranslationTable, String> {
+    #[derive(Default)]
+    struct Entry {
+        context: Option<String>,
+        id: String,
+        string: String,
+    }
+
+    let mut entries: Vec<Entry> = vec![];
+    let mut current: Option<(Entry, &str)> = None;
+
+    for (i, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line.trim();
+        let err = |err: String| format!("line {}: {err}", i + 1);
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('"') {
+            let (entry, field) = current
+                .as_mut()
+                .ok_or_else(|| err("string without a keyword".to_string()))?;
+            let text = unescape_po(line).map_err(err)?;
+
+            match *field {
+                "msgctxt" => entry
+                    .context
+                    .get_or_insert_with(String::new)
+                    .push_str(&text),
+                "msgid" => entry.id += &text,
+                _ => entry.string += &text,
+            }
+            continue;
+        }
+
+        let (keyword, value) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| err(format!("unexpected {line:?}")))?;
+        let value = unescape_po(value.trim()).map_err(err)?;
+
+        let keyword = match keyword {
+            "msgctxt" => "msgctxt",
+            "msgid" => "msgid",
+            "msgstr" => "msgstr",
+            "msgid_plural" | "msgstr[0]" => {
+                return Err(err("plural forms aren't supported".to_string()));
+            }
+            other => return Err(err(format!("unknown keyword {other:?}"))),
+        };
+
+        // A context or id after a msgstr starts the next entry
+        let starts_entry = match &current {
+            Some((_, field)) => keyword != "msgstr" && *field == "msgstr",
+            None => true,
+        };
+
+        if starts_entry {
+            if let Some((entry, _)) = current.take() {
+                entries.push(entry);
+            }
+            current = Some((Entry::default(), keyword));
+        }
+
+        let (entry, field) = current.as_mut().unwrap();
+        *field = keyword;
+
+        match keyword {
+            "msgctxt" => entry.context = Some(value),
+            "msgid" => entry.id = value,
+            _ => entry.string = value,
+        }
+    }
+
+    if let Some((entry, _)) = current {
+        entries.push(entry);
+    }
+
+    let mut language = None;
+    let mut source_language = None;
+
+    // The header is the entry without a context and an empty msgid
+    for entry in entries
+        .iter()
+        .filter(|v| v.context.is_none() && v.id.is_empty())
+    {
+        for line in entry.string.lines() {
+            match line.split_once(':') {
+                Some(("Language", v)) => language = Some(v.trim().to_string()),
+                Some(("X-Source-Language", v)) => source_language = Some(v.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let language = language.ok_or("missing \"Language\" in the header")?;
+    let source_language = source_language.unwrap_or(DEFAULT_PRIMARY_LANGUAGE.to_string());
+
+    let mut rows = vec![];
+    let mut seen = HashSet::new();
+
+    for entry in entries {
+        let Some(context) = entry.context else {
+            continue;
+        };
+
+        let (project, key) = context
+            .split_once(PO_CONTEXT_SEPARATOR)
+            .ok_or_else(|| format!("expected msgctxt {context:?} to look like \"project|key\""))?;
+
+        if !seen.insert(context.clone()) {
+            return Err(format!("{context:?} is translated more than once"));
+        }
+
+        rows.push(TranslationRow {
+            project: project.to_string(),
+            key: key.to_string(),
+            source: entry.id,
+            translations: HashMap::from([(language.clone(), entry.string)]),
+        });
+    }
+
+    Ok(TranslationTable {
+        source_language,
+        languages: vec![language],
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> TranslationTable {
+        TranslationTable {
+            source_language: "en_US".to_string(),
+            languages: vec!["de_DE".to_string(), "fr_FR".to_string()],
+            rows: vec![
+                TranslationRow {
+                    project: "FooRP".to_string(),
+                    key: "item.apple.name".to_string(),
+                    source: "Apple, \"red\"".to_string(),
+                    translations: HashMap::from([
+                        ("de_DE".to_string(), "Apfel\tmit \\ Stiel".to_string()),
+                        ("fr_FR".to_string(), "Pomme\nrouge".to_string()),
+                    ]),
+                },
+                TranslationRow {
+                    project: "FooRP".to_string(),
+                    key: "item.pear.name".to_string(),
+                    source: "Pear".to_string(),
+                    translations: HashMap::from([("de_DE".to_string(), "Birne".to_string())]),
+                },
+            ],
+        }
+    }
+
+    /// The rows of a table with the translations of `language`, so tables can be compared
+    fn rows(table: &TranslationTable, language: &str) -> Vec<(String, String, String, String)> {
+        table
+            .rows
+            .iter()
+            .map(|v| {
+                (
+                    v.project.clone(),
+                    v.key.clone(),
+                    v.source.clone(),
+                    v.translations.get(language).cloned().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn csv_round_trips() {
+        let table = table();
+        let text = write_csv(&table);
+        let read = read_csv(&text).unwrap();
+
+        assert!(text.starts_with("project,key,en_US,de_DE,fr_FR\r\n"));
+        assert!(text.contains("\"Apple, \"\"red\"\"\""));
+        assert_eq!(read.source_language, "en_US");
+        assert_eq!(read.languages, table.languages);
+        for language in ["de_DE", "fr_FR"] {
+            assert_eq!(rows(&read, language), rows(&table, language));
+        }
+        assert_eq!(write_csv(&read), text);
+    }
+
+    #[test]
+    fn rejects_malformed_csv() {
+        assert!(read_csv("").is_err());
+        assert!(read_csv("key,project,en_US\r\n").is_err());
+        assert_eq!(
+            read_csv("project,key,en_US\r\nFooRP,a\r\n")
+                .err()
+                .as_deref(),
+            Some("row 2 has less than 3 columns")
+        );
+        assert!(parse_csv("a,\"b\nc")
+            .unwrap_err()
+            .starts_with("unterminated quote"));
+    }
+
+    #[test]
+    fn po_round_trips() {
+        let table = table();
+        let text = write_po(&table, Some("de_DE"));
+        let read = read_po(&text).unwrap();
+
+        assert!(text.contains("msgctxt \"FooRP|item.apple.name\"\n"));
+        assert!(text.contains("msgstr \"Apfel\\tmit \\\\ Stiel\"\n"));
+        assert_eq!(read.source_language, "en_US");
+        assert_eq!(read.languages, ["de_DE"]);
+        assert_eq!(rows(&read, "de_DE"), rows(&table, "de_DE"));
+        assert_eq!(write_po(&read, Some("de_DE")), text);
+    }
+
+    #[test]
+    fn reads_multiline_po_strings() {
+        let text = "msgid \"\"\nmsgstr \"\"\n\"Language: de_DE\\n\"\n\nmsgctxt \"FooRP|a\"\nmsgid \"\"\n\"Hello \"\n\"World\"\nmsgstr \"Hallo Welt\"\n";
+        let read = read_po(text).unwrap();
+
+        assert_eq!(
+            rows(&read, "de_DE"),
+            [(
+                "FooRP".to_string(),
+                "a".to_string(),
+                "Hello World".to_string(),
+                "Hallo Welt".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_po() {
+        let table = table();
+        assert_eq!(
+            read_po(&write_po(&table, None)).err().as_deref(),
+            Some("missing \"Language\" in the header")
+        );
+
+        let header = "msgid \"\"\nmsgstr \"Language: de_DE\\n\"\n";
+        for (text, expected) in [
+            ("msgid \"a\"\nmsgid_plural \"b\"\n", "plural forms aren't supported"),
+            ("msgid \"a\\x\"\n", "unknown escape sequence \\x"),
+            ("msgid a\n", "expected a quoted string"),
+            ("msgctxt \"a\"\nmsgid \"a\"\nmsgstr \"\"\n", "to look like \"project|key\""),
+            (
+                "msgctxt \"P|a\"\nmsgid \"a\"\nmsgstr \"\"\nmsgctxt \"P|a\"\nmsgid \"a\"\nmsgstr \"\"\n",
+                "is translated more than once",
+            ),
+        ] {
+            let err = read_po(&format!("{header}{text}")).err().unwrap_or_default();
+            assert!(err.contains(expected), "{expected:?} not in {err:?}");
+        }
+    }
+
+    #[test]
+    fn merges_into_lang_files() {
+        let source = lang::parse("## Fruits\na=Apple\t## red\nb=Pear\nc=Cherry\n");
+        let translations = HashMap::from([("a", "Apfel"), ("b", "Birne"), ("c", "Kirsche")]);
+
+        assert_eq!(
+            merge(
+                None,
+                &source,
+                &HashMap::from([("a", "Apfel"), ("c", "Kirsche")])
+            ),
+            "## Fruits\na=Apfel\t## red\nc=Kirsche\n"
+        );
+        assert_eq!(
+            merge(Some("## Mine\nc=Alt\na=Alt\n"), &source, &translations),
+            "## Mine\nc=Kirsche\na=Apfel\nb=Birne\n"
+        );
+    }
+}
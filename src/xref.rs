@@ -0,0 +1,845 @@
+use crate::config::StrixConfigPackType;
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::json::JsonPath;
+use crate::lint::{
+    sound_references, texture_references, LintContext, LintFile, LintRule, SOUND_EXTENSIONS,
+    TEXTURE_EXTENSIONS,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Vanilla definitions that are commonly referenced by custom content
+const VANILLA_IDENTIFIERS: &[&str] = &[
+    "controller.render.default",
+    "controller.render.item_default",
+    "controller.render.armor",
+    "controller.render.bow",
+    "geometry.humanoid",
+    "geometry.humanoid.custom",
+    "geometry.humanoid.customSlim",
+    "geometry.item_sprite",
+];
+
+/// Texture folders which are used by the engine itself instead of being referenced by content
+const ENGINE_TEXTURE_FOLDERS: &[&str] = &[
+    "textures/ui/",
+    "textures/gui/",
+    "textures/environment/",
+    "textures/colormap/",
+    "textures/misc/",
+    "textures/map/",
+    "textures/painting/",
+    "textures/persona_thumbnails/",
+];
+
+/// Every asset and identifier defined across the projects
+#[derive(Default)]
+pub struct AssetIndex {
+    pub geometries: HashSet<String>,
+    pub render_controllers: HashSet<String>,
+    pub animations: HashSet<String>,
+    pub particles: HashSet<String>,
+    pub item_textures: HashSet<String>,
+    pub terrain_textures: HashSet<String>,
+    pub items: HashSet<String>,
+}
+
+fn definition_keys<'a>(json: &'a Value, field: &str) -> impl Iterator<Item = &'a String> {
+    json.get(field)
+        .and_then(Value::as_object)
+        .into_iter()
+        .flat_map(|v| v.keys())
+}
+
+impl AssetIndex {
+    pub fn build(ctx: &LintContext) -> Self {
+        let mut index = Self::default();
+
+        for file in &ctx.files {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            match file.pack_type {
+                StrixConfigPackType::Resource => {
+                    if file.is_in("models") {
+                        index.geometries.extend(geometry_identifiers(json));
+                    }
+                    if file.is_in("render_controllers") {
+                        index
+                            .render_controllers
+                            .extend(definition_keys(json, "render_controllers").cloned());
+                    }
+                    if file.is_in("particles") {
+                        if let Some(id) = json
+                            .pointer("/particle_effect/description/identifier")
+                            .and_then(Value::as_str)
+                        {
+                            index.particles.insert(id.to_string());
+                        }
+                    }
+                    match file.relative.as_str() {
+                        "textures/item_texture.json" => index
+                            .item_textures
+                            .extend(definition_keys(json, "texture_data").cloned()),
+                        "textures/terrain_texture.json" => index
+                            .terrain_textures
+                            .extend(definition_keys(json, "texture_data").cloned()),
+                        _ => {}
+                    }
+                }
+                StrixConfigPackType::Behaviour if file.is_in("items") => {
+                    if let Some(id) = json
+                        .pointer("/minecraft:item/description/identifier")
+                        .and_then(Value::as_str)
+                    {
+                        index.items.insert(id.to_string());
+                    }
+                }
+                _ => {}
+            }
+
+            if file.is_in("animations") {
+                index
+                    .animations
+                    .extend(definition_keys(json, "animations").cloned());
+            }
+            if file.is_in("animation_controllers") {
+                index
+                    .animations
+                    .extend(definition_keys(json, "animation_controllers").cloned());
+            }
+        }
+
+        index
+    }
+}
+
+/// Geometry identifiers of both the `1.12.0` and the legacy `1.8.0` model format
+fn geometry_identifiers(json: &Value) -> Vec<String> {
+    if let Some(geometries) = json.get("minecraft:geometry").and_then(Value::as_array) {
+        return geometries
+            .iter()
+            .filter_map(|v| v.pointer("/description/identifier")?.as_str())
+            .map(str::to_string)
+            .collect();
+    }
+
+    json.as_object()
+        .into_iter()
+        .flat_map(|v| v.keys())
+        .filter(|v| v.starts_with("geometry."))
+        // Legacy geometry may inherit from another one with `geometry.child:geometry.parent`
+        .map(|v| v.split(':').next().unwrap_or(v).to_string())
+        .collect()
+}
+
+struct Reference<'a> {
+    file: &'a LintFile,
+    path: JsonPath,
+    kind: &'static str,
+    target: String,
+}
+
+/// References may name the file, like `textures/blocks/foo.png`, which Bedrock resolves too
+fn without_extension<'a>(reference: &'a str, extensions: &[&str]) -> &'a str {
+    match reference.rsplit_once('.') {
+        Some((stem, extension)) if extensions.contains(&extension) => stem,
+        _ => reference,
+    }
+}
+
+fn is_vanilla(target: &str) -> bool {
+    target.starts_with("minecraft:") || VANILLA_IDENTIFIERS.contains(&target)
+}
+
+/// Collects all identifier references, file references are covered by `missing-texture` and `missing-sound`
+fn references<'a>(ctx: &'a LintContext, index: &AssetIndex) -> Vec<(Reference<'a>, bool)> {
+    let mut references = vec![];
+
+    let mut push =
+        |file: &'a LintFile, path: JsonPath, kind: &'static str, target: &str, found: bool| {
+            references.push((
+                Reference {
+                    file,
+                    path,
+                    kind,
+                    target: target.to_string(),
+                },
+                found || is_vanilla(target),
+            ))
+        };
+
+    for file in &ctx.files {
+        let Some(json) = &file.json else {
+            continue;
+        };
+
+        // Client entities and attachables
+        if file.pack_type == StrixConfigPackType::Resource
+            && (file.is_in("entity") || file.is_in("attachables"))
+        {
+            let Some((key, definition)) = json
+                .as_object()
+                .and_then(|v| v.iter().find(|(k, _)| k.starts_with("minecraft:")))
+            else {
+                continue;
+            };
+            let Some(description) = definition.get("description") else {
+                continue;
+            };
+            let root = JsonPath::default().key(key).key("description");
+
+            for (name, geometry) in description
+                .get("geometry")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(geometry) = geometry.as_str() {
+                    push(
+                        file,
+                        root.key("geometry").key(name),
+                        "Geometry",
+                        geometry,
+                        index.geometries.contains(geometry),
+                    );
+                }
+            }
+
+            for (name, animation) in description
+                .get("animations")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(animation) = animation.as_str() {
+                    push(
+                        file,
+                        root.key("animations").key(name),
+                        "Animation",
+                        animation,
+                        index.animations.contains(animation),
+                    );
+                }
+            }
+
+            for (name, particle) in description
+                .get("particle_effects")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(particle) = particle.as_str() {
+                    push(
+                        file,
+                        root.key("particle_effects").key(name),
+                        "Particle",
+                        particle,
+                        index.particles.contains(particle),
+                    );
+                }
+            }
+
+            if let Some(controllers) = description
+                .get("render_controllers")
+                .and_then(Value::as_array)
+            {
+                for (i, controller) in controllers.iter().enumerate() {
+                    // Conditional controllers are written as `{ "controller.render.x": "query..." }`
+                    let (path, controller) = match controller {
+                        Value::String(v) => (root.key("render_controllers").index(i), v.as_str()),
+                        Value::Object(v) => match v.keys().next() {
+                            Some(k) => (root.key("render_controllers").index(i), k.as_str()),
+                            None => continue,
+                        },
+                        _ => continue,
+                    };
+
+                    push(
+                        file,
+                        path,
+                        "Render controller",
+                        controller,
+                        index.render_controllers.contains(controller),
+                    );
+                }
+            }
+
+            if let Some(texture) = description
+                .pointer("/spawn_egg/texture")
+                .and_then(Value::as_str)
+            {
+                push(
+                    file,
+                    root.key("spawn_egg").key("texture"),
+                    "Item texture",
+                    texture,
+                    index.item_textures.contains(texture),
+                );
+            }
+
+            if let Some(item) = description.get("item").and_then(Value::as_object) {
+                for item_id in item.keys() {
+                    push(
+                        file,
+                        root.key("item").key(item_id),
+                        "Item",
+                        item_id,
+                        index.items.contains(item_id),
+                    );
+                }
+            }
+        }
+
+        // `blocks.json` points at short names from `terrain_texture.json`
+        if file.pack_type == StrixConfigPackType::Resource && file.relative == "blocks.json" {
+            for (block, entry) in json.as_object().into_iter().flatten() {
+                let path = JsonPath::default().key(block).key("textures");
+
+                match entry.get("textures") {
+                    Some(Value::String(v)) => push(
+                        file,
+                        path,
+                        "Terrain texture",
+                        v,
+                        index.terrain_textures.contains(v),
+                    ),
+                    Some(Value::Object(faces)) => {
+                        for (face, v) in faces {
+                            if let Some(v) = v.as_str() {
+                                push(
+                                    file,
+                                    path.key(face),
+                                    "Terrain texture",
+                                    v,
+                                    index.terrain_textures.contains(v),
+                                );
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if file.pack_type == StrixConfigPackType::Behaviour && file.is_in("blocks") {
+            let components = JsonPath::default().key("minecraft:block").key("components");
+
+            if let Some(instances) = json
+                .pointer("/minecraft:block/components/minecraft:material_instances")
+                .and_then(Value::as_object)
+            {
+                for (face, instance) in instances {
+                    if let Some(texture) = instance.get("texture").and_then(Value::as_str) {
+                        push(
+                            file,
+                            components
+                                .key("minecraft:material_instances")
+                                .key(face)
+                                .key("texture"),
+                            "Terrain texture",
+                            texture,
+                            index.terrain_textures.contains(texture),
+                        );
+                    }
+                }
+            }
+
+            let geometry = match json.pointer("/minecraft:block/components/minecraft:geometry") {
+                Some(Value::String(v)) => Some((components.key("minecraft:geometry"), v.as_str())),
+                Some(Value::Object(v)) => v
+                    .get("identifier")
+                    .and_then(Value::as_str)
+                    .map(|v| (components.key("minecraft:geometry").key("identifier"), v)),
+                _ => None,
+            };
+
+            if let Some((path, geometry)) = geometry {
+                push(
+                    file,
+                    path,
+                    "Geometry",
+                    geometry,
+                    index.geometries.contains(geometry),
+                );
+            }
+        }
+
+        if file.pack_type == StrixConfigPackType::Behaviour && file.is_in("items") {
+            let icon = match json.pointer("/minecraft:item/components/minecraft:icon") {
+                Some(Value::String(v)) => Some((JsonPath::default(), v.as_str())),
+                Some(Value::Object(v)) => match v.get("texture") {
+                    Some(Value::String(t)) => {
+                        Some((JsonPath::default().key("texture"), t.as_str()))
+                    }
+                    _ => v
+                        .get("textures")
+                        .and_then(|v| v.get("default"))
+                        .and_then(Value::as_str)
+                        .map(|t| (JsonPath::default().key("textures").key("default"), t)),
+                },
+                _ => None,
+            };
+
+            if let Some((suffix, icon)) = icon {
+                let mut path = JsonPath::default()
+                    .key("minecraft:item")
+                    .key("components")
+                    .key("minecraft:icon");
+                path.0.extend(suffix.0);
+
+                push(
+                    file,
+                    path,
+                    "Item texture",
+                    icon,
+                    index.item_textures.contains(icon),
+                );
+            }
+        }
+
+        if file.pack_type == StrixConfigPackType::Behaviour && file.is_in("entities") {
+            for (name, animation) in json
+                .pointer("/minecraft:entity/description/animations")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(animation) = animation.as_str() {
+                    push(
+                        file,
+                        JsonPath::default()
+                            .key("minecraft:entity")
+                            .key("description")
+                            .key("animations")
+                            .key(name),
+                        "Animation",
+                        animation,
+                        index.animations.contains(animation),
+                    );
+                }
+            }
+        }
+    }
+
+    references
+}
+
+/// Finds `Geometry.x`, `Texture.x` and `Material.x` references inside a render controller
+fn render_controller_slots(controller: &Value) -> Vec<(&'static str, String)> {
+    let mut slots = vec![];
+
+    fn visit(value: &Value, slots: &mut Vec<(&'static str, String)>) {
+        match value {
+            Value::String(text) => {
+                for (prefix, kind) in [
+                    ("geometry.", "geometry"),
+                    ("texture.", "textures"),
+                    ("material.", "materials"),
+                ] {
+                    let lower = text.to_ascii_lowercase();
+                    let mut rest = lower.as_str();
+
+                    while let Some(start) = rest.find(prefix) {
+                        let before = &rest[..start];
+                        rest = &rest[start + prefix.len()..];
+
+                        // Skip things like `array.geometry.x` or `query.texture.x`
+                        if before
+                            .ends_with(|c: char| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                        {
+                            continue;
+                        }
+
+                        let name: String = rest
+                            .chars()
+                            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                            .collect();
+
+                        if !name.is_empty() {
+                            slots.push((kind, name));
+                        }
+                    }
+                }
+            }
+            Value::Array(v) => v.iter().for_each(|v| visit(v, slots)),
+            Value::Object(v) => v.iter().for_each(|(k, v)| {
+                visit(&Value::String(k.clone()), slots);
+                visit(v, slots);
+            }),
+            _ => {}
+        }
+    }
+
+    visit(controller, &mut slots);
+    slots.sort();
+    slots.dedup();
+    slots
+}
+
+pub struct UnresolvedReference;
+
+impl LintRule for UnresolvedReference {
+    fn code(&self) -> &'static str {
+        "unresolved-reference"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let index = AssetIndex::build(ctx);
+        let mut diagnostics: Vec<Diagnostic> = references(ctx, &index)
+            .into_iter()
+            .filter(|(_, found)| !found)
+            .map(|(reference, _)| {
+                Diagnostic::new(
+                    self.code(),
+                    &reference.file.path,
+                    reference.file.range(&reference.path),
+                    format!(
+                        "{} {:?} isn't defined in any project",
+                        reference.kind, reference.target
+                    ),
+                )
+            })
+            .collect();
+
+        // Every slot a render controller uses has to be defined by the entities using it
+        let controllers: Vec<(&String, &Value)> = ctx
+            .files_of(StrixConfigPackType::Resource)
+            .filter(|v| v.is_in("render_controllers"))
+            .filter_map(|v| v.json.as_ref()?.get("render_controllers")?.as_object())
+            .flatten()
+            .collect();
+
+        for file in ctx
+            .files_of(StrixConfigPackType::Resource)
+            .filter(|v| v.is_in("entity") || v.is_in("attachables"))
+        {
+            let Some((key, description)) = file.json.as_ref().and_then(|json| {
+                let (key, definition) = json
+                    .as_object()?
+                    .iter()
+                    .find(|(k, _)| k.starts_with("minecraft:"))?;
+                Some((key, definition.get("description")?))
+            }) else {
+                continue;
+            };
+
+            let used: Vec<&str> = description
+                .get("render_controllers")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|v| match v {
+                    Value::String(v) => Some(v.as_str()),
+                    Value::Object(v) => v.keys().next().map(String::as_str),
+                    _ => None,
+                })
+                .collect();
+
+            for (name, controller) in &controllers {
+                if !used.contains(&name.as_str()) {
+                    continue;
+                }
+
+                for (kind, slot) in render_controller_slots(controller) {
+                    let defined = description
+                        .get(kind)
+                        .and_then(Value::as_object)
+                        .is_some_and(|v| v.keys().any(|k| k.eq_ignore_ascii_case(&slot)));
+
+                    if !defined {
+                        diagnostics.push(Diagnostic::new(
+                            self.code(),
+                            &file.path,
+                            file.range(&JsonPath::default().key(key).key("description")),
+                            format!(
+                                "Render controller {name:?} uses {kind} {slot:?}, which isn't defined here"
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+pub struct OrphanedAsset;
+
+impl LintRule for OrphanedAsset {
+    fn code(&self) -> &'static str {
+        "orphaned-asset"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, ctx: &LintContext) -> Vec<Diagnostic> {
+        let mut textures: HashSet<String> = texture_references(ctx)
+            .into_iter()
+            .map(|(_, _, v)| without_extension(v, TEXTURE_EXTENSIONS).to_string())
+            .collect();
+
+        for file in ctx.files_of(StrixConfigPackType::Resource) {
+            let Some(json) = &file.json else {
+                continue;
+            };
+
+            if let Some(texture) = json
+                .pointer("/particle_effect/description/basic_render_parameters/texture")
+                .and_then(Value::as_str)
+            {
+                textures.insert(without_extension(texture, TEXTURE_EXTENSIONS).to_string());
+            }
+
+            if file.relative == "textures/flipbook_textures.json" {
+                for entry in json.as_array().into_iter().flatten() {
+                    if let Some(texture) = entry.get("flipbook_texture").and_then(Value::as_str) {
+                        textures.insert(without_extension(texture, TEXTURE_EXTENSIONS).to_string());
+                    }
+                }
+            }
+        }
+
+        let sounds: HashSet<&str> = sound_references(ctx)
+            .into_iter()
+            .map(|(_, _, v)| without_extension(v, SOUND_EXTENSIONS))
+            .collect();
+
+        let index = AssetIndex::build(ctx);
+        let used_geometries: HashSet<String> = references(ctx, &index)
+            .into_iter()
+            .filter(|(v, _)| v.kind == "Geometry")
+            .map(|(v, _)| v.target)
+            .collect();
+
+        let mut diagnostics = vec![];
+
+        for file in ctx.files_of(StrixConfigPackType::Resource) {
+            // Flipbook frames are used if their strip is
+            let stem = ctx.flipbook_of(&file.relative).unwrap_or_else(|| {
+                file.relative
+                    .rsplit_once('.')
+                    .map(|(v, _)| v)
+                    .unwrap_or(&file.relative)
+            });
+            let extension = file.extension().unwrap_or_default();
+
+            let orphaned = if file.is_in("textures")
+                && TEXTURE_EXTENSIONS.contains(&extension)
+                && !ENGINE_TEXTURE_FOLDERS
+                    .iter()
+                    .any(|v| file.relative.starts_with(v))
+            {
+                !textures.contains(stem)
+            } else if file.is_in("sounds") && SOUND_EXTENSIONS.contains(&extension) {
+                !sounds.contains(stem)
+            } else if file.is_in("models") {
+                let ids = file
+                    .json
+                    .as_ref()
+                    .map(geometry_identifiers)
+                    .unwrap_or_default();
+                !ids.is_empty() && ids.iter().all(|v| !used_geometries.contains(v))
+            } else {
+                false
+            };
+
+            if orphaned {
+                diagnostics.push(Diagnostic::new(
+                    self.code(),
+                    &file.path,
+                    None,
+                    format!("{:?} isn't referenced by anything", file.relative),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{StrixConfig, StrixFlipbookConfig};
+    use StrixConfigPackType::{Behaviour, Resource};
+
+    const GEOMETRY: &str = r#"{ "format_version": "1.12.0", "minecraft:geometry": [{ "description": { "identifier": "geometry.foo" } }] }"#;
+
+    fn messages(rule: &dyn LintRule, ctx: &LintContext) -> Vec<String> {
+        let mut messages: Vec<String> = rule.check(ctx).into_iter().map(|v| v.message).collect();
+        messages.sort();
+        messages
+    }
+
+    #[test]
+    fn strips_texture_and_sound_extensions() {
+        let ctx = LintContext::from_files(
+            StrixConfig::default(),
+            &[
+                (
+                    Resource,
+                    "textures/terrain_texture.json",
+                    r#"{ "texture_data": { "foo": { "textures": "textures/blocks/foo.png" } } }"#,
+                ),
+                (Resource, "textures/blocks/foo.png", ""),
+                (
+                    Resource,
+                    "sounds/sound_definitions.json",
+                    r#"{ "sound_definitions": { "foo.step": { "sounds": ["sounds/foo.ogg", { "name": "sounds/bar" }] } } }"#,
+                ),
+                (Resource, "sounds/foo.ogg", ""),
+                (Resource, "sounds/bar.ogg", ""),
+                (Resource, "sounds/baz.ogg", ""),
+            ],
+        );
+
+        assert_eq!(
+            without_extension("textures/foo.png", TEXTURE_EXTENSIONS),
+            "textures/foo"
+        );
+        assert_eq!(
+            without_extension("textures/foo.v2", TEXTURE_EXTENSIONS),
+            "textures/foo.v2"
+        );
+        assert_eq!(
+            messages(&OrphanedAsset, &ctx),
+            ["\"sounds/baz.ogg\" isn't referenced by anything"]
+        );
+    }
+
+    #[test]
+    fn checks_render_controller_slots() {
+        let ctx = LintContext::from_files(
+            StrixConfig::default(),
+            &[
+                (
+                    Resource,
+                    "render_controllers/foo.json",
+                    r#"{ "render_controllers": { "controller.render.foo": {
+                        "geometry": "Geometry.default",
+                        "materials": [{ "*": "Material.default" }],
+                        "textures": ["Array.skins[query.variant]", "Texture.overlay"]
+                    } } }"#,
+                ),
+                (Resource, "models/entity/foo.geo.json", GEOMETRY),
+                (
+                    Resource,
+                    "entity/foo.json",
+                    r#"{ "minecraft:client_entity": { "description": {
+                        "geometry": { "default": "geometry.foo" },
+                        "materials": { "default": "entity_alphatest" },
+                        "textures": { "default": "textures/entity/foo" },
+                        "render_controllers": ["controller.render.foo"]
+                    } } }"#,
+                ),
+            ],
+        );
+
+        assert_eq!(
+            render_controller_slots(&ctx.files[0].json.as_ref().unwrap()["render_controllers"]),
+            [
+                ("geometry", String::from("default")),
+                ("materials", String::from("default")),
+                ("textures", String::from("overlay")),
+            ]
+        );
+        assert_eq!(
+            messages(&UnresolvedReference, &ctx),
+            ["Render controller \"controller.render.foo\" uses textures \"overlay\", which isn't defined here"]
+        );
+    }
+
+    #[test]
+    fn resolves_attachable_references() {
+        let ctx = LintContext::from_files(
+            StrixConfig::default(),
+            &[
+                (Resource, "models/entity/foo.geo.json", GEOMETRY),
+                (
+                    Resource,
+                    "attachables/foo.json",
+                    r#"{ "minecraft:attachable": { "description": {
+                        "identifier": "foo:sword",
+                        "item": { "foo:sword": "query.is_owner_identifier_any('minecraft:player')" },
+                        "geometry": { "default": "geometry.foo", "broken": "geometry.missing" },
+                        "render_controllers": ["controller.render.item_default"]
+                    } } }"#,
+                ),
+                (
+                    Behaviour,
+                    "items/foo.json",
+                    r#"{ "minecraft:item": { "description": { "identifier": "foo:sword" } } }"#,
+                ),
+            ],
+        );
+
+        assert_eq!(
+            messages(&UnresolvedReference, &ctx),
+            ["Geometry \"geometry.missing\" isn't defined in any project"]
+        );
+    }
+
+    #[test]
+    fn finds_orphaned_assets() {
+        let ctx = LintContext::from_files(
+            StrixConfig::default(),
+            &[
+                (Resource, "textures/blocks/unused.png", ""),
+                (Resource, "textures/ui/button.png", ""),
+                (Resource, "models/entity/foo.geo.json", GEOMETRY),
+                (
+                    Resource,
+                    "particles/foo.json",
+                    r#"{ "particle_effect": { "description": { "identifier": "foo:spark", "basic_render_parameters": { "texture": "textures/particle/spark" } } } }"#,
+                ),
+                (Resource, "textures/particle/spark.png", ""),
+            ],
+        );
+
+        assert_eq!(
+            messages(&OrphanedAsset, &ctx),
+            [
+                "\"models/entity/foo.geo.json\" isn't referenced by anything",
+                "\"textures/blocks/unused.png\" isn't referenced by anything",
+            ]
+        );
+    }
+
+    #[test]
+    fn flipbook_frames_are_used_through_their_strip() {
+        let mut config = StrixConfig::default();
+        for profile in config.build.profiles.values_mut() {
+            profile.flipbooks = vec![StrixFlipbookConfig {
+                frames: String::from("textures/blocks/fire/"),
+                atlas_tile: None,
+                ticks_per_frame: 2,
+            }];
+        }
+
+        let mut files = vec![
+            (Resource, "textures/blocks/fire/0.png", ""),
+            (Resource, "textures/blocks/fire/1.png", ""),
+        ];
+
+        let ctx = LintContext::from_files(config.clone(), &files);
+        assert_eq!(messages(&OrphanedAsset, &ctx).len(), 2);
+
+        files.push((
+            Resource,
+            "textures/terrain_texture.json",
+            r#"{ "texture_data": { "fire": { "textures": "textures/blocks/fire.png" } } }"#,
+        ));
+        let ctx = LintContext::from_files(config, &files);
+        assert!(messages(&OrphanedAsset, &ctx).is_empty());
+        assert!(ctx.asset_exists(Resource, "textures/blocks/fire", TEXTURE_EXTENSIONS));
+    }
+}